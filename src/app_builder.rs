@@ -3,8 +3,14 @@ use crate::configuration::Language;
 use crate::configuration::ServiceType;
 use crate::configuration::StorageType;
 use crate::domain::Candidate;
+use crate::domain::Phase;
+use crate::domain::Schedule;
 use crate::domain::VotingMachine;
+use crate::electoral_roll::load_roll;
+use crate::proxies::{load_proxies, ProxyRegistry};
+use crate::encryption::{ElectionKey, HomomorphicTally, PublicKey};
 use crate::interfaces::lexicon::Lexicon;
+use crate::results::ResultsPublisher;
 use crate::interfaces::lexicons::english::ENGLISH;
 use crate::interfaces::lexicons::french::FRENCH;
 use crate::services::service::Service;
@@ -13,8 +19,11 @@ use crate::services::tcp::TcpService;
 use crate::services::udp::UdpService;
 use crate::storage::Storage;
 use crate::storages::file::FileStore;
+use crate::storages::journal::JournalStore;
+use crate::storages::sqlite::SqliteStore;
 use crate::storages::memory::MemoryStore;
 use crate::use_cases::VotingController;
+use crate::voting_codes::issue_codes;
 
 
 fn create_voting_machine(configuration: &Configuration) -> VotingMachine {
@@ -25,21 +34,86 @@ fn create_voting_machine(configuration: &Configuration) -> VotingMachine {
     }
 
     VotingMachine::new(candidates)
+        .with_seats(configuration.seats as usize)
+        .with_secret(configuration.secret)
+        .with_revote(configuration.revote)
+        .with_proxies(ProxyRegistry::new(configuration.proxy_limit))
 }
 
-pub async fn handle_lines<Store: Storage+Sync+Send, Serv: Service<Store>>(config: Configuration) -> anyhow::Result<()> {
+pub async fn handle_lines<Store: Storage+Sync+Send+Clone+'static, Serv: Service<Store>>(config: Configuration) -> anyhow::Result<()> {
 
-    let voting_machine: VotingMachine = create_voting_machine(&config);
-    let lexicon: Lexicon = match config.language {
-        Language::FR => {
-           FRENCH
-        },
-        Language::EN => {
-            ENGLISH
+    let roll = match &config.roll {
+        Some(path) => Some(load_roll(path).await?),
+        None => None,
+    };
+    let encryption = match config.encrypted {
+        true => {
+            let public_key = PublicKey::load(&config.tally_public_key).await?;
+            let voting_machine = create_voting_machine(&config);
+            Some(HomomorphicTally::new(public_key, &voting_machine.get_candidates()))
+        }
+        false => None,
+    };
+    // Un scrutin repris garde ses codes : de nouveaux codes ne sont émis que pour un nouveau scrutin.
+    let resumed = !config.reset && Store::exists().await?;
+    let codes = match (&config.issue_codes, &roll) {
+        // Un code de vote ne dit pas qui vote : son poids serait inconnu.
+        (Some(_), Some(roll)) if roll.is_weighted() => anyhow::bail!("les codes de vote ne sont pas disponibles avec une liste électorale pondérée"),
+        (Some(_), Some(_)) if resumed => None,
+        (Some(path), Some(roll)) => Some(issue_codes(roll, path).await?),
+        _ => None,
+    };
+    let mut voting_machine: VotingMachine = create_voting_machine(&config)
+        .with_roll(roll)
+        .with_codes(codes)
+        .with_encryption(encryption);
+    voting_machine
+        .check_settings()
+        .map_err(|conflict| anyhow::anyhow!("réglages incompatibles : {:?}", conflict))?;
+    if let Some(path) = &config.proxies {
+        for (proxy, principal) in load_proxies(path).await? {
+            voting_machine
+                .register_proxy(proxy.clone(), principal.clone())
+                .map_err(|rejection| anyhow::anyhow!("procuration de {} à {} refusée : {:?}", principal.0, proxy.0, rejection))?;
+        }
+    }
+    let lexicon: Lexicon = select_lexicon(config.language);
+    let store = match config.reset {
+        true => Store::reset(voting_machine).await?,
+        false => Store::new(voting_machine).await?,
+    };
+    if resumed {
+        if config.issue_codes.is_some() != store.read(|voting_machine| voting_machine.get_codes().is_some()).await? {
+            anyhow::bail!("le scrutin enregistré ne correspond pas à la configuration (codes de vote), relancer avec --reset pour repartir d'un scrutin vierge");
         }
+        println!("{}", lexicon.election_resumed);
+    }
+    let schedule = Schedule {
+        opens_at: config.opens_at,
+        closes_at: config.closes_at,
     };
-    let store = Store::new(voting_machine).await?;
-    let controller  = VotingController::new(store);
+    let mut controller  = VotingController::new(store)
+        .with_method(config.method)
+        .with_schedule(schedule)
+        .with_publisher(ResultsPublisher::new(&config.signing_key, &config.results));
+    if let Some(token) = &config.admin_token {
+        controller = controller.with_admin_token(token);
+    }
+
+    let scheduler = controller.clone();
+    let scheduler_lexicon = lexicon.clone();
+    tokio::spawn(async move {
+        if let Err(e) = scheduler.follow_schedule().await {
+            eprintln!("{} : {}", scheduler_lexicon.schedule_error, e);
+            return;
+        }
+        // Clôture programmée : l'empreinte finale du registre est affichée.
+        if let Ok(voting_machine) = scheduler.get_voting_machine().await {
+            if scheduler.get_schedule().closes_at.is_some() && voting_machine.get_phase() == Phase::Closed {
+                println!("{} : {}", scheduler_lexicon.log_head, voting_machine.get_log().head());
+            }
+        }
+    });
 
     let port = config.port.unwrap_or(9999);
     Serv::new(port, lexicon, controller)
@@ -57,10 +131,36 @@ pub async fn run_app(config: Configuration) -> anyhow::Result<()>
         },
         StorageType::Memory => {
             dispatch_service::<MemoryStore>(config).await
+        },
+        StorageType::Journal => {
+            dispatch_service::<JournalStore>(config).await
+        },
+        StorageType::Sqlite => {
+            dispatch_service::<SqliteStore>(config).await
+        }
+    }
+}
+
+/// Lexique de la langue choisie, pour le serveur comme pour les outils.
+pub fn select_lexicon(language: Language) -> Lexicon {
+    match language {
+        Language::FR => {
+           FRENCH
+        },
+        Language::EN => {
+            ENGLISH
         }
     }
 }
 
+/// Génère la clé de l'élection d'un scrutin chiffré, avant son ouverture.
+pub async fn generate_tally_key(path: &str, lexicon: &Lexicon) -> anyhow::Result<()> {
+    ElectionKey::generate_files(path).await?;
+    println!("{} : {} ; {} : {}.pub", lexicon.private_key_written, path, lexicon.public_key_written, path);
+
+    Ok(())
+}
+
 async fn dispatch_service<Store: Storage + Send+ Sync+ Clone+ 'static>(config: Configuration)->Result<(), anyhow::Error>
 {
 
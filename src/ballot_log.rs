@@ -0,0 +1,244 @@
+use sha2::{Digest, Sha256};
+
+use crate::domain::{Ballot, Weight};
+
+/// Empreinte qui précède la première entrée du registre.
+pub const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Entrée du registre : le bulletin, le tour où il a été déposé et le chaînage.
+/// Une entrée `revoked` retire de l'urne un bulletin déposé plus tôt.
+/// `receipt` est l'empreinte du code de suivi remis au votant pour ce bulletin.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LogEntry {
+    pub round: usize,
+    pub ballot: Ballot,
+    pub weight: Weight,
+    pub revoked: bool,
+    pub receipt: Option<String>,
+    pub previous: String,
+    pub hash: String,
+}
+
+/// Nombre de bulletins secrets chaînés ensemble, dans l'ordre de l'urne.
+pub const SECRET_BATCH: usize = 10;
+
+/// Bulletin d'un scrutin secret en attente d'être chaîné avec les autres bulletins de son lot.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct StagedBallot {
+    pub round: usize,
+    pub ballot: Ballot,
+    pub weight: Weight,
+    pub receipt: Option<String>,
+}
+
+/// Registre des bulletins déposés, où chaque entrée scelle la précédente.
+/// En scrutin secret, les bulletins attendent d'être chaînés par lots, triés,
+/// pour que l'ordre du registre ne dise pas qui a voté quoi.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct BallotLog {
+    entries: Vec<LogEntry>,
+    staged: Vec<StagedBallot>,
+}
+
+/// Empreinte SHA-256 d'une entrée, calculée sur l'empreinte précédente,
+/// le tour et le bulletin sérialisé en JSON, suivis du poids s'il diffère de 1,
+/// d'une marque pour un retrait et de l'empreinte du code de suivi.
+pub fn hash_entry(previous: &str, round: usize, ballot: &Ballot, weight: Weight, revoked: bool, receipt: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(previous.as_bytes());
+    hasher.update(round.to_string().as_bytes());
+    hasher.update(serde_json::to_vec(ballot).unwrap_or_default());
+    if weight != Weight::default() {
+        hasher.update(format!("weight:{}", weight.0).as_bytes());
+    }
+    if revoked {
+        hasher.update(b"revoked");
+    }
+    if let Some(receipt) = receipt {
+        hasher.update(format!("receipt:{}", receipt).as_bytes());
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+impl BallotLog {
+    pub fn from_entries(entries: Vec<LogEntry>) -> Self {
+        Self { entries, staged: vec![] }
+    }
+
+    pub fn with_staged(mut self, staged: Vec<StagedBallot>) -> Self {
+        self.staged = staged;
+        self
+    }
+
+    pub fn append(&mut self, round: usize, ballot: &Ballot, weight: Weight, receipt: Option<String>) {
+        self.push(round, ballot, weight, false, receipt);
+    }
+
+    /// Le retrait porte l'empreinte du code de suivi du bulletin retiré, qui ne vérifie plus.
+    pub fn revoke(&mut self, round: usize, ballot: &Ballot, weight: Weight, receipt: Option<String>) {
+        self.push(round, ballot, weight, true, receipt);
+    }
+
+    /// Met un bulletin secret en attente, à sa place dans le lot trié ; un lot complet est chaîné.
+    pub fn stage(&mut self, round: usize, ballot: &Ballot, weight: Weight, receipt: Option<String>) {
+        let staged = StagedBallot { round, ballot: ballot.clone(), weight, receipt };
+        let position = self.staged.partition_point(|other| other <= &staged);
+        self.staged.insert(position, staged);
+
+        if self.staged.len() >= SECRET_BATCH {
+            self.seal_staged();
+        }
+    }
+
+    /// Chaîne les bulletins en attente dans l'ordre du lot, à la clôture ou quand le lot est complet.
+    pub fn seal_staged(&mut self) {
+        for staged in std::mem::take(&mut self.staged) {
+            self.push(staged.round, &staged.ballot, staged.weight, false, staged.receipt);
+        }
+    }
+
+    fn push(&mut self, round: usize, ballot: &Ballot, weight: Weight, revoked: bool, receipt: Option<String>) {
+        let previous = self.head().to_string();
+        let hash = hash_entry(&previous, round, ballot, weight, revoked, receipt.as_deref());
+
+        self.entries.push(LogEntry { round, ballot: ballot.clone(), weight, revoked, receipt, previous, hash });
+    }
+
+    /// Tête de la chaîne : l'empreinte de la dernière entrée.
+    pub fn head(&self) -> &str {
+        self.entries.last().map(|entry| entry.hash.as_str()).unwrap_or(GENESIS)
+    }
+
+    pub fn entries(&self) -> &Vec<LogEntry> {
+        &self.entries
+    }
+
+    pub fn staged(&self) -> &Vec<StagedBallot> {
+        &self.staged
+    }
+
+    /// Positions des entrées dont l'empreinte ou le chaînage ne se recalcule pas.
+    pub fn broken_links(&self) -> Vec<usize> {
+        let mut previous = GENESIS;
+        let mut broken = vec![];
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            if entry.previous != previous || entry.hash != hash_entry(&entry.previous, entry.round, &entry.ballot, entry.weight, entry.revoked, entry.receipt.as_deref()) {
+                broken.push(index);
+            }
+            previous = &entry.hash;
+        }
+
+        broken
+    }
+
+    pub fn entries_of_round(&self, round: usize) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter().filter(move |entry| entry.round == round)
+    }
+}
+
+/// Résultat de l'audit du registre : chaînage et scores recalculés.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LogAudit {
+    pub head: String,
+    pub entries: usize,
+    pub broken_links: Vec<usize>,
+    pub mismatched_rounds: Vec<usize>,
+}
+
+impl LogAudit {
+    pub fn is_intact(&self) -> bool {
+        self.broken_links.is_empty() && self.mismatched_rounds.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::Candidate;
+
+    use super::*;
+
+    fn ballot(name: &str) -> Ballot {
+        Ballot::Ranked(vec![Candidate(String::from(name))])
+    }
+
+    #[test]
+    fn test_entries_are_chained() {
+        let mut log = BallotLog::default();
+        log.append(0, &ballot("Louis"), Weight(1), None);
+        log.append(0, &Ballot::Blank, Weight(1), None);
+
+        assert_eq!(log.entries()[0].previous, GENESIS);
+        assert_eq!(log.entries()[1].previous, log.entries()[0].hash);
+        assert_eq!(log.head(), log.entries()[1].hash);
+        assert!(log.broken_links().is_empty());
+    }
+
+    #[test]
+    fn test_tampered_ballot_breaks_the_chain() {
+        let mut log = BallotLog::default();
+        log.append(0, &ballot("Louis"), Weight(1), None);
+        log.append(0, &ballot("Louis"), Weight(1), None);
+
+        let mut entries = log.entries().clone();
+        entries[0].ballot = ballot("Louise");
+
+        assert_eq!(BallotLog::from_entries(entries).broken_links(), vec![0]);
+    }
+
+    #[test]
+    fn test_weight_is_sealed() {
+        let mut log = BallotLog::default();
+        log.append(0, &ballot("Louis"), Weight(250), None);
+
+        let mut entries = log.entries().clone();
+        entries[0].weight = Weight(1000);
+
+        assert_eq!(BallotLog::from_entries(entries).broken_links(), vec![0]);
+    }
+
+    #[test]
+    fn test_staged_ballots_are_chained_in_batch_order() {
+        let mut log = BallotLog::default();
+        log.stage(0, &ballot("Louise"), Weight(1), None);
+        log.stage(0, &ballot("Louis"), Weight(1), None);
+
+        assert!(log.entries().is_empty());
+        assert_eq!(log.staged()[0].ballot, ballot("Louis"));
+
+        for _ in 2..SECRET_BATCH {
+            log.stage(0, &Ballot::Blank, Weight(1), None);
+        }
+
+        assert!(log.staged().is_empty());
+        assert_eq!(log.entries().len(), SECRET_BATCH);
+        assert_eq!(log.entries()[SECRET_BATCH - 2].ballot, ballot("Louis"));
+        assert_eq!(log.entries()[SECRET_BATCH - 1].ballot, ballot("Louise"));
+        assert!(log.broken_links().is_empty());
+    }
+
+    #[test]
+    fn test_receipt_is_sealed() {
+        let mut log = BallotLog::default();
+        log.append(0, &ballot("Louis"), Weight(1), Some(String::from("empreinte")));
+
+        let mut entries = log.entries().clone();
+        entries[0].receipt = Some(String::from("autre"));
+
+        assert_eq!(BallotLog::from_entries(entries).broken_links(), vec![0]);
+    }
+
+    #[test]
+    fn test_revocation_cannot_be_turned_into_a_ballot() {
+        let mut log = BallotLog::default();
+        log.append(0, &ballot("Louis"), Weight(1), None);
+        log.revoke(0, &ballot("Louis"), Weight(1), None);
+
+        let mut entries = log.entries().clone();
+        entries[1].revoked = false;
+
+        assert!(log.broken_links().is_empty());
+        assert_eq!(BallotLog::from_entries(entries).broken_links(), vec![1]);
+    }
+}
@@ -0,0 +1,23 @@
+use c1::app_builder::{generate_tally_key, select_lexicon};
+use c1::configuration::Language;
+use clap::Parser;
+
+/// Génère la clé d'un scrutin chiffré : la clé privée reste chez l'administrateur,
+/// seule la clé publique est donnée au serveur.
+#[derive(Parser)]
+struct Arguments {
+    /// Fichier de la clé privée ; la clé publique est écrite à côté, en `.pub`.
+    #[arg(default_value = "tally.key")]
+    path: String,
+
+    #[arg(short = 'l', long, value_enum, default_value_t = Language::FR)]
+    language: Language,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let arguments = Arguments::parse();
+    generate_tally_key(&arguments.path, &select_lexicon(arguments.language)).await?;
+
+    Ok(())
+}
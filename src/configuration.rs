@@ -1,10 +1,15 @@
+use chrono::{DateTime, Utc};
 use clap::Parser;
 use clap::ValueEnum;
 
+use crate::proxies::DEFAULT_PROXY_LIMIT;
+
 #[derive(Clone,Copy, ValueEnum, Debug)]
 pub enum StorageType {
     File,
     Memory,
+    Journal,
+    Sqlite,
 }
 #[derive(Clone,Copy, ValueEnum, Debug)]
 pub enum Language {
@@ -12,6 +17,14 @@ pub enum Language {
     EN,
 }
 
+#[derive(Clone,Copy, ValueEnum, Debug, PartialEq, Eq, Default)]
+pub enum VotingMethod {
+    #[default]
+    Plurality,
+    Approval,
+    Judgment,
+}
+
 #[derive(Clone,Copy, ValueEnum, Debug)]
 pub enum ServiceType {
     STDIO,
@@ -36,4 +49,70 @@ pub struct Configuration {
 
     #[arg(short = 'p', long, required = false, num_args = 1)]
     pub port: Option<u16>,
+
+    #[arg(short = 'm', long, value_enum, default_value_t = VotingMethod::Plurality)]
+    pub method: VotingMethod,
+
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub seats: u64,
+
+    /// Ouverture automatique du scrutin, au format RFC 3339 (2025-03-01T08:00:00+01:00).
+    #[arg(long, required = false, num_args = 1)]
+    pub opens_at: Option<DateTime<Utc>>,
+
+    /// Clôture automatique du scrutin, au format RFC 3339.
+    #[arg(long, required = false, num_args = 1)]
+    pub closes_at: Option<DateTime<Utc>>,
+
+    /// Liste électorale (JSON ou CSV), avec si besoin le poids de chaque électeur
+    /// (parts, tantièmes) ; sans elle, tout nom est accepté.
+    #[arg(long, required = false, num_args = 1)]
+    pub roll: Option<String>,
+
+    /// Tire un code de vote à usage unique par inscrit et écrit la liste (CSV) dans ce fichier.
+    #[arg(long, required = false, num_args = 1, requires = "roll")]
+    pub issue_codes: Option<String>,
+
+    /// Scrutin secret : les réponses accusent seulement réception du bulletin.
+    #[arg(long)]
+    pub secret: bool,
+
+    /// Changement de vote : un nouveau bulletin remplace le précédent, `revoke` le retire.
+    /// La machine garde le lien entre votant, ou code de vote, et bulletin, d'où
+    /// l'incompatibilité avec le scrutin secret.
+    #[arg(long, conflicts_with = "secret")]
+    pub revote: bool,
+
+    /// Procurations à enregistrer à l'ouverture (CSV : mandataire, mandant).
+    #[arg(long, required = false, num_args = 1, conflicts_with = "issue_codes")]
+    pub proxies: Option<String>,
+
+    /// Nombre maximal de procurations détenues par un même mandataire.
+    #[arg(long, default_value_t = DEFAULT_PROXY_LIMIT)]
+    pub proxy_limit: usize,
+
+    /// Jeton d'administration : les commandes réservées s'écrivent `admin <jeton> <commande>`.
+    #[arg(long, required = false, num_args = 1)]
+    pub admin_token: Option<String>,
+
+    /// Dépouillement chiffré : l'urne ne contient que des bulletins chiffrés.
+    #[arg(long)]
+    pub encrypted: bool,
+
+    /// Clé publique de l'élection, produite par `tally-keygen` : la clé privée reste
+    /// chez l'administrateur, qui la fournit après la clôture avec `reveal <fichier>`.
+    #[arg(long, default_value = "tally.key.pub")]
+    pub tally_public_key: String,
+
+    /// Clé Ed25519 de signature des résultats, générée si elle n'existe pas.
+    #[arg(long, default_value = "election.key")]
+    pub signing_key: String,
+
+    /// Repart d'un scrutin vierge au lieu de reprendre celui déjà enregistré.
+    #[arg(long)]
+    pub reset: bool,
+
+    /// Fichier des résultats signés, écrit à la clôture du scrutin.
+    #[arg(long, default_value = "results.json")]
+    pub results: String,
 }
@@ -1,45 +1,405 @@
 use std::collections::BTreeMap as Map;
 use std::collections::BTreeSet as Set;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::ballot_log::{BallotLog, LogAudit, StagedBallot};
+use crate::encryption::{Ciphertext, ElectionKey, EncryptedScoreboard, HomomorphicTally};
+use crate::proxies::{ProxyRegistry, ProxyRejection};
+use crate::voting_codes::{digest, CodeRejection, CodeSheet};
+use crate::tallies::condorcet::{condorcet, Condorcet};
+use crate::tallies::instant_runoff::{instant_runoff, InstantRunoff};
+use crate::tallies::majority_judgment::{majority_judgment, MajorityJudgment};
+use crate::tallies::positional::{positional, PositionalRule};
+use crate::tallies::stv::{stv, Stv, SurplusTransfer};
 
 #[derive(Ord, PartialEq, Eq, PartialOrd, Clone, Debug)]
 pub struct Voter(pub String);
 
-#[derive(Ord, PartialEq, Eq, PartialOrd, Clone, Debug)]
+#[derive(Ord, PartialEq, Eq, PartialOrd, Clone, Debug, Serialize)]
 pub struct Candidate(pub String);
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
 pub struct Score(pub usize);
 
+/// Mention attribuée à un candidat, de 0 (à rejeter) à MAX_GRADE (excellent).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Grade(pub usize);
+
+pub const MAX_GRADE: usize = 5;
+
+/// Score fractionnaire, utilisé par les méthodes positionnelles (Dowdall...).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Points(pub f64);
+
+/// Émargement d'un votant : en personne ou par l'intermédiaire de son mandataire.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Attendance {
+    InPerson,
+    ByProxy(Voter),
+}
+
 #[derive(Debug, Clone, Eq, PartialEq,)]
-pub struct AttendenceSheet(pub Set<Voter>);
+pub struct AttendenceSheet(pub Map<Voter, Attendance>);
+
+/// Code de suivi remis au votant pour vérifier que son bulletin est dans l'urne.
+#[derive(Ord, PartialEq, Eq, PartialOrd, Clone, Debug)]
+pub struct TrackingCode(pub String);
+
+impl TrackingCode {
+    /// Empreinte SHA-256 du code, seule conservée avec le bulletin.
+    pub fn commitment(&self) -> String {
+        hex::encode(Sha256::digest(self.0.as_bytes()))
+    }
+}
+
+/// Liste électorale : seuls les électeurs inscrits peuvent voter, chacun avec son poids.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ElectoralRoll(pub Map<Voter, Weight>);
+
+impl ElectoralRoll {
+    /// Liste sans pondération : chaque électeur pèse 1.
+    pub fn from_voters(voters: impl IntoIterator<Item = Voter>) -> Self {
+        Self(voters.into_iter().map(|voter| (voter, Weight::default())).collect())
+    }
+
+    pub fn contains(&self, voter: &Voter) -> bool {
+        self.0.contains_key(voter)
+    }
+
+    pub fn weight(&self, voter: &Voter) -> Weight {
+        self.0.get(voter).copied().unwrap_or_default()
+    }
+
+    pub fn is_weighted(&self) -> bool {
+        self.0.values().any(|weight| *weight != Weight::default())
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Turnout {
+    pub voted: Score,
+    pub registered: Score,
+}
 
-#[derive(Debug, Clone,Eq, PartialEq)]
-pub struct Scoreboard {
+impl Turnout {
+    pub fn rate(&self) -> f64 {
+        match self.registered.0 {
+            0 => 0.0,
+            registered => self.voted.0 as f64 / registered as f64,
+        }
+    }
+}
+
+/// Poids d'un électeur (parts, tantièmes) : sa voix compte autant de fois.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Weight(pub usize);
+
+impl Default for Weight {
+    fn default() -> Self {
+        Weight(1)
+    }
+}
+
+#[derive(Clone,Eq, PartialEq, Serialize)]
+pub struct Scoreboard<S = Score> {
+    pub scores: Map<Candidate, S>,
+    pub blank_score: Score,
+    pub invalid_score: Score,
+    /// Décompte sans pondération, une voix par bulletin ; absent des scores positionnels.
+    pub head_counts: Option<HeadCounts>,
+}
+
+/// Les voix brutes ne sont affichées que lorsqu'elles sont tenues.
+impl<S: fmt::Debug> fmt::Debug for Scoreboard<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Scoreboard");
+        debug
+            .field("scores", &self.scores)
+            .field("blank_score", &self.blank_score)
+            .field("invalid_score", &self.invalid_score);
+        if let Some(head_counts) = &self.head_counts {
+            debug.field("head_counts", head_counts);
+        }
+        debug.finish()
+    }
+}
+
+/// Scores bruts, sans tenir compte du poids des électeurs.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct HeadCounts {
     pub scores: Map<Candidate, Score>,
     pub blank_score: Score,
     pub invalid_score: Score,
 }
+
+impl HeadCounts {
+    pub fn new(candidates: &[Candidate]) -> Self {
+        Self {
+            scores: candidates.iter().map(|candidate| (candidate.clone(), Score(0))).collect(),
+            blank_score: Score(0),
+            invalid_score: Score(0),
+        }
+    }
+}
 #[derive(Clone)]
 pub struct BallotPaper {
     pub voter: Voter,
     pub candidate: Option<Candidate>,
 }
+
+/// Bulletin ordonné : les candidats sont classés du préféré au moins préféré.
+#[derive(Clone)]
+pub struct RankedBallotPaper {
+    pub voter: Voter,
+    pub candidates: Vec<Candidate>,
+}
+
+/// Bulletin d'approbation : le votant approuve autant de candidats qu'il le souhaite.
+#[derive(Clone)]
+pub struct ApprovalBallotPaper {
+    pub voter: Voter,
+    pub candidates: Vec<Candidate>,
+}
+
+/// Bulletin de jugement : une mention par candidat. Une mention illisible vaut `None`.
+#[derive(Clone)]
+pub struct GradedBallotPaper {
+    pub voter: Voter,
+    pub grades: Vec<(Candidate, Option<Grade>)>,
+}
+
+/// Bulletin tel qu'il est conservé dans l'urne, sans le nom du votant,
+/// afin de pouvoir recalculer les dépouillements.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize)]
+pub enum Ballot {
+    Blank,
+    Invalid,
+    Ranked(Vec<Candidate>),
+    Approval(Vec<Candidate>),
+    Graded(Map<Candidate, Grade>),
+    Encrypted(Box<EncryptedScoreboard>),
+}
+
+/// Bulletin de l'urne avec le poids de l'électeur qui l'a déposé : chaque
+/// dépouillement le compte autant de fois.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize)]
+pub struct WeightedBallot {
+    pub ballot: Ballot,
+    pub weight: Weight,
+}
+
+impl From<Ballot> for WeightedBallot {
+    fn from(ballot: Ballot) -> Self {
+        Self { ballot, weight: Weight::default() }
+    }
+}
+
 #[derive(Eq, PartialEq, Debug)]
 pub enum VoteOutcome {
     AcceptedVote(Voter, Candidate),
+    AcceptedRanking(Voter, Vec<Candidate>),
+    AcceptedApproval(Voter, Vec<Candidate>),
+    PartiallyInvalidApproval(Voter, Vec<Candidate>, Vec<Candidate>),
+    AcceptedGrades(Voter, Map<Candidate, Grade>),
     BlankVote(Voter),
     InvalidVote(Voter),
     HasAlreadyVoted(Voter),
+    ElectionNotOpen(Voter),
+    ElectionClosed(Voter),
+    NotRegistered(Voter),
+    UnknownCode(Voter),
+    CodeAlreadyUsed(Voter),
+    BallotReceived(Voter),
+    Revoked(Voter),
+    NothingToRevoke(Voter),
+    RevocationNotAllowed(Voter),
+    RevocationRefused(Voter),
+    NoProxy(Voter, Voter),
+    Tracked(Box<VoteOutcome>, TrackingCode),
 }
+
+impl VoteOutcome {
+    /// Vrai si un bulletin, même blanc ou nul, a été déposé dans l'urne.
+    pub fn is_cast(&self) -> bool {
+        matches!(
+            self,
+            VoteOutcome::AcceptedVote(_, _)
+                | VoteOutcome::AcceptedRanking(_, _)
+                | VoteOutcome::AcceptedApproval(_, _)
+                | VoteOutcome::PartiallyInvalidApproval(_, _, _)
+                | VoteOutcome::AcceptedGrades(_, _)
+                | VoteOutcome::BlankVote(_)
+                | VoteOutcome::InvalidVote(_)
+                | VoteOutcome::BallotReceived(_)
+        )
+    }
+
+    pub fn voter(&self) -> &Voter {
+        match self {
+            VoteOutcome::AcceptedVote(voter, _)
+            | VoteOutcome::AcceptedRanking(voter, _)
+            | VoteOutcome::AcceptedApproval(voter, _)
+            | VoteOutcome::PartiallyInvalidApproval(voter, _, _)
+            | VoteOutcome::AcceptedGrades(voter, _)
+            | VoteOutcome::BlankVote(voter)
+            | VoteOutcome::InvalidVote(voter)
+            | VoteOutcome::HasAlreadyVoted(voter)
+            | VoteOutcome::ElectionNotOpen(voter)
+            | VoteOutcome::ElectionClosed(voter)
+            | VoteOutcome::NotRegistered(voter)
+            | VoteOutcome::UnknownCode(voter)
+            | VoteOutcome::CodeAlreadyUsed(voter)
+            | VoteOutcome::BallotReceived(voter)
+            | VoteOutcome::Revoked(voter)
+            | VoteOutcome::NothingToRevoke(voter)
+            | VoteOutcome::RevocationNotAllowed(voter)
+            | VoteOutcome::RevocationRefused(voter)
+            | VoteOutcome::NoProxy(voter, _) => voter,
+            VoteOutcome::Tracked(outcome, _) => outcome.voter(),
+        }
+    }
+}
+/// Phase du scrutin : brouillon, ouvert, clos puis certifié.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Phase {
+    Draft,
+    Open,
+    Closed,
+    Certified,
+}
+
+/// Réglages de la machine qui ne peuvent aller ensemble.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SettingsConflict {
+    /// Le changement de vote garde le lien entre votant et bulletin, que le scrutin secret interdit.
+    SecretRevote,
+}
+
+/// Réglage du scrutin enregistré qui ne correspond plus à la configuration.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SettingsMismatch {
+    Candidates,
+    Roll,
+    Secret,
+    Seats,
+    Encryption,
+    Proxies,
+    Revote,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PhaseChange {
+    Changed(Phase),
+    Refused(Phase, Phase),
+}
+
+/// Horaires d'ouverture et de clôture automatiques du scrutin.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct Schedule {
+    pub opens_at: Option<DateTime<Utc>>,
+    pub closes_at: Option<DateTime<Utc>>,
+}
+
+/// Prochaine échéance du calendrier.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Deadline {
+    Opening(DateTime<Utc>),
+    Closing(DateTime<Utc>),
+}
+
+impl Schedule {
+    /// Seules les échéances à venir sont renvoyées.
+    pub fn next_deadline(&self, phase: Phase, now: DateTime<Utc>) -> Option<Deadline> {
+        let upcoming = |instant: Option<DateTime<Utc>>| instant.filter(|instant| *instant > now);
+
+        match phase {
+            Phase::Draft => upcoming(self.opens_at)
+                .map(Deadline::Opening)
+                .or(upcoming(self.closes_at).map(Deadline::Closing)),
+            Phase::Open => upcoming(self.closes_at).map(Deadline::Closing),
+            Phase::Closed | Phase::Certified => None,
+        }
+    }
+}
+
+/// Issue de la clôture d'un tour de scrutin.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub enum RoundOutcome {
+    Elected(Candidate),
+    SecondRound(Candidate, Candidate),
+    NoWinner,
+}
+
+/// Tour de scrutin clos, archivé avec ses bulletins et son résultat.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RoundResult {
+    pub voters: AttendenceSheet,
+    pub scoreboard: Scoreboard,
+    pub ballots: Vec<WeightedBallot>,
+    pub outcome: RoundOutcome,
+}
+
+/// Bulletin courant d'un votant, conservé pour pouvoir le remplacer ou le retirer,
+/// avec l'empreinte du code de suivi qui lui a été remis.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CastBallot {
+    pub ballot: Ballot,
+    pub receipt: Option<String>,
+}
+
+/// Circonstances d'un dépôt : le mandataire qui vote au nom du votant, et le code
+/// de suivi remis avec le bulletin.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Casting {
+    pub proxy: Option<Voter>,
+    pub receipt: Option<TrackingCode>,
+}
+
+/// Dépôt d'un bulletin, préparé par la machine sans la modifier puis appliqué par
+/// `deposit`. C'est tout ce qu'un vote change : le stockage l'enregistre et le rejoue.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Deposit {
+    /// Votant admis ; avec des codes de vote, l'empreinte du code consommé.
+    pub voter: Voter,
+    /// Émargement du votant, absent avec des codes de vote.
+    pub attendance: Option<Attendance>,
+    /// Bulletin tel qu'il entre dans l'urne, chiffré en mode chiffré.
+    pub ballot: WeightedBallot,
+    /// Empreinte du code de suivi remis avec le bulletin.
+    pub receipt: Option<String>,
+}
+
+/// Dépôt préparé et la réponse à rendre une fois enregistré, ou le motif du refus.
+pub type Prepared = Result<(Deposit, VoteOutcome), VoteOutcome>;
+
 #[derive( Clone, Debug, Eq, PartialEq, )]
 
 pub struct VotingMachine {
     voters: AttendenceSheet,
     scoreboard: Scoreboard,
+    ballots: Vec<WeightedBallot>,
+    seats: usize,
+    rounds: Vec<RoundResult>,
+    phase: Phase,
+    roll: Option<ElectoralRoll>,
+    secret: bool,
+    /// Mandataire et code de suivi du bulletin en cours de dépôt, le temps de `track` et `vote_for`.
+    pending: Casting,
+    log: BallotLog,
+    encryption: Option<HomomorphicTally>,
+    codes: Option<CodeSheet>,
+    revote: bool,
+    cast_ballots: Map<Voter, CastBallot>,
+    proxies: ProxyRegistry,
 }
 
 impl Scoreboard {
     pub fn new(candidates: Vec<Candidate>) -> Self {
+        let head_counts = HeadCounts::new(&candidates);
         let mut scores: Map<Candidate, Score> = Map::new();
 
         for candidat in candidates {
@@ -47,58 +407,913 @@ impl Scoreboard {
         }
 
         Self {
-            scores: scores,
+            scores,
             blank_score: Score(0),
             invalid_score: Score(0),
+            head_counts: Some(head_counts),
         }
     }
+
+    /// Un bulletin ordonné compte pour son premier choix, un bulletin d'approbation
+    /// donne un point à chaque approuvé et un bulletin de jugement ajoute chaque mention.
+    pub fn count(&mut self, ballot: &Ballot) {
+        self.count_weighted(ballot, Weight::default());
+    }
+
+    /// Les scores sont multipliés par le poids de l'électeur, les voix brutes non.
+    pub fn count_weighted(&mut self, ballot: &Ballot, weight: Weight) {
+        self.tally(ballot, weight, |score, points| score + points);
+    }
+
+    /// Retire du tableau un bulletin précédemment compté.
+    pub fn uncount_weighted(&mut self, ballot: &Ballot, weight: Weight) {
+        self.tally(ballot, weight, usize::saturating_sub);
+    }
+
+    fn tally(&mut self, ballot: &Ballot, weight: Weight, apply: fn(usize, usize) -> usize) {
+        apply_ballot(&mut self.scores, &mut self.blank_score, &mut self.invalid_score, ballot, weight.0, apply);
+        if let Some(head_counts) = &mut self.head_counts {
+            apply_ballot(&mut head_counts.scores, &mut head_counts.blank_score, &mut head_counts.invalid_score, ballot, 1, apply);
+        }
+    }
+
+    fn insert_candidate(&mut self, candidate: Candidate) {
+        if let Some(head_counts) = &mut self.head_counts {
+            head_counts.scores.insert(candidate.clone(), Score(0));
+        }
+        self.scores.insert(candidate, Score(0));
+    }
 }
 
-impl VotingMachine {
-    pub fn new(candidates: Vec<Candidate>) -> Self {
-        Self {
-            voters: AttendenceSheet(Set::new()),
-            scoreboard: Scoreboard::new(candidates),
-        }
+fn apply_ballot(
+    scores: &mut Map<Candidate, Score>,
+    blank_score: &mut Score,
+    invalid_score: &mut Score,
+    ballot: &Ballot,
+    weight: usize,
+    apply: fn(usize, usize) -> usize,
+) {
+    let mut adjust = |candidate: &Candidate, points: usize| {
+        if let Some(score) = scores.get_mut(candidate) {
+            *score = Score(apply(score.0, points * weight));
+        }
+    };
+
+    match ballot {
+        Ballot::Blank => *blank_score = Score(apply(blank_score.0, weight)),
+        Ballot::Invalid => *invalid_score = Score(apply(invalid_score.0, weight)),
+        Ballot::Ranked(ranking) => {
+            if let Some(first) = ranking.first() {
+                adjust(first, 1);
+            }
+        }
+        Ballot::Approval(approved) => {
+            for candidate in approved {
+                adjust(candidate, 1);
+            }
+        }
+        Ballot::Graded(grades) => {
+            for (candidate, grade) in grades {
+                adjust(candidate, grade.0);
+            }
+        }
+        // Un bulletin chiffré n'est compté qu'au dépouillement homomorphe.
+        Ballot::Encrypted(_) => {}
+    }
+}
+
+impl VotingMachine {
+    pub fn new(candidates: Vec<Candidate>) -> Self {
+        Self {
+            voters: AttendenceSheet(Map::new()),
+            scoreboard: Scoreboard::new(candidates),
+            ballots: vec![],
+            seats: 1,
+            rounds: vec![],
+            phase: Phase::Draft,
+            roll: None,
+            secret: false,
+            pending: Casting::default(),
+            log: BallotLog::default(),
+            encryption: None,
+            codes: None,
+            revote: false,
+            cast_ballots: Map::new(),
+            proxies: ProxyRegistry::default(),
+        }
+    }
+
+    pub fn recover_from(voters: AttendenceSheet, scoreboard :  Scoreboard)-> Self
+    {
+        Self{
+            voters, scoreboard, ballots: vec![], seats: 1, rounds: vec![], phase: Phase::Draft, roll: None, secret: false, pending: Casting::default(), log: BallotLog::default(), encryption: None, codes: None, revote: false, cast_ballots: Map::new(), proxies: ProxyRegistry::default()
+        }
+    }
+
+    pub fn with_seats(mut self, seats: usize) -> Self {
+        self.seats = seats;
+        self
+    }
+
+    pub fn with_ballots(mut self, ballots: Vec<WeightedBallot>) -> Self {
+        self.ballots = ballots;
+        self
+    }
+
+    pub fn with_rounds(mut self, rounds: Vec<RoundResult>) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    pub fn with_phase(mut self, phase: Phase) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    pub fn with_roll(mut self, roll: Option<ElectoralRoll>) -> Self {
+        self.roll = roll;
+        self
+    }
+
+    pub fn with_secret(mut self, secret: bool) -> Self {
+        self.secret = secret;
+        self
+    }
+
+    pub fn with_log(mut self, log: BallotLog) -> Self {
+        self.log = log;
+        self
+    }
+
+    pub fn with_encryption(mut self, encryption: Option<HomomorphicTally>) -> Self {
+        self.encryption = encryption;
+        self
+    }
+
+    pub fn with_codes(mut self, codes: Option<CodeSheet>) -> Self {
+        self.codes = codes;
+        self
+    }
+
+    pub fn with_revote(mut self, revote: bool) -> Self {
+        self.revote = revote;
+        self
+    }
+
+    pub fn with_cast_ballots(mut self, cast_ballots: Map<Voter, CastBallot>) -> Self {
+        self.cast_ballots = cast_ballots;
+        self
+    }
+
+    pub fn with_proxies(mut self, proxies: ProxyRegistry) -> Self {
+        self.proxies = proxies;
+        self
+    }
+
+    /// Refuse les réglages incompatibles, avant l'ouverture comme à la reprise.
+    pub fn check_settings(&self) -> Result<(), SettingsConflict> {
+        if self.secret && self.revote {
+            return Err(SettingsConflict::SecretRevote);
+        }
+
+        Ok(())
+    }
+
+    /// À la reprise, vérifie que le scrutin enregistré a les réglages de la machine
+    /// `configured`, construite d'après la configuration. Les procurations enregistrées
+    /// en cours de scrutin s'ajoutent à celles de la configuration.
+    pub fn compare_settings(&self, configured: &VotingMachine) -> Result<(), SettingsMismatch> {
+        let public_key = |machine: &VotingMachine| machine.encryption.as_ref().map(|encryption| encryption.public_key);
+        let proxies_kept = self.proxies.limit == configured.proxies.limit
+            && configured.proxies.mandates.iter().all(|(principal, proxy)| self.proxies.mandates.get(principal) == Some(proxy));
+        let checks = [
+            (self.get_candidates() == configured.get_candidates(), SettingsMismatch::Candidates),
+            (self.roll == configured.roll, SettingsMismatch::Roll),
+            (self.secret == configured.secret, SettingsMismatch::Secret),
+            (self.seats == configured.seats, SettingsMismatch::Seats),
+            (public_key(self) == public_key(configured), SettingsMismatch::Encryption),
+            (proxies_kept, SettingsMismatch::Proxies),
+            (self.revote == configured.revote, SettingsMismatch::Revote),
+        ];
+
+        match checks.into_iter().find(|(same, _)| !same) {
+            Some((_, mismatch)) => Err(mismatch),
+            None => Ok(()),
+        }
+    }
+
+    /// Votant tel que la machine le retient : avec des codes de vote, l'empreinte du
+    /// code, jamais le code en clair.
+    pub fn holder(&self, voter: &Voter) -> Voter {
+        match self.codes {
+            Some(_) => Voter(digest(&voter.0)),
+            None => voter.clone(),
+        }
+    }
+
+    /// Motif du refus d'un dépôt de `holder` : scrutin qui n'est pas ouvert, code de vote
+    /// inconnu ou déjà utilisé, votant absent de la liste électorale ou qui a déjà voté.
+    /// Si le changement de vote est permis, un votant qui a un bulletin dans l'urne peut
+    /// le remplacer. Le refus est rendu au nom de `voter`, tel qu'il s'est présenté.
+    fn refusal(&self, voter: &Voter, holder: &Voter) -> Option<VoteOutcome> {
+        match self.phase {
+            Phase::Draft => return Some(VoteOutcome::ElectionNotOpen(voter.clone())),
+            Phase::Closed | Phase::Certified => return Some(VoteOutcome::ElectionClosed(voter.clone())),
+            Phase::Open => {}
+        }
+
+        if let Some(codes) = &self.codes {
+            return match codes.check(&holder.0) {
+                Ok(()) => None,
+                Err(CodeRejection::Unknown) => Some(VoteOutcome::UnknownCode(voter.clone())),
+                Err(CodeRejection::AlreadyUsed) if self.may_revote(holder) => None,
+                Err(CodeRejection::AlreadyUsed) => Some(VoteOutcome::CodeAlreadyUsed(voter.clone())),
+            };
+        }
+
+        if self.roll.as_ref().is_some_and(|roll| !roll.contains(holder)) {
+            return Some(VoteOutcome::NotRegistered(voter.clone()));
+        }
+
+        if self.voters.0.contains_key(holder) && !self.may_revote(holder) {
+            return Some(VoteOutcome::HasAlreadyVoted(voter.clone()));
+        }
+
+        None
+    }
+
+    /// Vrai si le changement de vote est permis et que `holder` a un bulletin dans l'urne.
+    fn may_revote(&self, holder: &Voter) -> bool {
+        self.allows_revote() && self.cast_ballots.contains_key(holder)
+    }
+
+    /// Vrai si la machine admet toujours le dépôt à l'instant `now` : un autre bulletin
+    /// du même votant, ou l'heure de clôture, a pu passer depuis sa préparation.
+    pub fn admits(&self, deposit: &Deposit, schedule: &Schedule, now: DateTime<Utc>) -> bool {
+        !self.is_behind(schedule, now) && self.refusal(&deposit.voter, &deposit.voter).is_none()
+    }
+
+    /// Prépare le dépôt de `ballot` par le votant de `outcome`, sans modifier la machine :
+    /// le mandataire doit détenir la procuration et le votant être admis. En mode chiffré,
+    /// le bulletin est chiffré ici, avec le poids de l'électeur.
+    fn prepare(&self, ballot: Ballot, outcome: VoteOutcome, casting: &Casting) -> Prepared {
+        let voter = outcome.voter().clone();
+        if let Some(proxy) = casting.proxy.as_ref().filter(|proxy| !self.proxies.holds(proxy, &voter)) {
+            return Err(VoteOutcome::NoProxy(proxy.clone(), voter));
+        }
+        let holder = self.holder(&voter);
+        if let Some(rejection) = self.refusal(&voter, &holder) {
+            return Err(rejection);
+        }
+
+        let weight = self.weight_of(&voter);
+        let ballot = match &self.encryption {
+            Some(encryption) => Ballot::Encrypted(Box::new(EncryptedScoreboard::encrypt(&encryption.public_key, &self.get_candidates(), &ballot, weight))),
+            None => ballot,
+        };
+        let attendance = match (&self.codes, &casting.proxy) {
+            (Some(_), _) => None,
+            (None, Some(proxy)) => Some(Attendance::ByProxy(proxy.clone())),
+            (None, None) => Some(Attendance::InPerson),
+        };
+
+        let deposit = Deposit {
+            voter: holder,
+            attendance,
+            ballot: WeightedBallot { ballot, weight },
+            receipt: casting.receipt.as_ref().map(TrackingCode::commitment),
+        };
+        Ok((deposit, self.seal(outcome)))
+    }
+
+    /// Applique un dépôt préparé, en direct comme à la reprise : le votant est émargé,
+    /// ou son code consommé, puis le bulletin est déposé. Si le changement de vote est
+    /// permis, le bulletin est gardé pour pouvoir être remplacé ou retiré.
+    pub fn deposit(&mut self, deposit: Deposit) {
+        self.enroll(&deposit.voter, deposit.attendance);
+        self.store_ballot(deposit.ballot.clone(), deposit.receipt.clone());
+
+        if self.allows_revote() {
+            self.cast_ballots.insert(deposit.voter, CastBallot { ballot: deposit.ballot.ballot, receipt: deposit.receipt });
+        }
+    }
+
+    /// Porte le votant sur la liste d'émargement ou, sans émargement, consomme son code
+    /// de vote. Si le changement de vote est permis, son bulletin précédent est retiré.
+    pub fn enroll(&mut self, voter: &Voter, attendance: Option<Attendance>) {
+        if self.allows_revote() {
+            self.withdraw(voter);
+        }
+
+        match (attendance, &mut self.codes) {
+            (Some(attendance), _) => {
+                self.voters.0.insert(voter.clone(), attendance);
+            }
+            (None, Some(codes)) => {
+                codes.consumed.insert(voter.0.clone());
+            }
+            (None, None) => {}
+        }
+    }
+
+    /// Dépose le bulletin dans l'urne et le reporte sur le tableau des scores.
+    /// En scrutin secret, l'urne est gardée triée et le registre chaîne les bulletins
+    /// par lots triés, pour ne pas révéler l'ordre des votes.
+    /// En mode chiffré, le bulletin chiffré est ajouté au total chiffré.
+    /// Les scores sont pondérés par le poids de l'électeur sur la liste électorale.
+    pub fn store_ballot(&mut self, ballot: WeightedBallot, receipt: Option<String>) {
+        if let (Some(encryption), Ballot::Encrypted(encrypted)) = (&mut self.encryption, &ballot.ballot) {
+            encryption.scoreboard.add(encrypted);
+        }
+
+        self.scoreboard.count_weighted(&ballot.ballot, ballot.weight);
+        match self.secret {
+            true => self.log.stage(self.rounds.len(), &ballot.ballot, ballot.weight, receipt),
+            false => self.log.append(self.rounds.len(), &ballot.ballot, ballot.weight, receipt),
+        }
+        match self.secret {
+            true => {
+                let position = self.ballots.partition_point(|stored| stored <= &ballot);
+                self.ballots.insert(position, ballot);
+            }
+            false => self.ballots.push(ballot),
+        }
+    }
+
+    /// Dépose les bulletins secrets de `staged`, triés, qui ne sont pas déjà en attente :
+    /// un stockage les rejoue sur un instantané qui contient déjà ceux d'avant.
+    pub fn restage(&mut self, staged: Vec<StagedBallot>) {
+        let mut pending = self.log.staged().clone().into_iter().peekable();
+
+        for staged in staged {
+            if pending.peek() == Some(&staged) {
+                pending.next();
+                continue;
+            }
+            self.store_ballot(WeightedBallot { ballot: staged.ballot, weight: staged.weight }, staged.receipt);
+        }
+    }
+
+    /// Retire de l'urne, du tableau des scores et de la liste d'émargement le bulletin
+    /// courant du votant ; son code de vote redevient utilisable. Le retrait est inscrit au registre avec l'empreinte du code
+    /// de suivi, qui ne vérifie plus.
+    pub fn withdraw(&mut self, voter: &Voter) -> bool {
+        let Some(cast) = self.cast_ballots.remove(voter) else { return false };
+
+        let weight = self.weight_of(voter);
+        if let Some(position) = self.ballots.iter().position(|stored| stored.ballot == cast.ballot && stored.weight == weight) {
+            self.ballots.remove(position);
+        }
+        self.scoreboard.uncount_weighted(&cast.ballot, weight);
+        if let (Some(encryption), Ballot::Encrypted(encrypted)) = (&mut self.encryption, &cast.ballot) {
+            encryption.scoreboard.sub(encrypted);
+        }
+        self.log.revoke(self.rounds.len(), &cast.ballot, weight, cast.receipt);
+        self.voters.0.remove(voter);
+        if let Some(codes) = &mut self.codes {
+            codes.consumed.remove(&voter.0);
+        }
+
+        true
+    }
+
+    /// Enregistre la procuration du mandant au mandataire, tant que le scrutin n'est
+    /// pas clos et que le mandant n'a pas voté. Avec une liste électorale, tous deux
+    /// doivent y être inscrits.
+    pub fn register_proxy(&mut self, proxy: Voter, principal: Voter) -> Result<(), ProxyRejection> {
+        if !matches!(self.phase, Phase::Draft | Phase::Open) {
+            return Err(ProxyRejection::ElectionClosed);
+        }
+        if self.roll.as_ref().is_some_and(|roll| !roll.contains(&proxy) || !roll.contains(&principal)) {
+            return Err(ProxyRejection::NotRegistered);
+        }
+        if self.voters.0.contains_key(&principal) {
+            return Err(ProxyRejection::AlreadyVoted);
+        }
+
+        self.proxies.register(proxy, principal)
+    }
+
+    /// Vote par procuration : `vote` dépose le bulletin au nom du mandant, si `proxy`
+    /// détient sa procuration, et l'émargement indique le mandataire qui l'a déposé.
+    pub fn vote_for(&mut self, proxy: &Voter, vote: impl FnOnce(&mut Self) -> VoteOutcome) -> VoteOutcome {
+        self.pending.proxy = Some(proxy.clone());
+        let outcome = vote(self);
+        self.pending.proxy = None;
+        outcome
+    }
+
+    /// Retire le vote d'un votant avant la clôture, si le scrutin le permet et si
+    /// `credential` est le code de suivi de son bulletin, ou le code de vote utilisé.
+    pub fn revoke(&mut self, voter: &Voter, credential: &str) -> VoteOutcome {
+        match self.phase {
+            Phase::Draft => return VoteOutcome::ElectionNotOpen(voter.clone()),
+            Phase::Closed | Phase::Certified => return VoteOutcome::ElectionClosed(voter.clone()),
+            Phase::Open => {}
+        }
+
+        if !self.allows_revote() {
+            return VoteOutcome::RevocationNotAllowed(voter.clone());
+        }
+
+        let holder = self.holder(voter);
+        let Some(cast) = self.cast_ballots.get(&holder) else {
+            return VoteOutcome::NothingToRevoke(voter.clone());
+        };
+        let commitment = TrackingCode(credential.to_string()).commitment();
+        let by_receipt = cast.receipt.as_ref().is_some_and(|receipt| receipt == &commitment);
+        let by_code = self.codes.is_some() && holder.0 == digest(credential);
+        if !by_receipt && !by_code {
+            return VoteOutcome::RevocationRefused(voter.clone());
+        }
+
+        self.withdraw(&holder);
+        VoteOutcome::Revoked(voter.clone())
+    }
+
+    /// En scrutin secret ou chiffré, la réponse se contente d'accuser réception du bulletin.
+    fn seal(&self, outcome: VoteOutcome) -> VoteOutcome {
+        if !self.secret && self.encryption.is_none() {
+            return outcome;
+        }
+
+        match outcome {
+            VoteOutcome::AcceptedVote(voter, _)
+            | VoteOutcome::AcceptedRanking(voter, _)
+            | VoteOutcome::AcceptedApproval(voter, _)
+            | VoteOutcome::PartiallyInvalidApproval(voter, _, _)
+            | VoteOutcome::AcceptedGrades(voter, _)
+            | VoteOutcome::BlankVote(voter)
+            | VoteOutcome::InvalidVote(voter) => VoteOutcome::BallotReceived(voter),
+            rejection => rejection,
+        }
+    }
+
+    pub fn vote(&mut self, ballot_paper: BallotPaper) -> VoteOutcome {
+        let prepared = self.prepare_vote(ballot_paper, &self.pending.clone());
+        self.settle(prepared)
+    }
+
+    pub fn vote_ranked(&mut self, ballot_paper: RankedBallotPaper) -> VoteOutcome {
+        let prepared = self.prepare_ranked(ballot_paper, &self.pending.clone());
+        self.settle(prepared)
+    }
+
+    pub fn vote_approval(&mut self, ballot_paper: ApprovalBallotPaper) -> VoteOutcome {
+        let prepared = self.prepare_approval(ballot_paper, &self.pending.clone());
+        self.settle(prepared)
+    }
+
+    pub fn vote_graded(&mut self, ballot_paper: GradedBallotPaper) -> VoteOutcome {
+        let prepared = self.prepare_graded(ballot_paper, &self.pending.clone());
+        self.settle(prepared)
+    }
+
+    fn settle(&mut self, prepared: Prepared) -> VoteOutcome {
+        match prepared {
+            Ok((deposit, outcome)) => {
+                self.deposit(deposit);
+                outcome
+            }
+            Err(rejection) => rejection,
+        }
+    }
+
+    pub fn prepare_vote(&self, ballot_paper: BallotPaper, casting: &Casting) -> Prepared {
+        let (ballot, outcome) = match ballot_paper.candidate {
+            Some(candidate) if self.scoreboard.scores.contains_key(&candidate) => {
+                (Ballot::Ranked(vec![candidate.clone()]), VoteOutcome::AcceptedVote(ballot_paper.voter, candidate))
+            }
+            Some(_) => (Ballot::Invalid, VoteOutcome::InvalidVote(ballot_paper.voter)),
+            None => (Ballot::Blank, VoteOutcome::BlankVote(ballot_paper.voter)),
+        };
+
+        self.prepare(ballot, outcome, casting)
+    }
+
+    /// Un classement vide est blanc ; un candidat inconnu ou cité deux fois le rend nul.
+    /// Le premier choix est compté dans le tableau des scores.
+    pub fn prepare_ranked(&self, ballot_paper: RankedBallotPaper, casting: &Casting) -> Prepared {
+        let ranking = ballot_paper.candidates;
+        let mut seen = Set::new();
+        let is_valid = ranking
+            .iter()
+            .all(|candidate| self.scoreboard.scores.contains_key(candidate) && seen.insert(candidate));
+
+        let (ballot, outcome) = if ranking.is_empty() {
+            (Ballot::Blank, VoteOutcome::BlankVote(ballot_paper.voter))
+        } else if is_valid {
+            (Ballot::Ranked(ranking.clone()), VoteOutcome::AcceptedRanking(ballot_paper.voter, ranking))
+        } else {
+            (Ballot::Invalid, VoteOutcome::InvalidVote(ballot_paper.voter))
+        };
+
+        self.prepare(ballot, outcome, casting)
+    }
+
+    /// Chaque candidat approuvé marque un point. Les noms inconnus sont écartés :
+    /// le bulletin n'est nul que si aucun nom n'est reconnu.
+    pub fn prepare_approval(&self, ballot_paper: ApprovalBallotPaper, casting: &Casting) -> Prepared {
+        let requested: Set<Candidate> = ballot_paper.candidates.into_iter().collect();
+        let (approved, unknown): (Vec<Candidate>, Vec<Candidate>) = requested
+            .into_iter()
+            .partition(|candidate| self.scoreboard.scores.contains_key(candidate));
+
+        let (ballot, outcome) = if approved.is_empty() && unknown.is_empty() {
+            (Ballot::Blank, VoteOutcome::BlankVote(ballot_paper.voter))
+        } else if approved.is_empty() {
+            (Ballot::Invalid, VoteOutcome::InvalidVote(ballot_paper.voter))
+        } else if unknown.is_empty() {
+            (Ballot::Approval(approved.clone()), VoteOutcome::AcceptedApproval(ballot_paper.voter, approved))
+        } else {
+            (Ballot::Approval(approved.clone()), VoteOutcome::PartiallyInvalidApproval(ballot_paper.voter, approved, unknown))
+        };
+
+        self.prepare(ballot, outcome, casting)
+    }
+
+    /// Chaque mention s'ajoute au score du candidat. Un candidat inconnu, noté deux fois
+    /// ou une mention hors barème rend le bulletin nul.
+    pub fn prepare_graded(&self, ballot_paper: GradedBallotPaper, casting: &Casting) -> Prepared {
+        if ballot_paper.grades.is_empty() {
+            return self.prepare(Ballot::Blank, VoteOutcome::BlankVote(ballot_paper.voter), casting);
+        }
+
+        let mut grades: Map<Candidate, Grade> = Map::new();
+        for (candidate, grade) in ballot_paper.grades {
+            let is_valid = self.scoreboard.scores.contains_key(&candidate)
+                && !grades.contains_key(&candidate)
+                && grade.is_some_and(|grade| grade.0 <= MAX_GRADE);
+
+            match (is_valid, grade) {
+                (true, Some(grade)) => {
+                    grades.insert(candidate, grade);
+                }
+                _ => return self.prepare(Ballot::Invalid, VoteOutcome::InvalidVote(ballot_paper.voter), casting),
+            }
+        }
+
+        self.prepare(Ballot::Graded(grades.clone()), VoteOutcome::AcceptedGrades(ballot_paper.voter, grades), casting)
+    }
+
+    /// Vote avec `vote` en scellant l'empreinte du code de suivi dans l'entrée du
+    /// registre du bulletin déposé, puis joint le code à la réponse.
+    /// Le code n'est pas lié au votant ; seule son empreinte est conservée.
+    pub fn track(&mut self, code: TrackingCode, vote: impl FnOnce(&mut Self) -> VoteOutcome) -> VoteOutcome {
+        self.pending.receipt = Some(code.clone());
+        let outcome = vote(self);
+        self.pending.receipt = None;
+
+        match outcome.is_cast() {
+            true => VoteOutcome::Tracked(Box::new(outcome), code),
+            false => outcome,
+        }
+    }
+
+    /// Vrai si le bulletin du code est dans l'urne dépouillée : une seule entrée du
+    /// registre porte l'empreinte du code, sans retrait, et son bulletin est dans l'urne de son tour.
+    pub fn verify(&self, code: &TrackingCode) -> bool {
+        let commitment = code.commitment();
+        let chained = self.log.entries().iter().map(|entry| (entry.round, &entry.ballot, entry.revoked, &entry.receipt));
+        let staged = self.log.staged().iter().map(|staged| (staged.round, &staged.ballot, false, &staged.receipt));
+        let mut entries = chained.chain(staged).filter(|(_, _, _, receipt)| receipt.as_ref() == Some(&commitment));
+
+        match (entries.next(), entries.next()) {
+            (Some((round, ballot, false, _)), None) => {
+                let urn = self.rounds.get(round).map_or(&self.ballots, |round| &round.ballots);
+                urn.iter().any(|stored| &stored.ballot == ballot)
+            }
+            _ => false,
+        }
+    }
+
+    /// Déchiffre le total chiffré une fois le scrutin clos et le reporte sur le
+    /// tableau des scores. Échoue si la clé ne correspond pas à celle de l'élection.
+    pub fn reveal(&mut self, key: &ElectionKey) -> bool {
+        let max = self.voted_weight() * MAX_GRADE;
+        let Some(encryption) = &mut self.encryption else { return false };
+        if !matches!(self.phase, Phase::Closed | Phase::Certified) || key.public_key() != encryption.public_key {
+            return false;
+        }
+
+        match encryption.scoreboard.decrypt(key, max) {
+            Some(scoreboard) => {
+                self.scoreboard = scoreboard;
+                encryption.revealed = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Vrai sans chiffrement, ou une fois le total chiffré déchiffré.
+    pub fn is_revealed(&self) -> bool {
+        self.encryption.as_ref().is_none_or(|encryption| encryption.revealed)
+    }
+
+    /// Un candidat ne peut être ajouté que tant que le scrutin est en brouillon.
+    pub fn add_candidate(&mut self, candidate: Candidate) -> bool {
+        if self.phase != Phase::Draft || self.scoreboard.scores.contains_key(&candidate) {
+            return false;
+        }
+
+        if let Some(encryption) = &mut self.encryption {
+            encryption.scoreboard.scores.insert(candidate.clone(), Ciphertext::zero());
+        }
+        self.scoreboard.insert_candidate(candidate);
+        true
+    }
+
+    /// Seules les transitions brouillon -> ouvert -> clos -> certifié sont permises ;
+    /// une fois certifiée, la machine est figée. À la clôture, les bulletins secrets
+    /// en attente sont chaînés au registre. Un scrutin chiffré n'est certifié qu'une fois déchiffré.
+    pub fn change_phase(&mut self, to: Phase) -> PhaseChange {
+        let allowed = match (self.phase, to) {
+            (Phase::Draft, Phase::Open) | (Phase::Open, Phase::Closed) => true,
+            (Phase::Closed, Phase::Certified) => self.is_revealed(),
+            _ => false,
+        };
+
+        match allowed {
+            true => {
+                self.phase = to;
+                if to == Phase::Closed {
+                    self.log.seal_staged();
+                }
+                PhaseChange::Changed(to)
+            }
+            false => PhaseChange::Refused(self.phase, to),
+        }
+    }
+
+    /// Applique les transitions dont l'heure est passée ; si l'ouverture et la clôture
+    /// sont toutes deux dépassées, le scrutin passe directement à l'état clos.
+    pub fn follow_schedule(&mut self, schedule: &Schedule, now: DateTime<Utc>) -> Vec<PhaseChange> {
+        let mut changes = vec![];
+        let is_past = |instant: Option<DateTime<Utc>>| instant.is_some_and(|instant| instant <= now);
+
+        if self.phase == Phase::Draft && is_past(schedule.opens_at) {
+            changes.push(self.change_phase(Phase::Open));
+        }
+        if self.phase == Phase::Open && is_past(schedule.closes_at) {
+            changes.push(self.change_phase(Phase::Closed));
+        }
+
+        changes
+    }
+
+    /// Vrai si une échéance du calendrier est passée sans que la phase l'ait suivie.
+    pub fn is_behind(&self, schedule: &Schedule, now: DateTime<Utc>) -> bool {
+        let is_past = |instant: Option<DateTime<Utc>>| instant.is_some_and(|instant| instant <= now);
+
+        match self.phase {
+            Phase::Draft => is_past(schedule.opens_at),
+            Phase::Open => is_past(schedule.closes_at),
+            Phase::Closed | Phase::Certified => false,
+        }
+    }
+
+    pub fn get_phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Les résultats ne sont consultables qu'une fois le scrutin clos et, s'il est chiffré, déchiffré.
+    pub fn results_visible(&self) -> bool {
+        matches!(self.phase, Phase::Closed | Phase::Certified) && self.is_revealed()
+    }
+
+    pub fn get_scoreboard(&self) -> &Scoreboard {
+        &self.scoreboard
+    }
+
+    pub fn get_voters(&self) -> &AttendenceSheet {
+        &self.voters
+    }
+
+    pub fn get_ballots(&self) -> &Vec<WeightedBallot> {
+        &self.ballots
+    }
+
+    pub fn get_log(&self) -> &BallotLog {
+        &self.log
+    }
+
+    /// Recalcule le chaînage du registre et, pour chaque tour, le tableau des scores
+    /// à partir des bulletins enregistrés, puis les compare aux scores conservés.
+    pub fn audit_log(&self) -> LogAudit {
+        if let Some(encryption) = &self.encryption {
+            return self.audit_encrypted_log(encryption);
+        }
+
+        let mut stored: Vec<&Scoreboard> = self.rounds.iter().map(|round| &round.scoreboard).collect();
+        if !self.is_decided() {
+            stored.push(&self.scoreboard);
+        }
+
+        let mismatched_rounds = stored
+            .into_iter()
+            .enumerate()
+            .filter(|(round, scoreboard)| {
+                let mut recount = Scoreboard::new(scoreboard.scores.keys().cloned().collect());
+                // Les voix brutes ne sont comparées que si elles ont été conservées.
+                if scoreboard.head_counts.is_none() {
+                    recount.head_counts = None;
+                }
+                for entry in self.log.entries_of_round(*round) {
+                    match entry.revoked {
+                        true => recount.uncount_weighted(&entry.ballot, entry.weight),
+                        false => recount.count_weighted(&entry.ballot, entry.weight),
+                    }
+                }
+                for staged in self.log.staged().iter().filter(|staged| staged.round == *round) {
+                    recount.count_weighted(&staged.ballot, staged.weight);
+                }
+                &&recount != scoreboard
+            })
+            .map(|(round, _)| round)
+            .collect();
+
+        LogAudit {
+            head: self.log.head().to_string(),
+            entries: self.log.entries().len(),
+            broken_links: self.log.broken_links(),
+            mismatched_rounds,
+        }
+    }
+
+    /// En mode chiffré, le total chiffré est recalculé en additionnant les bulletins
+    /// chiffrés du registre, sans avoir besoin de la clé de l'élection.
+    fn audit_encrypted_log(&self, encryption: &HomomorphicTally) -> LogAudit {
+        let mut recount = EncryptedScoreboard::zero(&self.get_candidates());
+        let staged = self.log.staged().iter().map(|staged| (&staged.ballot, false));
+        for (ballot, revoked) in self.log.entries_of_round(0).map(|entry| (&entry.ballot, entry.revoked)).chain(staged) {
+            match (ballot, revoked) {
+                (Ballot::Encrypted(encrypted), false) => recount.add(encrypted),
+                (Ballot::Encrypted(encrypted), true) => recount.sub(encrypted),
+                _ => {}
+            }
+        }
+
+        LogAudit {
+            head: self.log.head().to_string(),
+            entries: self.log.entries().len(),
+            broken_links: self.log.broken_links(),
+            mismatched_rounds: match recount == encryption.scoreboard {
+                true => vec![],
+                false => vec![0],
+            },
+        }
+    }
+
+    pub fn get_encryption(&self) -> Option<&HomomorphicTally> {
+        self.encryption.as_ref()
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.secret
+    }
+
+    /// Jamais en scrutin secret : le bulletin d'un votant n'y est pas retrouvable.
+    pub fn allows_revote(&self) -> bool {
+        self.revote && !self.secret
+    }
+
+    pub fn get_cast_ballots(&self) -> &Map<Voter, CastBallot> {
+        &self.cast_ballots
+    }
+
+    pub fn get_proxies(&self) -> &ProxyRegistry {
+        &self.proxies
+    }
+
+    pub fn get_roll(&self) -> Option<&ElectoralRoll> {
+        self.roll.as_ref()
+    }
+
+    /// Participation rapportée à la liste électorale, si le scrutin en a une.
+    /// Nombre de votants du tour en cours : émargements ou codes consommés.
+    pub fn voted(&self) -> usize {
+        self.voters.0.len() + self.codes.as_ref().map_or(0, |codes| codes.consumed.len())
+    }
+
+    /// Poids d'un électeur : 1 sans liste électorale ou pour un code de vote.
+    pub fn weight_of(&self, voter: &Voter) -> Weight {
+        self.roll.as_ref().map(|roll| roll.weight(voter)).unwrap_or_default()
+    }
+
+    /// Somme des poids des votants du tour en cours.
+    pub fn voted_weight(&self) -> usize {
+        let weights: usize = self.voters.0.keys().map(|voter| self.weight_of(voter).0).sum();
+        weights + self.codes.as_ref().map_or(0, |codes| codes.consumed.len())
+    }
+
+    pub fn get_codes(&self) -> Option<&CodeSheet> {
+        self.codes.as_ref()
+    }
+
+    pub fn turnout(&self) -> Option<Turnout> {
+        self.roll.as_ref().map(|roll| Turnout {
+            voted: Score(self.voted()),
+            registered: Score(roll.0.len()),
+        })
+    }
+
+    pub fn get_rounds(&self) -> &Vec<RoundResult> {
+        &self.rounds
+    }
+
+    /// Le scrutin est terminé dès qu'un tour clos n'a pas appelé de second tour.
+    pub fn is_decided(&self) -> bool {
+        self.rounds
+            .last()
+            .is_some_and(|round| !matches!(round.outcome, RoundOutcome::SecondRound(_, _)))
     }
 
-    pub fn recover_from(voters: AttendenceSheet, scoreboard :  Scoreboard)-> Self
-    {
-        Self{
-            voters, scoreboard
+    /// Clôt le tour en cours (scrutin à deux tours). Sans majorité absolue des suffrages
+    /// exprimés au premier tour, un second tour est ouvert entre les deux premiers
+    /// avec une nouvelle liste d'émargement ; au second tour, la majorité relative suffit.
+    /// Renvoie `None` si la phase du scrutin ne permet pas de clore un tour.
+    /// Les scores d'un scrutin chiffré ne sont connus qu'au dépouillement :
+    /// le scrutin à deux tours n'y est pas disponible.
+    pub fn close_round(&mut self) -> Option<RoundOutcome> {
+        if !matches!(self.phase, Phase::Open | Phase::Closed) || self.encryption.is_some() {
+            return None;
         }
-    }
-    pub fn vote(&mut self, ballot_paper: BallotPaper) -> VoteOutcome {
-        if self.voters.0.contains(&ballot_paper.voter) {
-            return VoteOutcome::HasAlreadyVoted(ballot_paper.voter);
+
+        if let Some(last) = self.rounds.last() {
+            if self.is_decided() {
+                return Some(last.outcome.clone());
+            }
         }
 
-        self.voters.0.insert(ballot_paper.voter.clone());
+        let mut standings: Vec<(Candidate, usize)> = self
+            .scoreboard
+            .scores
+            .iter()
+            .map(|(candidate, score)| (candidate.clone(), score.0))
+            .collect();
+        standings.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let expressed: usize = standings.iter().map(|(_, score)| score).sum();
+        let is_first_round = self.rounds.is_empty();
 
-        match ballot_paper.candidate {
-            Some(candidate) => match self.scoreboard.scores.get_mut(&candidate) {
-                Some(score) => {
-                    *score = Score(score.0 + 1);
-                    VoteOutcome::AcceptedVote(ballot_paper.voter, candidate)
-                }
-                None => {
-                    self.scoreboard.invalid_score = Score(self.scoreboard.invalid_score.0 + 1);
-                    VoteOutcome::InvalidVote(ballot_paper.voter)
+        self.log.seal_staged();
+        let outcome = match standings.as_slice() {
+            [(leader, score), ..] if *score * 2 > expressed => RoundOutcome::Elected(leader.clone()),
+            [(first, _), (second, _), ..] if is_first_round => RoundOutcome::SecondRound(first.clone(), second.clone()),
+            [(leader, score), (_, runner_up), ..] if score > runner_up => RoundOutcome::Elected(leader.clone()),
+            _ => RoundOutcome::NoWinner,
+        };
+
+        self.rounds.push(RoundResult {
+            voters: self.voters.clone(),
+            scoreboard: self.scoreboard.clone(),
+            ballots: self.ballots.clone(),
+            outcome: outcome.clone(),
+        });
+
+        match &outcome {
+            RoundOutcome::SecondRound(first, second) => {
+                self.voters = AttendenceSheet(Map::new());
+                self.cast_ballots.clear();
+                if let Some(codes) = &mut self.codes {
+                    codes.consumed.clear();
                 }
-            },
-            None => {
-                self.scoreboard.blank_score = Score(self.scoreboard.blank_score.0 + 1);
-                VoteOutcome::BlankVote(ballot_paper.voter)
+                self.scoreboard = Scoreboard::new(vec![first.clone(), second.clone()]);
+                self.ballots = vec![];
+                self.phase = Phase::Open;
             }
+            _ => self.phase = Phase::Closed,
         }
+
+        Some(outcome)
     }
 
-    pub fn get_scoreboard(&self) -> &Scoreboard {
-        &self.scoreboard
+    pub fn get_seats(&self) -> usize {
+        self.seats
     }
 
-    pub fn get_voters(&self) -> &AttendenceSheet {
-        &self.voters
+    pub fn get_candidates(&self) -> Vec<Candidate> {
+        self.scoreboard.scores.keys().cloned().collect()
+    }
+
+    pub fn instant_runoff(&self) -> InstantRunoff {
+        instant_runoff(&self.get_candidates(), &self.ballots)
+    }
+
+    pub fn condorcet(&self) -> Condorcet {
+        condorcet(&self.get_candidates(), &self.ballots)
+    }
+
+    pub fn positional(&self, rule: &PositionalRule) -> Scoreboard<Points> {
+        positional(&self.get_candidates(), &self.ballots, rule)
+    }
+
+    pub fn majority_judgment(&self) -> MajorityJudgment {
+        majority_judgment(&self.get_candidates(), &self.ballots)
+    }
+
+    pub fn stv(&self, transfer: SurplusTransfer) -> Stv {
+        stv(&self.get_candidates(), &self.ballots, self.seats, transfer)
     }
 }
 
@@ -112,7 +1327,7 @@ mod tests {
             Candidate(String::from("Louis")),
         ];
 
-        VotingMachine::new(candidates)
+        VotingMachine::new(candidates).with_phase(Phase::Open)
     }
 
     #[test]
@@ -197,4 +1412,605 @@ mod tests {
             _ => panic!("Expected HasAlreadyVoted"),
         }
     }
+
+    #[test]
+    fn test_prepared_deposit_is_refused_once_voter_has_voted() {
+        let mut voting_machine = setup();
+        let ballot_paper = BallotPaper { voter: Voter(String::from("Alice")), candidate: Some(Candidate(String::from("Louis"))) };
+
+        let (deposit, outcome) = voting_machine.prepare_vote(ballot_paper.clone(), &Casting::default()).expect("vote refuse");
+        assert_eq!(voting_machine, setup());
+        assert_eq!(outcome, VoteOutcome::AcceptedVote(Voter(String::from("Alice")), Candidate(String::from("Louis"))));
+
+        assert!(voting_machine.admits(&deposit, &Schedule::default(), Utc::now()));
+        let closed = Schedule { opens_at: None, closes_at: Some(Utc::now()) };
+        assert!(!voting_machine.admits(&deposit, &closed, Utc::now()));
+
+        voting_machine.vote(ballot_paper);
+        assert!(!voting_machine.admits(&deposit, &Schedule::default(), Utc::now()));
+    }
+
+    #[test]
+    fn test_vote_refused_outside_open_phase() {
+        let mut voting_machine = VotingMachine::new(vec![Candidate(String::from("Louis"))]);
+        let ballot_paper = BallotPaper {
+            voter: Voter(String::from("Alice")),
+            candidate: Some(Candidate(String::from("Louis"))),
+        };
+
+        assert_eq!(voting_machine.vote(ballot_paper.clone()), VoteOutcome::ElectionNotOpen(Voter(String::from("Alice"))));
+
+        voting_machine.change_phase(Phase::Open);
+        voting_machine.change_phase(Phase::Closed);
+
+        assert_eq!(voting_machine.vote(ballot_paper), VoteOutcome::ElectionClosed(Voter(String::from("Alice"))));
+        assert!(voting_machine.get_voters().0.is_empty());
+        assert!(voting_machine.results_visible());
+    }
+
+    #[test]
+    fn test_not_registered() {
+        let roll = ElectoralRoll::from_voters([Voter(String::from("Alice")), Voter(String::from("Bob"))]);
+        let mut voting_machine = setup().with_roll(Some(roll));
+
+        let outcome = voting_machine.vote(BallotPaper {
+            voter: Voter(String::from("Mallory")),
+            candidate: Some(Candidate(String::from("Louis"))),
+        });
+        plurality(&mut voting_machine, "Alice", "Louis");
+
+        assert_eq!(outcome, VoteOutcome::NotRegistered(Voter(String::from("Mallory"))));
+        assert_eq!(voting_machine.get_voters().0.len(), 1);
+        assert_eq!(voting_machine.turnout().map(|turnout| turnout.rate()), Some(0.5));
+    }
+
+    #[test]
+    fn test_secret_ballot_only_acknowledges_receipt() {
+        let mut voting_machine = setup().with_secret(true);
+
+        plurality(&mut voting_machine, "Alice", "Louis");
+        let outcome = voting_machine.vote(BallotPaper {
+            voter: Voter(String::from("Bob")),
+            candidate: Some(Candidate(String::from("Biggard"))),
+        });
+
+        assert_eq!(outcome, VoteOutcome::BallotReceived(Voter(String::from("Bob"))));
+        assert_eq!(
+            voting_machine.get_ballots(),
+            &vec![
+                WeightedBallot::from(Ballot::Ranked(vec![Candidate(String::from("Biggard"))])),
+                WeightedBallot::from(Ballot::Ranked(vec![Candidate(String::from("Louis"))])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_secret_ballot_refuses_revote() {
+        let mut voting_machine = setup().with_secret(true).with_revote(true);
+        let alice = Voter(String::from("Alice"));
+
+        plurality(&mut voting_machine, "Alice", "Louis");
+
+        assert_eq!(voting_machine.check_settings(), Err(SettingsConflict::SecretRevote));
+        assert_eq!(voting_machine.vote(BallotPaper { voter: alice.clone(), candidate: None }), VoteOutcome::HasAlreadyVoted(alice.clone()));
+        assert_eq!(voting_machine.revoke(&alice, "ABCD-EFGH"), VoteOutcome::RevocationNotAllowed(alice));
+        assert!(voting_machine.get_cast_ballots().is_empty());
+    }
+
+    #[test]
+    fn test_resumed_settings_must_match_configuration() {
+        let configured = setup()
+            .with_roll(Some(ElectoralRoll::from_voters([Voter(String::from("Alice")), Voter(String::from("Bob"))])))
+            .with_proxies(ProxyRegistry::new(2));
+        let mut saved = configured.clone();
+        saved.register_proxy(Voter(String::from("Alice")), Voter(String::from("Bob"))).expect("procuration refusee");
+
+        assert_eq!(saved.compare_settings(&configured), Ok(()));
+        assert_eq!(saved.compare_settings(&configured.clone().with_revote(true)), Err(SettingsMismatch::Revote));
+        assert_eq!(saved.compare_settings(&configured.clone().with_roll(None)), Err(SettingsMismatch::Roll));
+        assert_eq!(configured.compare_settings(&saved), Err(SettingsMismatch::Proxies));
+        let key = ElectionKey::generate();
+        let encrypted = configured.clone().with_encryption(Some(HomomorphicTally::new(key.public_key(), &configured.get_candidates())));
+        assert_eq!(saved.compare_settings(&encrypted), Err(SettingsMismatch::Encryption));
+    }
+
+    #[test]
+    fn test_secret_log_does_not_follow_voting_order() {
+        let mut voting_machine = setup().with_secret(true);
+        let code = TrackingCode(String::from("ABCD-EFGH"));
+
+        voting_machine.track(code.clone(), |voting_machine| plurality_outcome(voting_machine, "Alice", "Louis"));
+        plurality(&mut voting_machine, "Bob", "Biggard");
+
+        assert!(voting_machine.get_log().entries().is_empty());
+        assert!(voting_machine.audit_log().is_intact());
+        assert!(voting_machine.verify(&code));
+
+        voting_machine.change_phase(Phase::Closed);
+
+        let chained: Vec<&Ballot> = voting_machine.get_log().entries().iter().map(|entry| &entry.ballot).collect();
+        assert_eq!(chained, vec![&Ballot::Ranked(vec![Candidate(String::from("Biggard"))]), &Ballot::Ranked(vec![Candidate(String::from("Louis"))])]);
+        assert!(voting_machine.get_log().staged().is_empty());
+        assert!(voting_machine.audit_log().is_intact());
+        assert!(voting_machine.verify(&code));
+    }
+
+    #[test]
+    fn test_tracking_code_only_for_cast_ballots() {
+        let mut voting_machine = setup();
+        let code = TrackingCode(String::from("ABCD-EFGH"));
+
+        let tracked = voting_machine.track(code.clone(), |voting_machine| {
+            voting_machine.vote(BallotPaper { voter: Voter(String::from("Alice")), candidate: None })
+        });
+        let refused = voting_machine.track(TrackingCode(String::from("IJKL-MNOP")), |voting_machine| {
+            voting_machine.vote(BallotPaper { voter: Voter(String::from("Alice")), candidate: None })
+        });
+
+        assert_eq!(tracked, VoteOutcome::Tracked(Box::new(VoteOutcome::BlankVote(Voter(String::from("Alice")))), code.clone()));
+        assert_eq!(refused, VoteOutcome::HasAlreadyVoted(Voter(String::from("Alice"))));
+        assert!(voting_machine.verify(&code));
+        assert!(!voting_machine.verify(&TrackingCode(String::from("IJKL-MNOP"))));
+        assert_eq!(voting_machine.get_log().entries()[0].receipt, Some(code.commitment()));
+    }
+
+    #[test]
+    fn test_verify_requires_ballot_in_urn() {
+        let mut voting_machine = setup();
+        let code = TrackingCode(String::from("ABCD-EFGH"));
+        voting_machine.track(code.clone(), |voting_machine| plurality_outcome(voting_machine, "Alice", "Louis"));
+
+        let emptied = voting_machine.clone().with_ballots(vec![]);
+
+        assert!(voting_machine.verify(&code));
+        assert!(!emptied.verify(&code));
+    }
+
+    #[test]
+    fn test_audit_log_detects_altered_scores() {
+        let mut voting_machine = setup();
+        plurality(&mut voting_machine, "Alice", "Louis");
+        plurality(&mut voting_machine, "Bob", "Biggard");
+
+        assert!(voting_machine.audit_log().is_intact());
+        assert_eq!(voting_machine.audit_log().entries, 2);
+
+        let mut scoreboard = voting_machine.get_scoreboard().clone();
+        scoreboard.scores.insert(Candidate(String::from("Louis")), Score(2));
+        let tampered = VotingMachine::recover_from(voting_machine.get_voters().clone(), scoreboard)
+            .with_log(voting_machine.get_log().clone());
+
+        assert_eq!(tampered.audit_log().mismatched_rounds, vec![0]);
+    }
+
+    #[test]
+    fn test_encrypted_mode_keeps_only_ciphertexts() {
+        let key = ElectionKey::generate();
+        let candidates = vec![Candidate(String::from("Biggard")), Candidate(String::from("Louis"))];
+        let mut voting_machine = setup().with_encryption(Some(HomomorphicTally::new(key.public_key(), &candidates)));
+
+        let outcome = plurality_outcome(&mut voting_machine, "Alice", "Louis");
+        plurality(&mut voting_machine, "Bob", "Louis");
+
+        assert_eq!(outcome, VoteOutcome::BallotReceived(Voter(String::from("Alice"))));
+        assert!(voting_machine.get_ballots().iter().all(|ballot| matches!(ballot.ballot, Ballot::Encrypted(_))));
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louis"))], Score(0));
+        assert!(voting_machine.audit_log().is_intact());
+        assert!(!voting_machine.reveal(&key));
+
+        voting_machine.change_phase(Phase::Closed);
+
+        assert!(!voting_machine.results_visible());
+        assert_eq!(voting_machine.change_phase(Phase::Certified), PhaseChange::Refused(Phase::Closed, Phase::Certified));
+        assert!(!voting_machine.reveal(&ElectionKey::generate()));
+        assert!(voting_machine.reveal(&key));
+        assert!(voting_machine.results_visible());
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louis"))], Score(2));
+    }
+
+    #[test]
+    fn test_revote_replaces_previous_ballot() {
+        let mut voting_machine = setup().with_revote(true);
+
+        plurality(&mut voting_machine, "Alice", "Louis");
+        let outcome = voting_machine.vote(BallotPaper { voter: Voter(String::from("Alice")), candidate: None });
+
+        assert_eq!(outcome, VoteOutcome::BlankVote(Voter(String::from("Alice"))));
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louis"))], Score(0));
+        assert_eq!(voting_machine.get_scoreboard().blank_score, Score(1));
+        assert_eq!(voting_machine.get_ballots(), &vec![WeightedBallot::from(Ballot::Blank)]);
+        assert_eq!(voting_machine.voted(), 1);
+        assert!(voting_machine.audit_log().is_intact());
+    }
+
+    #[test]
+    fn test_revoke_withdraws_ballot_and_receipt() {
+        let mut voting_machine = setup().with_revote(true);
+        let alice = Voter(String::from("Alice"));
+
+        voting_machine.track(TrackingCode(String::from("ABCD-EFGH-JKLM")), |voting_machine| plurality_outcome(voting_machine, "Alice", "Louis"));
+
+        assert_eq!(voting_machine.revoke(&alice, "ABCD-EFGH-JKLM"), VoteOutcome::Revoked(alice.clone()));
+        assert_eq!(voting_machine.revoke(&alice, "ABCD-EFGH-JKLM"), VoteOutcome::NothingToRevoke(alice.clone()));
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louis"))], Score(0));
+        assert!(voting_machine.get_ballots().is_empty());
+        assert!(!voting_machine.verify(&TrackingCode(String::from("ABCD-EFGH-JKLM"))));
+        assert_eq!(voting_machine.audit_log().entries, 2);
+        assert!(voting_machine.audit_log().is_intact());
+    }
+
+    #[test]
+    fn test_revote_is_refused_by_default() {
+        let mut voting_machine = setup();
+        let alice = Voter(String::from("Alice"));
+
+        plurality(&mut voting_machine, "Alice", "Louis");
+
+        assert_eq!(voting_machine.revoke(&alice, "ABCD-EFGH"), VoteOutcome::RevocationNotAllowed(alice.clone()));
+        assert_eq!(voting_machine.vote(BallotPaper { voter: alice.clone(), candidate: None }), VoteOutcome::HasAlreadyVoted(alice));
+    }
+
+    #[test]
+    fn test_revoke_requires_own_tracking_code() {
+        let mut voting_machine = setup().with_revote(true);
+        let alice = Voter(String::from("Alice"));
+        let alice_code = TrackingCode(String::from("ABCD-EFGH-JKLM"));
+
+        voting_machine.track(alice_code.clone(), |voting_machine| plurality_outcome(voting_machine, "Alice", "Louis"));
+        voting_machine.track(TrackingCode(String::from("NPQR-STUV-WXYZ")), |voting_machine| plurality_outcome(voting_machine, "Bob", "Biggard"));
+
+        assert_eq!(voting_machine.revoke(&alice, "NPQR-STUV-WXYZ"), VoteOutcome::RevocationRefused(alice.clone()));
+        assert_eq!(voting_machine.revoke(&alice, "Alice"), VoteOutcome::RevocationRefused(alice.clone()));
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louis"))], Score(1));
+        assert!(voting_machine.verify(&alice_code));
+    }
+
+    #[test]
+    fn test_revoke_with_voting_code() {
+        let mut voting_machine = setup()
+            .with_revote(true)
+            .with_codes(Some(CodeSheet::issue(&[String::from("ABCD-EFGH-JKLM"), String::from("NPQR-STUV-WXYZ")])));
+        let code = Voter(String::from("ABCD-EFGH-JKLM"));
+
+        plurality(&mut voting_machine, "ABCD-EFGH-JKLM", "Louis");
+
+        assert_eq!(voting_machine.revoke(&code, "NPQR-STUV-WXYZ"), VoteOutcome::RevocationRefused(code.clone()));
+        assert_eq!(voting_machine.revoke(&code, "ABCD-EFGH-JKLM"), VoteOutcome::Revoked(code));
+        assert!(voting_machine.get_ballots().is_empty());
+        assert_eq!(voting_machine.voted(), 0);
+
+        plurality(&mut voting_machine, "ABCD-EFGH-JKLM", "Biggard");
+        assert_eq!(voting_machine.get_ballots().len(), 1);
+        assert_eq!(voting_machine.voted(), 1);
+    }
+
+    #[test]
+    fn test_revote_with_voting_code() {
+        let mut voting_machine = setup()
+            .with_revote(true)
+            .with_codes(Some(CodeSheet::issue(&[String::from("ABCD-EFGH-JKLM")])));
+
+        plurality(&mut voting_machine, "ABCD-EFGH-JKLM", "Louis");
+        let outcome = plurality_outcome(&mut voting_machine, "ABCD-EFGH-JKLM", "Biggard");
+
+        assert!(!matches!(outcome, VoteOutcome::CodeAlreadyUsed(_)));
+        assert_eq!(voting_machine.get_ballots().len(), 1);
+        assert_eq!(voting_machine.voted(), 1);
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louis"))], Score(0));
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Biggard"))], Score(1));
+    }
+
+    #[test]
+    fn test_revote_in_encrypted_mode() {
+        let key = ElectionKey::generate();
+        let candidates = vec![Candidate(String::from("Biggard")), Candidate(String::from("Louis"))];
+        let mut voting_machine = setup()
+            .with_revote(true)
+            .with_encryption(Some(HomomorphicTally::new(key.public_key(), &candidates)));
+
+        plurality(&mut voting_machine, "Alice", "Louis");
+        plurality(&mut voting_machine, "Alice", "Biggard");
+        voting_machine.change_phase(Phase::Closed);
+
+        assert!(voting_machine.audit_log().is_intact());
+        assert!(voting_machine.reveal(&key));
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louis"))], Score(0));
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Biggard"))], Score(1));
+    }
+
+    #[test]
+    fn test_weighted_vote_keeps_head_counts() {
+        let roll = ElectoralRoll(Map::from([(Voter(String::from("Alice")), Weight(250)), (Voter(String::from("Bob")), Weight(100))]));
+        let mut voting_machine = setup().with_roll(Some(roll));
+
+        plurality(&mut voting_machine, "Alice", "Louis");
+        plurality(&mut voting_machine, "Bob", "Biggard");
+
+        let scoreboard = voting_machine.get_scoreboard();
+        let head_counts = scoreboard.head_counts.as_ref().expect("voix brutes absentes");
+        assert_eq!(scoreboard.scores[&Candidate(String::from("Louis"))], Score(250));
+        assert_eq!(scoreboard.scores[&Candidate(String::from("Biggard"))], Score(100));
+        assert_eq!(head_counts.scores[&Candidate(String::from("Louis"))], Score(1));
+        assert_eq!(voting_machine.voted_weight(), 350);
+        assert!(voting_machine.audit_log().is_intact());
+    }
+
+    #[test]
+    fn test_weights_apply_to_every_tally() {
+        let roll = ElectoralRoll(Map::from([(Voter(String::from("Alice")), Weight(3)), (Voter(String::from("Bob")), Weight(1)), (Voter(String::from("Carol")), Weight(1))]));
+        let mut voting_machine = setup().with_roll(Some(roll));
+
+        plurality(&mut voting_machine, "Alice", "Louis");
+        plurality(&mut voting_machine, "Bob", "Biggard");
+        plurality(&mut voting_machine, "Carol", "Biggard");
+
+        let louis = Candidate(String::from("Louis"));
+        assert_eq!(voting_machine.instant_runoff().winner, Some(louis.clone()));
+        assert_eq!(voting_machine.condorcet().condorcet_winner, Some(louis.clone()));
+        assert_eq!(voting_machine.stv(SurplusTransfer::Gregory).elected, vec![louis.clone()]);
+        assert_eq!(voting_machine.positional(&PositionalRule::Dowdall).scores[&louis], Points(3.0));
+    }
+
+    #[test]
+    fn test_proxy_vote_is_recorded_as_such() {
+        let mut voting_machine = setup();
+        let (alice, bob) = (Voter(String::from("Alice")), Voter(String::from("Bob")));
+        let ballot_paper = BallotPaper { voter: bob.clone(), candidate: Some(Candidate(String::from("Louis"))) };
+
+        let refused = voting_machine.vote_for(&alice, |voting_machine| voting_machine.vote(ballot_paper.clone()));
+        assert_eq!(refused, VoteOutcome::NoProxy(alice.clone(), bob.clone()));
+
+        assert_eq!(voting_machine.register_proxy(alice.clone(), bob.clone()), Ok(()));
+        let outcome = voting_machine.vote_for(&alice, |voting_machine| voting_machine.vote(ballot_paper));
+        plurality(&mut voting_machine, "Alice", "Biggard");
+
+        assert_eq!(outcome, VoteOutcome::AcceptedVote(bob.clone(), Candidate(String::from("Louis"))));
+        assert_eq!(voting_machine.get_voters().0[&bob], Attendance::ByProxy(alice.clone()));
+        assert_eq!(voting_machine.get_voters().0[&alice], Attendance::InPerson);
+        assert_eq!(voting_machine.register_proxy(alice, Voter(String::from("Carol"))), Err(ProxyRejection::LimitReached));
+    }
+
+    #[test]
+    fn test_proxy_requires_registered_principal_who_has_not_voted() {
+        let roll = ElectoralRoll::from_voters([Voter(String::from("Alice")), Voter(String::from("Bob"))]);
+        let mut voting_machine = setup().with_roll(Some(roll));
+
+        plurality(&mut voting_machine, "Bob", "Louis");
+
+        assert_eq!(voting_machine.register_proxy(Voter(String::from("Alice")), Voter(String::from("Bob"))), Err(ProxyRejection::AlreadyVoted));
+        assert_eq!(voting_machine.register_proxy(Voter(String::from("Alice")), Voter(String::from("Tux"))), Err(ProxyRejection::NotRegistered));
+    }
+
+    #[test]
+    fn test_one_time_codes_replace_names() {
+        let mut voting_machine = setup().with_codes(Some(CodeSheet::issue(&[String::from("ABCD-EFGH-JKLM")])));
+
+        plurality(&mut voting_machine, "ABCD-EFGH-JKLM", "Louis");
+        let reused = voting_machine.vote(BallotPaper { voter: Voter(String::from("ABCD-EFGH-JKLM")), candidate: None });
+        let unknown = voting_machine.vote(BallotPaper { voter: Voter(String::from("Alice")), candidate: None });
+
+        assert_eq!(reused, VoteOutcome::CodeAlreadyUsed(Voter(String::from("ABCD-EFGH-JKLM"))));
+        assert_eq!(unknown, VoteOutcome::UnknownCode(Voter(String::from("Alice"))));
+        assert!(voting_machine.get_voters().0.is_empty());
+        assert_eq!(voting_machine.voted(), 1);
+    }
+
+    #[test]
+    fn test_add_candidate_only_in_draft() {
+        let mut voting_machine = VotingMachine::new(vec![Candidate(String::from("Louis"))]);
+
+        assert!(voting_machine.add_candidate(Candidate(String::from("Louise"))));
+        assert!(!voting_machine.add_candidate(Candidate(String::from("Louise"))));
+
+        voting_machine.change_phase(Phase::Open);
+
+        assert!(!voting_machine.add_candidate(Candidate(String::from("Tux"))));
+        assert_eq!(voting_machine.get_candidates().len(), 2);
+    }
+
+    #[test]
+    fn test_phase_transitions() {
+        let mut voting_machine = VotingMachine::new(vec![Candidate(String::from("Louis"))]);
+
+        assert_eq!(voting_machine.change_phase(Phase::Closed), PhaseChange::Refused(Phase::Draft, Phase::Closed));
+        assert_eq!(voting_machine.change_phase(Phase::Open), PhaseChange::Changed(Phase::Open));
+        assert!(!voting_machine.results_visible());
+        assert_eq!(voting_machine.change_phase(Phase::Closed), PhaseChange::Changed(Phase::Closed));
+        assert_eq!(voting_machine.change_phase(Phase::Certified), PhaseChange::Changed(Phase::Certified));
+        assert_eq!(voting_machine.change_phase(Phase::Open), PhaseChange::Refused(Phase::Certified, Phase::Open));
+        assert_eq!(voting_machine.close_round(), None);
+    }
+
+    #[test]
+    fn test_follow_schedule() {
+        let now = Utc::now();
+        let schedule = Schedule {
+            opens_at: Some(now - chrono::Duration::hours(1)),
+            closes_at: Some(now + chrono::Duration::hours(1)),
+        };
+        let mut voting_machine = VotingMachine::new(vec![Candidate(String::from("Louis"))]);
+
+        assert_eq!(voting_machine.follow_schedule(&schedule, now), vec![PhaseChange::Changed(Phase::Open)]);
+        assert_eq!(schedule.next_deadline(voting_machine.get_phase(), now), schedule.closes_at.map(Deadline::Closing));
+
+        voting_machine.follow_schedule(&schedule, now + chrono::Duration::hours(2));
+
+        assert_eq!(voting_machine.get_phase(), Phase::Closed);
+        assert_eq!(schedule.next_deadline(voting_machine.get_phase(), now), None);
+    }
+
+    fn plurality(voting_machine: &mut VotingMachine, voter: &str, candidate: &str) {
+        plurality_outcome(voting_machine, voter, candidate);
+    }
+
+    fn plurality_outcome(voting_machine: &mut VotingMachine, voter: &str, candidate: &str) -> VoteOutcome {
+        voting_machine.vote(BallotPaper {
+            voter: Voter(String::from(voter)),
+            candidate: Some(Candidate(String::from(candidate))),
+        })
+    }
+
+    #[test]
+    fn test_close_round_with_absolute_majority() {
+        let mut voting_machine = setup();
+        plurality(&mut voting_machine, "Alice", "Louis");
+        plurality(&mut voting_machine, "Bob", "Louis");
+        plurality(&mut voting_machine, "Carol", "Biggard");
+
+        let outcome = voting_machine.close_round();
+
+        assert_eq!(outcome, Some(RoundOutcome::Elected(Candidate(String::from("Louis")))));
+        assert!(voting_machine.is_decided());
+        assert_eq!(voting_machine.get_phase(), Phase::Closed);
+        assert_eq!(voting_machine.close_round(), outcome);
+        assert_eq!(voting_machine.get_rounds().len(), 1);
+    }
+
+    #[test]
+    fn test_close_round_opens_second_round() {
+        let mut voting_machine = VotingMachine::new(vec![
+            Candidate(String::from("Biggard")),
+            Candidate(String::from("Louis")),
+            Candidate(String::from("Louise")),
+        ])
+        .with_phase(Phase::Open);
+        plurality(&mut voting_machine, "Alice", "Louis");
+        plurality(&mut voting_machine, "Bob", "Louise");
+        plurality(&mut voting_machine, "Carol", "Biggard");
+        plurality(&mut voting_machine, "Dave", "Louis");
+
+        let outcome = voting_machine.close_round();
+
+        assert_eq!(
+            outcome,
+            Some(RoundOutcome::SecondRound(Candidate(String::from("Louis")), Candidate(String::from("Biggard"))))
+        );
+        assert!(voting_machine.get_voters().0.is_empty());
+        assert_eq!(
+            voting_machine.get_candidates(),
+            vec![Candidate(String::from("Biggard")), Candidate(String::from("Louis"))]
+        );
+
+        plurality(&mut voting_machine, "Alice", "Biggard");
+        assert_eq!(voting_machine.close_round(), Some(RoundOutcome::Elected(Candidate(String::from("Biggard")))));
+        assert_eq!(voting_machine.get_rounds().len(), 2);
+    }
+
+    #[test]
+    fn test_accepted_ranking() {
+        let mut voting_machine = setup();
+
+        let ballot_paper = RankedBallotPaper {
+            voter: Voter(String::from("Alice")),
+            candidates: vec![Candidate(String::from("Louis")), Candidate(String::from("Biggard"))],
+        };
+
+        let outcome = voting_machine.vote_ranked(ballot_paper);
+
+        assert_eq!(
+            outcome,
+            VoteOutcome::AcceptedRanking(
+                Voter(String::from("Alice")),
+                vec![Candidate(String::from("Louis")), Candidate(String::from("Biggard"))]
+            )
+        );
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louis"))], Score(1));
+        assert_eq!(
+            voting_machine.get_ballots(),
+            &vec![WeightedBallot::from(Ballot::Ranked(vec![Candidate(String::from("Louis")), Candidate(String::from("Biggard"))]))]
+        );
+    }
+
+    #[test]
+    fn test_ranking_with_duplicate_is_invalid() {
+        let mut voting_machine = setup();
+
+        let ballot_paper = RankedBallotPaper {
+            voter: Voter(String::from("Alice")),
+            candidates: vec![Candidate(String::from("Louis")), Candidate(String::from("Louis"))],
+        };
+
+        let outcome = voting_machine.vote_ranked(ballot_paper);
+
+        assert_eq!(outcome, VoteOutcome::InvalidVote(Voter(String::from("Alice"))));
+        assert_eq!(voting_machine.get_scoreboard().invalid_score, Score(1));
+    }
+
+    #[test]
+    fn test_approval_gives_a_point_to_each_candidate() {
+        let mut voting_machine = setup();
+
+        let ballot_paper = ApprovalBallotPaper {
+            voter: Voter(String::from("Alice")),
+            candidates: vec![Candidate(String::from("Louis")), Candidate(String::from("Biggard"))],
+        };
+
+        let outcome = voting_machine.vote_approval(ballot_paper);
+
+        assert_eq!(
+            outcome,
+            VoteOutcome::AcceptedApproval(
+                Voter(String::from("Alice")),
+                vec![Candidate(String::from("Biggard")), Candidate(String::from("Louis"))]
+            )
+        );
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louis"))], Score(1));
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Biggard"))], Score(1));
+    }
+
+    #[test]
+    fn test_approval_with_unknown_candidate() {
+        let mut voting_machine = setup();
+
+        let ballot_paper = ApprovalBallotPaper {
+            voter: Voter(String::from("Alice")),
+            candidates: vec![Candidate(String::from("Louis")), Candidate(String::from("Inconnu"))],
+        };
+
+        let outcome = voting_machine.vote_approval(ballot_paper);
+
+        assert_eq!(
+            outcome,
+            VoteOutcome::PartiallyInvalidApproval(
+                Voter(String::from("Alice")),
+                vec![Candidate(String::from("Louis"))],
+                vec![Candidate(String::from("Inconnu"))]
+            )
+        );
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louis"))], Score(1));
+        assert_eq!(voting_machine.get_scoreboard().invalid_score, Score(0));
+    }
+
+    #[test]
+    fn test_graded_vote() {
+        let mut voting_machine = setup();
+
+        let ballot_paper = GradedBallotPaper {
+            voter: Voter(String::from("Alice")),
+            grades: vec![(Candidate(String::from("Louis")), Some(Grade(4))), (Candidate(String::from("Biggard")), Some(Grade(1)))],
+        };
+
+        let outcome = voting_machine.vote_graded(ballot_paper);
+
+        let mut expected = Map::new();
+        expected.insert(Candidate(String::from("Biggard")), Grade(1));
+        expected.insert(Candidate(String::from("Louis")), Grade(4));
+        assert_eq!(outcome, VoteOutcome::AcceptedGrades(Voter(String::from("Alice")), expected));
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louis"))], Score(4));
+    }
+
+    #[test]
+    fn test_graded_vote_out_of_scale_is_invalid() {
+        let mut voting_machine = setup();
+
+        let ballot_paper = GradedBallotPaper {
+            voter: Voter(String::from("Alice")),
+            grades: vec![(Candidate(String::from("Louis")), Some(Grade(MAX_GRADE + 1)))],
+        };
+
+        let outcome = voting_machine.vote_graded(ballot_paper);
+
+        assert_eq!(outcome, VoteOutcome::InvalidVote(Voter(String::from("Alice"))));
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louis"))], Score(0));
+    }
 }
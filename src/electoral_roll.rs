@@ -0,0 +1,119 @@
+use std::collections::BTreeMap as Map;
+
+use anyhow::Context;
+use serde::Deserialize;
+use tokio::fs;
+
+use crate::domain::{ElectoralRoll, Voter, Weight};
+
+const CSV_HEADER: &str = "voter";
+const WEIGHT_COLUMN: &str = "weight";
+
+/// Liste JSON : un tableau de noms, ou un objet associant chaque nom à son poids.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RollJson {
+    Names(Vec<String>),
+    Weights(Map<String, usize>),
+}
+
+/// Lit la liste électorale depuis un fichier JSON (tableau de noms ou objet
+/// nom -> poids) ou CSV (un électeur par ligne, première colonne, en-tête `voter`
+/// facultatif ; une colonne `weight` désignée par l'en-tête donne les poids).
+pub async fn load_roll(path: &str) -> anyhow::Result<ElectoralRoll> {
+    let content = fs::read_to_string(path)
+        .await
+        .with_context(|| format!("impossible de lire la liste électorale {}", path))?;
+
+    match path.to_lowercase().ends_with(".json") {
+        true => parse_json(&content),
+        false => parse_csv(&content),
+    }
+    .with_context(|| format!("liste électorale {} invalide", path))
+}
+
+fn parse_json(content: &str) -> anyhow::Result<ElectoralRoll> {
+    match serde_json::from_str(content)? {
+        RollJson::Names(names) => Ok(ElectoralRoll::from_voters(names.into_iter().map(Voter))),
+        RollJson::Weights(weights) => Ok(ElectoralRoll(
+            weights
+                .into_iter()
+                .map(|(name, weight)| match weight {
+                    0 => anyhow::bail!("{} : poids {} invalide", name, weight),
+                    weight => Ok((Voter(name), Weight(weight))),
+                })
+                .collect::<anyhow::Result<_>>()?,
+        )),
+    }
+}
+
+fn parse_csv(content: &str) -> anyhow::Result<ElectoralRoll> {
+    let mut voters = Map::new();
+    let mut weight_column = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+        let name = columns[0];
+
+        if index == 0 && name.eq_ignore_ascii_case(CSV_HEADER) {
+            weight_column = columns.iter().position(|column| column.eq_ignore_ascii_case(WEIGHT_COLUMN));
+            continue;
+        }
+        if name.is_empty() {
+            continue;
+        }
+
+        let weight = match weight_column.and_then(|column| columns.get(column)) {
+            // Un poids nul retirerait l'électeur du scrutin sans le dire.
+            Some(weight) if !weight.is_empty() => match weight.parse() {
+                Ok(0) | Err(_) => anyhow::bail!("ligne {} : poids {} invalide", index + 1, weight),
+                Ok(parsed) => Weight(parsed),
+            },
+            _ => Weight::default(),
+        };
+        voters.insert(Voter(name.to_string()), weight);
+    }
+
+    Ok(ElectoralRoll(voters))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_skips_header_and_blank_lines() {
+        let roll = parse_csv("voter,email\nAlice,alice@example.org\n\nBob\n").expect("liste invalide");
+
+        assert_eq!(roll, ElectoralRoll::from_voters([Voter(String::from("Alice")), Voter(String::from("Bob"))]));
+    }
+
+    #[test]
+    fn test_parse_weights() {
+        let roll = parse_csv("voter,email,weight\nAlice,alice@example.org,250\nBob,,\n").expect("liste invalide");
+        let json = parse_json(r#"{"Alice": 250}"#).expect("liste invalide");
+
+        assert_eq!(roll.weight(&Voter(String::from("Alice"))), Weight(250));
+        assert_eq!(json.weight(&Voter(String::from("Alice"))), Weight(250));
+        assert!(parse_csv("voter,weight\nBob,beaucoup\n").is_err());
+        assert_eq!(parse_csv("voter,weight\nAlice,2\nBob,0\n").unwrap_err().to_string(), "ligne 3 : poids 0 invalide");
+        assert!(parse_json(r#"{"Alice": 0}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let roll = parse_json(r#"["Alice", "Bob"]"#).expect("liste invalide");
+
+        assert_eq!(roll, ElectoralRoll::from_voters([Voter(String::from("Alice")), Voter(String::from("Bob"))]));
+    }
+
+    #[tokio::test]
+    async fn test_load_roll_from_file() {
+        let path = std::env::temp_dir().join("c1_test_load_roll.csv");
+        fs::write(&path, "Alice\nBob\n").await.expect("ecriture impossible");
+
+        let roll = load_roll(path.to_str().unwrap()).await.expect("lecture impossible");
+
+        assert_eq!(roll.0.len(), 2);
+    }
+}
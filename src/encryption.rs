@@ -0,0 +1,285 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap as Map;
+
+use anyhow::Context;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use rand::rngs::OsRng;
+use serde::Serialize;
+use tokio::fs;
+
+use crate::domain::{Ballot, Candidate, Score, Scoreboard, Weight};
+use crate::storages::file::write_private_key;
+
+/// Chiffré ElGamal exponentiel sur Ristretto : (rG, mG + rH).
+/// La somme de deux chiffrés chiffre la somme des messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Ciphertext {
+    pub c1: RistrettoPoint,
+    pub c2: RistrettoPoint,
+}
+
+/// Clé publique de l'élection, seule connue du serveur pendant le scrutin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey(pub RistrettoPoint);
+
+/// Clé privée de l'élection, lue uniquement pour le dépouillement.
+pub struct ElectionKey(Scalar);
+
+/// Tableau des scores chiffré : sert à la fois de bulletin chiffré et de total.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct EncryptedScoreboard {
+    pub scores: Map<Candidate, Ciphertext>,
+    pub blank_score: Ciphertext,
+    pub invalid_score: Ciphertext,
+}
+
+/// Dépouillement homomorphe : la clé publique et le total chiffré courant.
+/// `revealed` indique que le total a été déchiffré avec la clé de l'élection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HomomorphicTally {
+    pub public_key: PublicKey,
+    pub scoreboard: EncryptedScoreboard,
+    pub revealed: bool,
+}
+
+pub fn to_hex(point: &RistrettoPoint) -> String {
+    hex::encode(point.compress().as_bytes())
+}
+
+/// Un point illisible est une erreur : la clé ou la machine a été altérée.
+pub fn from_hex(point: &str) -> anyhow::Result<RistrettoPoint> {
+    let bytes = hex::decode(point).with_context(|| format!("point {} illisible", point))?;
+
+    CompressedRistretto::from_slice(&bytes)
+        .ok()
+        .and_then(|compressed| compressed.decompress())
+        .ok_or_else(|| anyhow::anyhow!("point {} invalide", point))
+}
+
+impl PublicKey {
+    /// Lit la clé publique de l'élection, en hexadécimal.
+    pub async fn load(path: &str) -> anyhow::Result<Self> {
+        let public_key = fs::read_to_string(path)
+            .await
+            .with_context(|| format!("impossible de lire la clé publique de l'élection {}", path))?;
+
+        from_hex(public_key.trim())
+            .map(Self)
+            .with_context(|| format!("clé publique de l'élection {} invalide", path))
+    }
+}
+
+impl Ciphertext {
+    /// Chiffré de zéro sans aléa, élément neutre de l'addition.
+    pub fn zero() -> Self {
+        Self { c1: RistrettoPoint::identity(), c2: RistrettoPoint::identity() }
+    }
+
+    pub fn encrypt(public_key: &PublicKey, message: usize) -> Self {
+        let randomness = Scalar::random(&mut OsRng);
+
+        Self {
+            c1: randomness * RISTRETTO_BASEPOINT_POINT,
+            c2: Scalar::from(message as u64) * RISTRETTO_BASEPOINT_POINT + randomness * public_key.0,
+        }
+    }
+
+    pub fn add(&self, other: &Ciphertext) -> Self {
+        Self { c1: self.c1 + other.c1, c2: self.c2 + other.c2 }
+    }
+
+    pub fn sub(&self, other: &Ciphertext) -> Self {
+        Self { c1: self.c1 - other.c1, c2: self.c2 - other.c2 }
+    }
+
+    /// Retrouve le message par recherche exhaustive, borné par `max`.
+    pub fn decrypt(&self, key: &ElectionKey, max: usize) -> Option<usize> {
+        let target = self.c2 - key.0 * self.c1;
+        let mut candidate = RistrettoPoint::identity();
+
+        for message in 0..=max {
+            if candidate == target {
+                return Some(message);
+            }
+            candidate += RISTRETTO_BASEPOINT_POINT;
+        }
+
+        None
+    }
+
+    fn as_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(self.c1.compress().as_bytes());
+        bytes[32..].copy_from_slice(self.c2.compress().as_bytes());
+        bytes
+    }
+}
+
+/// Ordre arbitraire mais stable, pour garder l'urne triée en scrutin secret.
+impl Ord for Ciphertext {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(&other.as_bytes())
+    }
+}
+
+impl PartialOrd for Ciphertext {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl ElectionKey {
+    pub fn generate() -> Self {
+        Self(Scalar::random(&mut OsRng))
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(self.0 * RISTRETTO_BASEPOINT_POINT)
+    }
+
+    /// Génère la clé de l'élection hors du serveur : la clé privée, lisible par son seul
+    /// propriétaire, et la clé publique à côté, dans un fichier `.pub`.
+    pub async fn generate_files(path: &str) -> anyhow::Result<PublicKey> {
+        let key = Self::generate();
+        write_private_key(path, &hex::encode(key.0.as_bytes())).await?;
+        fs::write(format!("{}.pub", path), to_hex(&key.public_key().0)).await?;
+
+        Ok(key.public_key())
+    }
+
+    pub async fn load(path: &str) -> anyhow::Result<Self> {
+        let secret = fs::read_to_string(path)
+            .await
+            .with_context(|| format!("impossible de lire la clé de l'élection {}", path))?;
+        let bytes: [u8; 32] = hex::decode(secret.trim())?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("clé de l'élection {} invalide", path))?;
+
+        Ok(Self(Scalar::from_bytes_mod_order(bytes)))
+    }
+}
+
+impl EncryptedScoreboard {
+    pub fn zero(candidates: &[Candidate]) -> Self {
+        Self {
+            scores: candidates.iter().map(|candidate| (candidate.clone(), Ciphertext::zero())).collect(),
+            blank_score: Ciphertext::zero(),
+            invalid_score: Ciphertext::zero(),
+        }
+    }
+
+    /// Chiffre chaque case du décompte d'un bulletin, y compris les cases à zéro,
+    /// pour que rien ne distingue les choix dans l'urne. Le décompte est pondéré.
+    pub fn encrypt(public_key: &PublicKey, candidates: &[Candidate], ballot: &Ballot, weight: Weight) -> Self {
+        let mut scoreboard = Scoreboard::new(candidates.to_vec());
+        scoreboard.count_weighted(ballot, weight);
+
+        Self {
+            scores: scoreboard
+                .scores
+                .iter()
+                .map(|(candidate, score)| (candidate.clone(), Ciphertext::encrypt(public_key, score.0)))
+                .collect(),
+            blank_score: Ciphertext::encrypt(public_key, scoreboard.blank_score.0),
+            invalid_score: Ciphertext::encrypt(public_key, scoreboard.invalid_score.0),
+        }
+    }
+
+    pub fn add(&mut self, other: &EncryptedScoreboard) {
+        for (candidate, ciphertext) in &other.scores {
+            if let Some(total) = self.scores.get_mut(candidate) {
+                *total = total.add(ciphertext);
+            }
+        }
+        self.blank_score = self.blank_score.add(&other.blank_score);
+        self.invalid_score = self.invalid_score.add(&other.invalid_score);
+    }
+
+    /// Retire du total un bulletin chiffré qui y avait été ajouté.
+    pub fn sub(&mut self, other: &EncryptedScoreboard) {
+        for (candidate, ciphertext) in &other.scores {
+            if let Some(total) = self.scores.get_mut(candidate) {
+                *total = total.sub(ciphertext);
+            }
+        }
+        self.blank_score = self.blank_score.sub(&other.blank_score);
+        self.invalid_score = self.invalid_score.sub(&other.invalid_score);
+    }
+
+    /// Seuls les totaux pondérés sont chiffrés : les voix brutes restent inconnues.
+    pub fn decrypt(&self, key: &ElectionKey, max: usize) -> Option<Scoreboard> {
+        let mut scores = Map::new();
+        for (candidate, ciphertext) in &self.scores {
+            scores.insert(candidate.clone(), Score(ciphertext.decrypt(key, max)?));
+        }
+
+        Some(Scoreboard {
+            scores,
+            blank_score: Score(self.blank_score.decrypt(key, max)?),
+            invalid_score: Score(self.invalid_score.decrypt(key, max)?),
+            head_counts: None,
+        })
+    }
+}
+
+impl HomomorphicTally {
+    pub fn new(public_key: PublicKey, candidates: &[Candidate]) -> Self {
+        Self { public_key, scoreboard: EncryptedScoreboard::zero(candidates), revealed: false }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sum_of_ciphertexts_decrypts_to_sum() {
+        let key = ElectionKey::generate();
+        let public_key = key.public_key();
+
+        let sum = Ciphertext::encrypt(&public_key, 2).add(&Ciphertext::encrypt(&public_key, 3));
+
+        assert_eq!(sum.decrypt(&key, 10), Some(5));
+        assert_eq!(sum.decrypt(&key, 4), None);
+    }
+
+    #[test]
+    fn test_invalid_point_is_an_error() {
+        let point = to_hex(&ElectionKey::generate().public_key().0);
+
+        assert!(from_hex(&point).is_ok());
+        assert!(from_hex(&"ff".repeat(32)).is_err());
+        assert!(from_hex("zz").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_generated_public_key_is_loaded() {
+        let path = std::env::temp_dir().join("c1_test_generated_tally.key");
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path).await;
+
+        let public_key = ElectionKey::generate_files(path).await.expect("generation impossible");
+
+        assert_eq!(PublicKey::load(&format!("{}.pub", path)).await.expect("clé publique illisible"), public_key);
+        assert_eq!(ElectionKey::load(path).await.expect("clé privée illisible").public_key(), public_key);
+    }
+
+    #[test]
+    fn test_encrypted_ballots_add_up() {
+        let key = ElectionKey::generate();
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let mut tally = EncryptedScoreboard::zero(&candidates);
+
+        for ballot in [Ballot::Ranked(vec![candidates[0].clone()]), Ballot::Ranked(vec![candidates[0].clone()]), Ballot::Blank] {
+            tally.add(&EncryptedScoreboard::encrypt(&key.public_key(), &candidates, &ballot, Weight(1)));
+        }
+
+        let scoreboard = tally.decrypt(&key, 3).expect("dechiffrement impossible");
+        assert_eq!(scoreboard.scores[&candidates[0]], Score(2));
+        assert_eq!(scoreboard.scores[&candidates[1]], Score(0));
+        assert_eq!(scoreboard.blank_score, Score(1));
+    }
+}
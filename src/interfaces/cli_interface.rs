@@ -1,7 +1,15 @@
+use chrono::{DateTime, Utc};
+
 use crate::{
-    domain::{AttendenceSheet, Scoreboard, VoteOutcome}, 
+    ballot_log::LogAudit,
+    proxies::ProxyRejection,
+    results::verify_results_file,
+    encryption::ElectionKey,
+    domain::{AttendenceSheet, Candidate, Deadline, Phase, PhaseChange, Points, RoundOutcome, Scoreboard, TrackingCode, VoteOutcome, Voter, VotingMachine}, 
     storage::Storage, 
-    use_cases::{VoteForm, VotingController}
+    tallies::{condorcet::Condorcet, instant_runoff::InstantRunoff, majority_judgment::MajorityJudgment, positional::PositionalRule, stv::{Stv, SurplusTransfer}},
+    configuration::VotingMethod,
+    use_cases::{ApprovalVoteForm, GradedVoteForm, RankedVoteForm, VoteForm, VotingController}
 };
 
 
@@ -12,47 +20,404 @@ fn show_vote_outcome(outcome: VoteOutcome, lexicon: &Lexicon) -> String {
         VoteOutcome::InvalidVote(voter) => format!("{} {:?}", lexicon.has_voted_null, voter),
         VoteOutcome::BlankVote(voter) => format!("{} {:?}", lexicon.has_voted_blank, voter),
         VoteOutcome::HasAlreadyVoted(voter) => format!("{} {:?}", lexicon.has_already_voted, voter),
+        VoteOutcome::ElectionNotOpen(voter) => format!("{} {:?}", lexicon.election_not_open, voter),
+        VoteOutcome::NotRegistered(voter) => format!("{} {:?}", lexicon.not_registered, voter),
+        VoteOutcome::UnknownCode(voter) => format!("{} {:?}", lexicon.unknown_code, voter),
+        VoteOutcome::CodeAlreadyUsed(voter) => format!("{} {:?}", lexicon.code_already_used, voter),
+        VoteOutcome::BallotReceived(voter) => format!("{} {:?}", lexicon.ballot_received, voter),
+        VoteOutcome::Revoked(voter) => format!("{} {:?}", lexicon.revoked, voter),
+        VoteOutcome::NothingToRevoke(voter) => format!("{} {:?}", lexicon.nothing_to_revoke, voter),
+        VoteOutcome::RevocationNotAllowed(voter) => format!("{} {:?}", lexicon.revocation_not_allowed, voter),
+        VoteOutcome::RevocationRefused(voter) => format!("{} {:?}", lexicon.revocation_refused, voter),
+        VoteOutcome::NoProxy(proxy, principal) => format!("{} {:?} {} {:?}", lexicon.no_proxy, proxy, lexicon.on_behalf_of, principal),
+        VoteOutcome::Tracked(outcome, code) => format!("{}, {} : {}", show_vote_outcome(*outcome, lexicon), lexicon.tracking_code, code.0),
+        VoteOutcome::ElectionClosed(voter) => format!("{} {:?}", lexicon.election_closed, voter),
         VoteOutcome::AcceptedVote(voter, candidat) => format!("{} {:?} {:?}", lexicon.has_voted_for, voter, candidat),
+        VoteOutcome::AcceptedRanking(voter, ranking) => format!("{} {:?} {:?}", lexicon.has_voted_for, voter, ranking),
+        VoteOutcome::AcceptedApproval(voter, approved) => format!("{} {:?} {:?}", lexicon.has_voted_for, voter, approved),
+        VoteOutcome::AcceptedGrades(voter, grades) => format!("{} {:?} {:?}", lexicon.has_voted_for, voter, grades),
+        VoteOutcome::PartiallyInvalidApproval(voter, approved, unknown) => format!("{} {:?} {:?}, {} {:?}", lexicon.has_voted_for, voter, approved, lexicon.unknown_candidates_ignored, unknown),
+    }
+}
+
+fn show_instant_runoff(instant_runoff: &InstantRunoff, lexicon: &Lexicon) -> String {
+    let mut lines = vec![];
+
+    for (index, round) in instant_runoff.rounds.iter().enumerate() {
+        let mut line = format!("{} {} : {:?}, {} : {}", lexicon.round, index + 1, round.counts, lexicon.exhausted, round.exhausted.0);
+        if let Some(eliminated) = &round.eliminated {
+            line.push_str(&format!(", {} : {:?}, {} : {:?}", lexicon.eliminated, eliminated, lexicon.transfers, round.transfers));
+        }
+        lines.push(line);
+    }
+
+    match &instant_runoff.winner {
+        Some(winner) => lines.push(format!("{} : {:?}", lexicon.winner, winner)),
+        None => lines.push(lexicon.no_winner.to_string()),
     }
+
+    lines.join("\n")
 }
 
 fn show_scoreboard(scoreboard: &Scoreboard, lexicon: &Lexicon) -> String {
     format!("{} : {:?}", lexicon.actual_score, scoreboard)
 }
 
+fn show_positional_scoreboard(scoreboard: &Scoreboard<Points>, lexicon: &Lexicon) -> String {
+    format!("{} : {:?}", lexicon.positional_scores, scoreboard)
+}
+
+fn show_turnout(voting_machine: &VotingMachine, lexicon: &Lexicon) -> String {
+    match voting_machine.turnout() {
+        Some(turnout) => format!(
+            "{} : {} / {} ({:.2} %)",
+            lexicon.turnout, turnout.voted.0, turnout.registered.0, turnout.rate() * 100.0
+        ),
+        None => format!("{} : {} {}", lexicon.turnout, voting_machine.voted(), lexicon.no_roll),
+    }
+}
+
 fn show_attendence_sheet(attendence_sheet: &AttendenceSheet, lexicon: &Lexicon) -> String {
     format!("{} : {:?}", lexicon.voter, attendence_sheet)
 }
 
+fn show_proxy_rejection(rejection: ProxyRejection, lexicon: &Lexicon) -> &'static str {
+    match rejection {
+        ProxyRejection::SelfProxy => lexicon.proxy_self,
+        ProxyRejection::AlreadyDelegated => lexicon.proxy_already_delegated,
+        ProxyRejection::LimitReached => lexicon.proxy_limit_reached,
+        ProxyRejection::NotRegistered => lexicon.proxy_not_registered,
+        ProxyRejection::AlreadyVoted => lexicon.proxy_already_voted,
+        ProxyRejection::ElectionClosed => lexicon.proxy_election_closed,
+    }
+}
+
+/// Retire `--for <mandant>` des mots d'un vote. Une option sans mandant est une erreur.
+fn take_principal(words: &mut Vec<String>) -> Result<Option<String>, ()> {
+    let Some(position) = words.iter().position(|word| word == "--for") else { return Ok(None) };
+    if position + 1 >= words.len() {
+        return Err(());
+    }
+
+    let principal = words.remove(position + 1);
+    words.remove(position);
+    Ok(Some(principal))
+}
+
+fn show_condorcet(condorcet: &Condorcet, lexicon: &Lexicon) -> String {
+    let mut lines = vec![format!("{} :", lexicon.pairwise)];
+
+    for (a, row) in &condorcet.pairwise {
+        for (b, score) in row.range(a..) {
+            lines.push(format!("{:?} / {:?} : {} - {}", a, b, score.0, condorcet.pairwise[b][a].0));
+        }
+    }
+
+    match &condorcet.condorcet_winner {
+        Some(winner) => lines.push(format!("{} : {:?}", lexicon.condorcet_winner, winner)),
+        None => {
+            lines.push(lexicon.no_condorcet_winner.to_string());
+            lines.push(format!("{} : {:?}", lexicon.schulze_winners, condorcet.schulze_winners));
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn show_stv(stv: &Stv, lexicon: &Lexicon) -> String {
+    let mut lines = vec![];
+
+    for (index, round) in stv.rounds.iter().enumerate() {
+        let mut line = format!(
+            "{} {} ({} {:.2}) : {:?}, {} : {:.2}",
+            lexicon.round, index + 1, lexicon.quota, round.quota.0, round.counts, lexicon.exhausted, round.exhausted.0
+        );
+        if !round.elected.is_empty() {
+            line.push_str(&format!(", {} : {:?}", lexicon.elected, round.elected));
+        }
+        if let Some(eliminated) = &round.eliminated {
+            line.push_str(&format!(", {} : {:?}", lexicon.eliminated, eliminated));
+        }
+        if !round.transfers.is_empty() {
+            line.push_str(&format!(", {} : {:?}", lexicon.transfers, round.transfers));
+        }
+        lines.push(line);
+    }
+
+    lines.push(format!("{} ({}) : {:?}", lexicon.elected, stv.seats, stv.elected));
+    lines.join("\n")
+}
+
+fn show_majority_judgment(majority_judgment: &MajorityJudgment, lexicon: &Lexicon) -> String {
+    let mut lines = vec![];
+
+    for (candidate, grades) in &majority_judgment.grades {
+        let distribution: Vec<String> = grades
+            .distribution
+            .iter()
+            .zip(lexicon.grade_labels)
+            .map(|(count, label)| format!("{} : {}", label, count.0))
+            .collect();
+        let median = grades.median.map(|grade| lexicon.grade_labels[grade.0]).unwrap_or("-");
+
+        lines.push(format!(
+            "{:?} : {} {}, {} {:.2}, [{}]",
+            candidate, lexicon.majority_grade, median, lexicon.average, grades.average.0, distribution.join(", ")
+        ));
+    }
+
+    lines.push(format!("{} : {:?}", lexicon.ranking, majority_judgment.ranking));
+    lines.join("\n")
+}
+
+fn phase_label(phase: Phase, lexicon: &Lexicon) -> &'static str {
+    match phase {
+        Phase::Draft => lexicon.phase_draft,
+        Phase::Open => lexicon.phase_open,
+        Phase::Closed => lexicon.phase_closed,
+        Phase::Certified => lexicon.phase_certified,
+    }
+}
+
+fn show_phase_change(change: &PhaseChange, lexicon: &Lexicon) -> String {
+    match change {
+        PhaseChange::Changed(phase) => format!("{} : {}", lexicon.phase, phase_label(*phase, lexicon)),
+        PhaseChange::Refused(from, to) => format!("{} : {} -> {}", lexicon.transition_refused, phase_label(*from, lexicon), phase_label(*to, lexicon)),
+    }
+}
+
+fn show_log_audit(audit: &LogAudit, lexicon: &Lexicon) -> String {
+    if audit.is_intact() {
+        return format!("{} ({} : {}), {} : {}", lexicon.log_intact, lexicon.log_entries, audit.entries, lexicon.log_head, audit.head);
+    }
+
+    let mut lines = vec![];
+    if !audit.broken_links.is_empty() {
+        let positions: Vec<usize> = audit.broken_links.iter().map(|index| index + 1).collect();
+        lines.push(format!("{} : {:?}", lexicon.log_broken, positions));
+    }
+    if !audit.mismatched_rounds.is_empty() {
+        let rounds: Vec<usize> = audit.mismatched_rounds.iter().map(|round| round + 1).collect();
+        lines.push(format!("{} : {:?}", lexicon.log_scores_mismatch, rounds));
+    }
+
+    lines.join("\n")
+}
+
+/// À la clôture, ajoute l'empreinte finale du registre et, une fois les résultats
+/// consultables, le fichier des résultats signés.
+async fn with_log_head<Store: Storage>(response: String, controller: &VotingController<Store>, lexicon: &Lexicon) -> anyhow::Result<String> {
+    let (head, results_visible) = controller
+        .read(|voting_machine| (voting_machine.get_log().head().to_string(), voting_machine.results_visible()))
+        .await?;
+    let mut lines = vec![response, format!("{} : {}", lexicon.log_head, head)];
+
+    if let Some(publisher) = controller.get_publisher().filter(|_| results_visible) {
+        lines.push(format!("{} {}", lexicon.results_published, publisher.get_results_path()));
+    }
+
+    Ok(lines.join("\n"))
+}
+
+fn show_remaining_time(deadline: Option<Deadline>, now: DateTime<Utc>, lexicon: &Lexicon) -> String {
+    let (label, instant) = match deadline {
+        Some(Deadline::Opening(instant)) => (lexicon.opens_in, instant),
+        Some(Deadline::Closing(instant)) => (lexicon.closes_in, instant),
+        None => return lexicon.no_deadline.to_string(),
+    };
+
+    let seconds = (instant - now).num_seconds().max(0);
+    format!("{} {}h {:02}m {:02}s", label, seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
+
+fn show_round_outcome(outcome: &RoundOutcome, lexicon: &Lexicon) -> String {
+    match outcome {
+        RoundOutcome::Elected(candidate) => format!("{} : {:?}", lexicon.winner, candidate),
+        RoundOutcome::SecondRound(first, second) => format!("{} {:?} / {:?}", lexicon.second_round, first, second),
+        RoundOutcome::NoWinner => lexicon.no_winner.to_string(),
+    }
+}
+
+fn show_rounds(voting_machine: &VotingMachine, lexicon: &Lexicon) -> String {
+    let mut lines = vec![];
+
+    for (index, round) in voting_machine.get_rounds().iter().enumerate() {
+        lines.push(format!(
+            "{} {} : {:?} -> {}",
+            lexicon.round, index + 1, round.scoreboard, show_round_outcome(&round.outcome, lexicon)
+        ));
+    }
+
+    if !voting_machine.is_decided() && voting_machine.results_visible() {
+        lines.push(format!(
+            "{} {} ({}) : {:?}",
+            lexicon.round, voting_machine.get_rounds().len() + 1, lexicon.current_round, voting_machine.get_scoreboard()
+        ));
+    }
+
+    lines.join("\n")
+}
+
 fn display_menu(lexicon: &Lexicon) -> String {
     lexicon.menu.to_string()
 }
 
+/// Commandes réservées à l'administrateur lorsqu'un jeton est configuré.
+const PRIVILEGED_COMMANDS: [&str; 8] = ["votants", "open", "close", "reveal", "certify", "close-round", "add-candidate", "proxy"];
+
 pub async fn handle_line<Store: Storage>(
     line: &str, 
     controller: &VotingController<Store>,
     lexicon: &Lexicon
 ) -> anyhow::Result<String> {
-    let mut words = line.trim().split_whitespace();
-    let voting_machine = controller.get_voting_machine().await?;
+    let mut words = line.split_whitespace();
+    let results_visible = controller.read(VotingMachine::results_visible).await?;
+
+    let mut command = words.next();
+    let mut is_admin = controller.is_admin(None);
+    if command == Some("admin") {
+        is_admin = controller.is_admin(words.next());
+        command = words.next();
+        if !is_admin {
+            return Ok(lexicon.permission_denied.to_string());
+        }
+    }
+    // Seul un administrateur authentifié par jeton voit les résultats avant la clôture.
+    let sees_live_results = is_admin && controller.has_admin_token();
 
-    let response = match words.next() {
+    let response = match command {
+        Some(command) if PRIVILEGED_COMMANDS.contains(&command) && !is_admin => Ok(lexicon.permission_denied.to_string()),
         Some(command) => match command {
             "voter" => match words.next() {
                 Some(voter) => {
-                    let candidate = words.next().unwrap_or("").to_string();
-                    let ballot_paper = VoteForm {
-                        voter: voter.to_string(),
-                        candidate,
+                    let mut candidates: Vec<String> = words.map(String::from).collect();
+                    let Ok(on_behalf_of) = take_principal(&mut candidates) else {
+                        return Ok(lexicon.invalid_command_vote.to_string());
                     };
 
-                    let vote: VoteOutcome = controller.vote(ballot_paper).await?;
+                    let vote: VoteOutcome = if controller.get_method() == VotingMethod::Approval {
+                        let ballot_paper = ApprovalVoteForm {
+                            voter: voter.to_string(),
+                            on_behalf_of: on_behalf_of.clone(),
+                            candidates: candidates
+                                .iter()
+                                .flat_map(|word| word.split(','))
+                                .filter(|name| !name.is_empty())
+                                .map(String::from)
+                                .collect(),
+                        };
+                        controller.vote_approval(ballot_paper).await?
+                    } else if controller.get_method() == VotingMethod::Judgment {
+                        let ballot_paper = GradedVoteForm {
+                            voter: voter.to_string(),
+                            on_behalf_of: on_behalf_of.clone(),
+                            grades: candidates
+                                .iter()
+                                .map(|word| match word.split_once('=') {
+                                    Some((candidate, grade)) => (candidate.to_string(), grade.to_string()),
+                                    None => (word.to_string(), String::new()),
+                                })
+                                .collect(),
+                        };
+                        controller.vote_graded(ballot_paper).await?
+                    } else if candidates.len() > 1 {
+                        let ballot_paper = RankedVoteForm {
+                            voter: voter.to_string(),
+                            on_behalf_of: on_behalf_of.clone(),
+                            candidates,
+                        };
+                        controller.vote_ranked(ballot_paper).await?
+                    } else {
+                        let ballot_paper = VoteForm {
+                            voter: voter.to_string(),
+                            on_behalf_of: on_behalf_of.clone(),
+                            candidate: candidates.pop().unwrap_or_default(),
+                        };
+                        controller.vote(ballot_paper).await?
+                    };
                     Ok(show_vote_outcome(vote, lexicon))
                 }
                 None => Ok(lexicon.invalid_command_vote.to_string()),
             },
-            "scores" => Ok(show_scoreboard(voting_machine.get_scoreboard(), lexicon)),
-            "votants" => Ok(show_attendence_sheet(voting_machine.get_voters(), lexicon)),
+            "revoke" => match (words.next(), words.next()) {
+                (Some(voter), Some(credential)) => Ok(show_vote_outcome(controller.revoke(voter, credential).await?, lexicon)),
+                _ => Ok(lexicon.invalid_command_revoke.to_string()),
+            },
+            "scores" | "irv" | "pairwise" | "positional" | "grades" | "stv" if !results_visible && !sees_live_results => {
+                Ok(lexicon.results_hidden.to_string())
+            }
+            "scores" => Ok(show_scoreboard(&controller.get_scoreboard().await?, lexicon)),
+            "turnout" => Ok(controller.read(|voting_machine| show_turnout(voting_machine, lexicon)).await?),
+            "add-candidate" => match words.next() {
+                Some(name) => match controller.add_candidate(name).await? {
+                    true => Ok(format!("{} : {:?}", lexicon.candidate_added, Candidate(name.to_string()))),
+                    false => Ok(format!("{} : {:?}", lexicon.candidate_refused, Candidate(name.to_string()))),
+                },
+                None => Ok(lexicon.invalid_command_add_candidate.to_string()),
+            },
+            "proxy" => match (words.next(), words.next()) {
+                (Some(proxy), Some(principal)) => match controller.register_proxy(proxy, principal).await? {
+                    Ok(()) => Ok(format!("{} : {:?} {} {:?}", lexicon.proxy_registered, Voter(proxy.to_string()), lexicon.on_behalf_of, Voter(principal.to_string()))),
+                    Err(rejection) => Ok(format!("{} : {}", lexicon.proxy_refused, show_proxy_rejection(rejection, lexicon))),
+                },
+                _ => Ok(lexicon.invalid_command_proxy.to_string()),
+            },
+            "verify" => match words.next() {
+                Some(code) => match controller.read(|voting_machine| voting_machine.verify(&TrackingCode(code.to_uppercase()))).await? {
+                    true => Ok(lexicon.ballot_found.to_string()),
+                    false => Ok(lexicon.ballot_not_found.to_string()),
+                },
+                None => Ok(lexicon.invalid_command_verify.to_string()),
+            },
+            "votants" => Ok(controller.read(|voting_machine| show_attendence_sheet(voting_machine.get_voters(), lexicon)).await?),
+            "irv" => Ok(controller.read(|voting_machine| show_instant_runoff(&voting_machine.instant_runoff(), lexicon)).await?),
+            "pairwise" => Ok(controller.read(|voting_machine| show_condorcet(&voting_machine.condorcet(), lexicon)).await?),
+            "positional" => match words.next().map(str::parse::<PositionalRule>) {
+                Some(Ok(rule)) => Ok(controller.read(|voting_machine| show_positional_scoreboard(&voting_machine.positional(&rule), lexicon)).await?),
+                _ => Ok(lexicon.invalid_positional_rule.to_string()),
+            },
+            "open" => Ok(show_phase_change(&controller.change_phase(Phase::Open).await?, lexicon)),
+            "close" => match controller.change_phase(Phase::Closed).await? {
+                change @ PhaseChange::Changed(_) => with_log_head(show_phase_change(&change, lexicon), controller, lexicon).await,
+                change => Ok(show_phase_change(&change, lexicon)),
+            },
+            "reveal" => match words.next() {
+                Some(path) => match ElectionKey::load(path).await {
+                    Ok(key) if controller.reveal(&key).await? => with_log_head(lexicon.tally_revealed.to_string(), controller, lexicon).await,
+                    Ok(_) => Ok(lexicon.reveal_refused.to_string()),
+                    Err(_) => Ok(lexicon.tally_key_unreadable.to_string()),
+                },
+                None => Ok(lexicon.invalid_command_reveal.to_string()),
+            },
+            "verify-log" => Ok(controller.read(|voting_machine| show_log_audit(&voting_machine.audit_log(), lexicon)).await?),
+            // Seul le fichier de résultats du scrutin peut être lu.
+            "verify-results" => match (words.next(), controller.get_publisher()) {
+                (Some(public_key), Some(publisher)) => match verify_results_file(publisher.get_results_path(), public_key).await {
+                    Ok(true) => Ok(lexicon.results_valid.to_string()),
+                    Ok(false) => Ok(lexicon.results_invalid.to_string()),
+                    Err(_) => Ok(lexicon.results_unreadable.to_string()),
+                },
+                (Some(_), None) => Ok(lexicon.results_unreadable.to_string()),
+                (None, _) => Ok(lexicon.invalid_command_verify_results.to_string()),
+            },
+            "certify" => Ok(show_phase_change(&controller.change_phase(Phase::Certified).await?, lexicon)),
+            "time" => {
+                let now = Utc::now();
+                let deadline = controller.get_schedule().next_deadline(controller.get_phase().await?, now);
+                Ok(show_remaining_time(deadline, now, lexicon))
+            }
+            "phase" => Ok(format!("{} : {}", lexicon.phase, phase_label(controller.get_phase().await?, lexicon))),
+            "close-round" => match controller.close_round().await? {
+                Some(outcome @ RoundOutcome::SecondRound(_, _)) => Ok(show_round_outcome(&outcome, lexicon)),
+                Some(outcome) => with_log_head(show_round_outcome(&outcome, lexicon), controller, lexicon).await,
+                None => Ok(format!("{} : {}", lexicon.transition_refused, phase_label(controller.get_phase().await?, lexicon))),
+            },
+            "rounds" => Ok(controller.read(|voting_machine| show_rounds(voting_machine, lexicon)).await?),
+            "grades" => Ok(controller.read(|voting_machine| show_majority_judgment(&voting_machine.majority_judgment(), lexicon)).await?),
+            "stv" => match words.next().map(str::parse::<SurplusTransfer>).unwrap_or(Ok(SurplusTransfer::default())) {
+                Ok(transfer) => Ok(controller.read(|voting_machine| show_stv(&voting_machine.stv(transfer), lexicon)).await?),
+                Err(_) => Ok(lexicon.invalid_surplus_transfer.to_string()),
+            },
             _ => Ok(lexicon.unokwn_command.to_string()),
         },
         None => Ok(display_menu(lexicon)),
@@ -66,85 +431,115 @@ pub async fn handle_line<Store: Storage>(
 mod tests {
     use std::vec;
 
-    use crate::{domain::{Candidate, VotingMachine}, interfaces::lexicons::french::FRENCH, storages::memory::MemoryStore};
+    use crate::{domain::{Candidate, ElectoralRoll, Phase, Voter, VotingMachine}, encryption::HomomorphicTally, interfaces::lexicons::french::FRENCH, results::ResultsPublisher, storages::memory::MemoryStore};
     use super::*;
 
+    /// Retire le code de suivi aléatoire de la réponse à un vote.
+    fn untracked(response: String) -> String {
+        match response.split_once(", code de suivi : ") {
+            Some((outcome, _)) => outcome.to_string(),
+            None => response,
+        }
+    }
+
     #[tokio::test]
     async fn test_display_menu_if_no_command()
     {
 
-        let mut  candidates = vec![];
-        candidates.push(Candidate(String::from("Louis")));
+        let candidates = vec![Candidate(String::from("Louis"))];
         let voting_machine = VotingMachine::new(candidates);
 
         let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
         let lexicon: Lexicon = FRENCH;
          
         
-        let mut controller  = VotingController::new(store);
+        let controller  = VotingController::new(store);
     
     
         assert_eq!(r#"
-Il y a 4 commandes disponibles :
+Il y a 29 commandes disponibles :
 1) voter Tux Nixos -> Voter pour Nixos en tant que Tux
-2) voter Tux -> Voter blanc en tant que Tux
-3) votants -> Afficher la liste des votants
-4) scores -> Afficher les scores des candidats
-"#,handle_line("", &mut controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+2) voter Tux Nixos Debian Arch -> Classer Nixos, puis Debian, puis Arch en tant que Tux
+3) voter Tux Nixos,Debian -> Approuver Nixos et Debian en tant que Tux (avec --method approval)
+4) voter Tux Nixos=5 Debian=2 -> Noter Nixos 5 et Debian 2 sur 5 en tant que Tux (avec --method judgment)
+5) voter Tux -> Voter blanc en tant que Tux
+6) voter Tux Nixos --for Gnu -> Voter pour Nixos au nom de Gnu, qui a donné procuration à Tux
+7) revoke Tux <code> -> Retirer le vote de Tux avant la clôture, avec son code de suivi ou son code de vote (avec --revote, qui permet aussi de voter à nouveau)
+8) votants -> Afficher la liste des votants
+9) scores -> Afficher les scores des candidats
+10) irv -> Afficher le dépouillement par vote alternatif, tour par tour
+11) pairwise -> Afficher les duels, le vainqueur de Condorcet ou à défaut celui de Schulze
+12) positional borda -> Afficher les scores positionnels (borda, dowdall ou un barème comme 3-2-1)
+13) stv -> Afficher le vote unique transférable pour --seats sièges (gregory ou meek)
+14) grades -> Afficher la répartition des mentions, la mention majoritaire et la moyenne de chaque candidat
+15) close-round -> Clore le tour en cours (sans majorité absolue, un second tour est ouvert entre les deux premiers)
+16) rounds -> Afficher les résultats des tours clos et du tour en cours
+17) open -> Ouvrir le scrutin
+18) close -> Clore le scrutin et dévoiler les résultats
+19) reveal tally.key -> Déchiffrer le dépouillement d'un scrutin chiffré et clos avec la clé privée de l'élection
+20) certify -> Certifier les résultats et figer la machine
+21) phase -> Afficher la phase du scrutin
+22) time -> Afficher le temps restant avant l'ouverture ou la clôture programmée
+23) turnout -> Afficher la participation rapportée à la liste électorale
+24) verify <code> -> Vérifier que le bulletin portant ce code de suivi est dans l'urne
+25) verify-log -> Recalculer le chaînage du registre et les scores à partir des bulletins enregistrés
+26) verify-results <clé publique> -> Vérifier la signature des résultats publiés
+27) add-candidate Gentoo -> Ajouter un candidat tant que le scrutin est en brouillon
+28) proxy Tux Gnu -> Enregistrer la procuration de Gnu à Tux (au plus --proxy-limit procurations par mandataire, ou --proxies fichier.csv)
+29) admin <jeton> <commande> -> Exécuter une commande réservée (votants, scores avant la clôture, open, close, reveal, certify, close-round, add-candidate, proxy)
+Avec --issue-codes, indiquez votre code de vote à usage unique au lieu de votre nom : voter K7QM-2XRB-T9DE Nixos
+"#,handle_line("", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
     }
 
     #[tokio::test]
     async fn test_display_voters()
     {
 
-        let mut  candidates = vec![];
-        candidates.push(Candidate(String::from("Louis")));
+        let candidates = vec![Candidate(String::from("Louis"))];
         let voting_machine = VotingMachine::new(candidates);
 
         let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
         let lexicon: Lexicon = FRENCH;
          
         
-        let mut controller  = VotingController::new(store);
+        let controller  = VotingController::new(store);
     
     
-        assert_eq!("Votant : AttendenceSheet({})",handle_line("votants", &mut controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Votant : AttendenceSheet({})",handle_line("votants", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
     }
 
     #[tokio::test]
     async fn test_display_scores()
     {
 
-        let mut  candidates = vec![];
-        candidates.push(Candidate(String::from("Louis")));
-        let voting_machine = VotingMachine::new(candidates);
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Closed);
 
         let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
         let lexicon: Lexicon = FRENCH;
          
         
-        let mut controller  = VotingController::new(store);
+        let controller  = VotingController::new(store);
     
     
-        assert_eq!("Scores actuels : Scoreboard { scores: {Candidate(\"Louis\"): Score(0)}, blank_score: Score(0), invalid_score: Score(0) }",handle_line("scores", &mut controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Scores actuels : Scoreboard { scores: {Candidate(\"Louis\"): Score(0)}, blank_score: Score(0), invalid_score: Score(0), head_counts: HeadCounts { scores: {Candidate(\"Louis\"): Score(0)}, blank_score: Score(0), invalid_score: Score(0) } }",handle_line("scores", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
     }
 
     #[tokio::test]
     async fn test_display_legit_vote()
     {
 
-        let mut  candidates = vec![];
-        candidates.push(Candidate(String::from("Louis")));
-        let voting_machine = VotingMachine::new(candidates);
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open);
 
         let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
         let lexicon: Lexicon = FRENCH;
          
         
-        let mut controller  = VotingController::new(store);
+        let controller  = VotingController::new(store);
     
     
-        assert_eq!("a voté pour Voter(\"Louis\") Candidate(\"Louis\")",handle_line("voter Louis Louis", &mut controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("a voté pour Voter(\"Louis\") Candidate(\"Louis\")",untracked(handle_line("voter Louis Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne")));
     }
 
 
@@ -152,55 +547,383 @@ Il y a 4 commandes disponibles :
     async fn test_display_blank_vote()
     {
 
-        let mut  candidates = vec![];
-        candidates.push(Candidate(String::from("Louis")));
-        let voting_machine = VotingMachine::new(candidates);
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open);
 
         let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
         let lexicon: Lexicon = FRENCH;
          
         
-        let mut controller  = VotingController::new(store);
+        let controller  = VotingController::new(store);
     
     
-        assert_eq!("a voté blanc Voter(\"Louise\")",handle_line("voter Louise", &mut controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("a voté blanc Voter(\"Louise\")",untracked(handle_line("voter Louise", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne")));
     }
 
     #[tokio::test]
     async fn test_vote_command_without_name()
     {
 
-        let mut  candidates = vec![];
-        candidates.push(Candidate(String::from("Louis")));
+        let candidates = vec![Candidate(String::from("Louis"))];
         let voting_machine = VotingMachine::new(candidates);
 
         let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
         let lexicon: Lexicon = FRENCH;
          
         
-        let mut controller  = VotingController::new(store);
+        let controller  = VotingController::new(store);
     
     
-        assert_eq!("Commande 'voter' invalide, veuillez spécifier un électeur.",handle_line("voter", &mut controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Commande 'voter' invalide, veuillez spécifier un électeur.",handle_line("voter", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
     }
 
     #[tokio::test]
     async fn test_vote_invalid_command()
     {
 
-        let mut  candidates = vec![];
-        candidates.push(Candidate(String::from("Louis")));
+        let candidates = vec![Candidate(String::from("Louis"))];
         let voting_machine = VotingMachine::new(candidates);
 
         let store: MemoryStore = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
         let lexicon: Lexicon = FRENCH;
          
         
-        let mut controller  = VotingController::new(store);
+        let controller  = VotingController::new(store);
     
     
-        assert_eq!("Commande inconnue. Tapez une commande valide.",handle_line("azertyuiop", &mut controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Commande inconnue. Tapez une commande valide.",handle_line("azertyuiop", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
     }
 
+    #[tokio::test]
+    async fn test_display_ranked_vote_and_instant_runoff()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store);
+
+        assert_eq!("a voté pour Voter(\"Tux\") [Candidate(\"Louise\"), Candidate(\"Louis\")]",untracked(handle_line("voter Tux Louise Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne")));
+        handle_line("close", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        assert_eq!("Tour 1 : {Candidate(\"Louis\"): Score(0), Candidate(\"Louise\"): Score(1)}, bulletins épuisés : 0\nVainqueur : Candidate(\"Louise\")",handle_line("irv", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+    }
+
+    #[tokio::test]
+    async fn test_display_approval_vote()
+    {
+
+        let candidates = vec![Candidate(String::from("Debian")), Candidate(String::from("Nixos"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store).with_method(VotingMethod::Approval);
+
+        assert_eq!("a voté pour Voter(\"Tux\") [Candidate(\"Debian\"), Candidate(\"Nixos\")]",untracked(handle_line("voter Tux Nixos,Debian", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne")));
+        assert_eq!("a voté pour Voter(\"Tom\") [Candidate(\"Nixos\")], candidats inconnus ignorés : [Candidate(\"Arch\")]",untracked(handle_line("voter Tom Nixos,Arch", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne")));
+    }
+
+    #[tokio::test]
+    async fn test_display_pairwise()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store);
+
+        handle_line("voter Tux Louise Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        handle_line("close", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        assert_eq!("Duels :\nCandidate(\"Louis\") / Candidate(\"Louise\") : 0 - 1\nVainqueur de Condorcet : Candidate(\"Louise\")",handle_line("pairwise", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+    }
+
+    #[tokio::test]
+    async fn test_display_positional_scores()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
 
+        let controller  = VotingController::new(store);
+
+        handle_line("voter Tux Louise Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        handle_line("close", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        assert_eq!("Scores positionnels : Scoreboard { scores: {Candidate(\"Louis\"): Points(0.5), Candidate(\"Louise\"): Points(1.0)}, blank_score: Score(0), invalid_score: Score(0) }",handle_line("positional dowdall", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Commande 'positional' invalide, attendu : borda, dowdall ou un barème comme 3-2-1.",handle_line("positional", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+    }
+
+    #[tokio::test]
+    async fn test_display_stv()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let voting_machine = VotingMachine::new(candidates).with_seats(1).with_phase(Phase::Open);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store);
+
+        handle_line("voter Tux Louise Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        handle_line("close", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        assert_eq!("Tour 1 (quota 1.00) : {Candidate(\"Louis\"): Points(0.0), Candidate(\"Louise\"): Points(1.0)}, bulletins épuisés : 0.00, Élus : [Candidate(\"Louise\")]\nÉlus (1) : [Candidate(\"Louise\")]",handle_line("stv", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Commande 'stv' invalide, attendu : gregory ou meek.",handle_line("stv droop", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+    }
+
+    #[tokio::test]
+    async fn test_display_grades()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store).with_method(VotingMethod::Judgment);
+
+        assert_eq!("a voté pour Voter(\"Tux\") {Candidate(\"Louis\"): Grade(1), Candidate(\"Louise\"): Grade(5)}",untracked(handle_line("voter Tux Louise=5 Louis=1", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne")));
+        assert_eq!("a voté nul Voter(\"Tom\")",untracked(handle_line("voter Tom Louise=top", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne")));
+        handle_line("close", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        assert_eq!(r#"Candidate("Louis") : mention majoritaire Insuffisant, moyenne 1.00, [À rejeter : 0, Insuffisant : 1, Passable : 0, Bien : 0, Très bien : 0, Excellent : 0]
+Candidate("Louise") : mention majoritaire Excellent, moyenne 5.00, [À rejeter : 0, Insuffisant : 0, Passable : 0, Bien : 0, Très bien : 0, Excellent : 1]
+Classement : [Candidate("Louise"), Candidate("Louis")]"#,handle_line("grades", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+    }
+
+    #[tokio::test]
+    async fn test_two_round_workflow()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise")), Candidate(String::from("Tux"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store);
+
+        handle_line("voter Alice Louise", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        handle_line("voter Bob Tux", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        assert_eq!("Pas de majorité absolue, second tour entre Candidate(\"Louise\") / Candidate(\"Tux\")",handle_line("close-round", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("a voté pour Voter(\"Alice\") Candidate(\"Tux\")",untracked(handle_line("voter Alice Tux", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne")));
+        let closing = handle_line("close-round", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        assert_eq!(Some("Vainqueur : Candidate(\"Tux\")"),closing.lines().next());
+        assert!(closing.contains("Empreinte finale du registre : "));
+        assert_eq!(r#"Tour 1 : Scoreboard { scores: {Candidate("Louis"): Score(0), Candidate("Louise"): Score(1), Candidate("Tux"): Score(1)}, blank_score: Score(0), invalid_score: Score(0), head_counts: HeadCounts { scores: {Candidate("Louis"): Score(0), Candidate("Louise"): Score(1), Candidate("Tux"): Score(1)}, blank_score: Score(0), invalid_score: Score(0) } } -> Pas de majorité absolue, second tour entre Candidate("Louise") / Candidate("Tux")
+Tour 2 : Scoreboard { scores: {Candidate("Louise"): Score(0), Candidate("Tux"): Score(1)}, blank_score: Score(0), invalid_score: Score(0), head_counts: HeadCounts { scores: {Candidate("Louise"): Score(0), Candidate("Tux"): Score(1)}, blank_score: Score(0), invalid_score: Score(0) } } -> Vainqueur : Candidate("Tux")"#,handle_line("rounds", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+    }
+
+    #[tokio::test]
+    async fn test_election_lifecycle()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine = VotingMachine::new(candidates);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store);
+
+        assert_eq!("Phase : Brouillon",handle_line("phase", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Le scrutin n'est pas encore ouvert, vote refusé pour Voter(\"Tux\")",untracked(handle_line("voter Tux Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne")));
+        assert_eq!("Transition refusée : Brouillon -> Certifié",handle_line("certify", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Phase : Ouvert",handle_line("open", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        handle_line("voter Tux Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        assert_eq!("Les résultats seront disponibles à la clôture du scrutin.",handle_line("scores", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        let closing = handle_line("close", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        let head = controller.get_voting_machine().await.expect("erreur lors de la recuperation").get_log().head().to_string();
+        assert_eq!(format!("Phase : Clos\nEmpreinte finale du registre : {}", head),closing);
+        assert_eq!(format!("Registre intègre (entrées : 1), Empreinte finale du registre : {}", head),handle_line("verify-log", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Le scrutin est clos, vote refusé pour Voter(\"Tom\")",untracked(handle_line("voter Tom Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne")));
+        assert_eq!("Phase : Certifié",handle_line("certify", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Transition refusée : Certifié",handle_line("close-round", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_election_is_revealed_with_private_key()
+    {
+        let key_path = std::env::temp_dir().join("c1_test_cli_tally.key");
+        let _ = tokio::fs::remove_file(&key_path).await;
+        let public_key = ElectionKey::generate_files(key_path.to_str().unwrap()).await.expect("erreur lors de la generation de la cle");
+
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine = VotingMachine::new(candidates.clone())
+            .with_phase(Phase::Open)
+            .with_encryption(Some(HomomorphicTally::new(public_key, &candidates)));
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+        let controller  = VotingController::new(store);
+
+        handle_line("voter Tux Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        assert_eq!("Déchiffrement refusé : le scrutin n'est pas chiffré et clos, ou la clé ne correspond pas.",handle_line(&format!("reveal {}", key_path.display()), &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        handle_line("close", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        assert_eq!("Les résultats seront disponibles à la clôture du scrutin.",handle_line("scores", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Clé privée de l'élection illisible.",handle_line("reveal c1_test_absente.key", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert!(handle_line(&format!("reveal {}", key_path.display()), &controller, &lexicon).await.expect("erreur lors de lecture de la ligne").starts_with("Dépouillement déchiffré."));
+        assert!(handle_line("scores", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne").contains("Score(1)"));
+    }
+
+    #[tokio::test]
+    async fn test_display_remaining_time()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine = VotingMachine::new(candidates);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store);
+
+        assert_eq!("Aucune ouverture ni clôture n'est programmée.",handle_line("time", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+
+        let now = Utc::now();
+        let deadline = Deadline::Closing(now + chrono::Duration::seconds(3723));
+        assert_eq!("Clôture dans 1h 02m 03s", show_remaining_time(Some(deadline), now, &lexicon));
+    }
+
+    #[tokio::test]
+    async fn test_electoral_roll_and_turnout()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let roll = ElectoralRoll::from_voters([Voter(String::from("Tux")), Voter(String::from("Gnu"))]);
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open).with_roll(Some(roll));
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store);
+
+        assert_eq!("n'est pas inscrit sur la liste électorale, vote refusé pour Voter(\"Tuxx\")",untracked(handle_line("voter Tuxx Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne")));
+        handle_line("voter Tux Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        assert_eq!("Participation : 1 / 2 (50.00 %)",handle_line("turnout", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_tracking_code()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store);
+
+        let response = handle_line("voter Tux Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        let (_, code) = response.split_once(", code de suivi : ").expect("code de suivi absent");
+
+        assert_eq!("Ce bulletin est dans l'urne et compté au dépouillement.",handle_line(&format!("verify {}", code.to_lowercase()), &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Aucun bulletin ne correspond à ce code de suivi.",handle_line("verify AAAA-AAAA-AAAA", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Commande 'verify' invalide, veuillez indiquer un code de suivi.",handle_line("verify", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_results_command()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open);
+
+        let directory = std::env::temp_dir();
+        let key_path = directory.join("c1_test_cli_results.key");
+        let results_path = directory.join("c1_test_cli_results.json");
+        let _ = tokio::fs::remove_file(&key_path).await;
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store)
+            .with_publisher(ResultsPublisher::new(key_path.to_str().unwrap(), results_path.to_str().unwrap()));
+
+        handle_line("voter Tux Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        let closing = handle_line("close", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        let public_key = tokio::fs::read_to_string(format!("{}.pub", key_path.to_str().unwrap())).await.expect("clé publique absente");
+        let other_key = hex::encode(ed25519_dalek::SigningKey::from_bytes(&[7; 32]).verifying_key().as_bytes());
+
+        assert!(closing.ends_with(&format!("Résultats signés publiés dans {}", results_path.to_str().unwrap())));
+        assert_eq!("Signature valide : ces résultats sont authentiques.",handle_line(&format!("verify-results {}", public_key), &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Signature invalide : ces résultats ont été modifiés ou signés avec une autre clé.",handle_line(&format!("verify-results {}", other_key), &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Commande 'verify-results' invalide, attendu : verify-results <clé publique>.",handle_line("verify-results", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+    }
+
+    #[tokio::test]
+    async fn test_revote_and_revoke()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open).with_revote(true);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store);
+
+        let first = handle_line("voter Tux Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        let second = handle_line("voter Tux Louise", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        let (outcome, code) = second.split_once(", code de suivi : ").expect("code de suivi absent");
+        let (_, first_code) = first.split_once(", code de suivi : ").expect("code de suivi absent");
+        assert_eq!("a voté pour Voter(\"Tux\") Candidate(\"Louise\")",outcome);
+        assert_eq!("Code de suivi ou code de vote incorrect, retrait refusé pour Voter(\"Tux\")",handle_line(&format!("revoke Tux {}", first_code), &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Vote retiré pour Voter(\"Tux\")",handle_line(&format!("revoke Tux {}", code), &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Aucun vote à retirer pour Voter(\"Tux\")",handle_line(&format!("revoke Tux {}", code), &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Commande 'revoke' invalide, attendu : revoke <votant> <code>.",handle_line("revoke Tux", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+    }
+
+    #[tokio::test]
+    async fn test_proxy_vote()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store);
+
+        assert_eq!("Aucune procuration, vote refusé pour Voter(\"Tux\") au nom de Voter(\"Gnu\")",handle_line("voter Tux Louis --for Gnu", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Procuration enregistrée : Voter(\"Tux\") au nom de Voter(\"Gnu\")",handle_line("proxy Tux Gnu", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Procuration refusée : le mandant a déjà donné procuration",handle_line("proxy Linus Gnu", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("a voté pour Voter(\"Gnu\") Candidate(\"Louis\")",untracked(handle_line("voter Tux --for Gnu Louis", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne")));
+        assert_eq!("Votant : AttendenceSheet({Voter(\"Gnu\"): ByProxy(Voter(\"Tux\"))})",handle_line("votants", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Commande 'voter' invalide, veuillez spécifier un électeur.",handle_line("voter Tux Louis --for", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+    }
+
+    #[tokio::test]
+    async fn test_admin_token_gates_privileged_commands()
+    {
+
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine = VotingMachine::new(candidates);
+
+        let store = MemoryStore::new(voting_machine).await.expect("erreur lors de la creation de la memoire");
+        let lexicon: Lexicon = FRENCH;
+
+        let controller  = VotingController::new(store).with_admin_token("s3cr3t");
+        let denied = "Permission refusée : commande réservée à l'administrateur (admin <jeton> <commande>).";
+
+        assert_eq!(denied,handle_line("open", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!(denied,handle_line("admin wrong open", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!(denied,handle_line("admin", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Candidat ajouté : Candidate(\"Louise\")",handle_line("admin s3cr3t add-candidate Louise", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Phase : Ouvert",handle_line("admin s3cr3t open", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        handle_line("voter Tux Louise", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne");
+        assert_eq!(denied,handle_line("votants", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert_eq!("Les résultats seront disponibles à la clôture du scrutin.",handle_line("scores", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne"));
+        assert!(handle_line("admin s3cr3t scores", &controller, &lexicon).await.expect("erreur lors de lecture de la ligne").contains("Candidate(\"Louise\"): Score(1)"));
+    }
 }
\ No newline at end of file
@@ -1,3 +1,5 @@
+use crate::domain::MAX_GRADE;
+
 #[derive(PartialEq, Eq, Clone)]
 
 pub struct Lexicon{
@@ -8,11 +10,93 @@ pub struct Lexicon{
     pub has_voted_blank:  &'static str,
     pub has_already_voted: &'static str,
     pub has_voted_for: &'static str,
+    pub unknown_candidates_ignored: &'static str,
     pub actual_score : &'static str,
     pub menu: &'static str,
     pub invalid_command_vote: &'static str,
     pub unokwn_command: &'static str,
     pub scores : &'static str,
+    pub round: &'static str,
+    pub eliminated: &'static str,
+    pub transfers: &'static str,
+    pub exhausted: &'static str,
+    pub winner: &'static str,
+    pub no_winner: &'static str,
+    pub pairwise: &'static str,
+    pub condorcet_winner: &'static str,
+    pub no_condorcet_winner: &'static str,
+    pub schulze_winners: &'static str,
+    pub positional_scores: &'static str,
+    pub invalid_positional_rule: &'static str,
+    pub quota: &'static str,
+    pub elected: &'static str,
+    pub invalid_surplus_transfer: &'static str,
+    pub grade_labels: [&'static str; MAX_GRADE + 1],
+    pub majority_grade: &'static str,
+    pub average: &'static str,
+    pub ranking: &'static str,
+    pub second_round: &'static str,
+    pub current_round: &'static str,
+    pub phase: &'static str,
+    pub phase_draft: &'static str,
+    pub phase_open: &'static str,
+    pub phase_closed: &'static str,
+    pub phase_certified: &'static str,
+    pub transition_refused: &'static str,
+    pub election_not_open: &'static str,
+    pub election_closed: &'static str,
+    pub results_hidden: &'static str,
+    pub opens_in: &'static str,
+    pub closes_in: &'static str,
+    pub no_deadline: &'static str,
+    pub not_registered: &'static str,
+    pub unknown_code: &'static str,
+    pub code_already_used: &'static str,
+    pub turnout: &'static str,
+    pub no_roll: &'static str,
+    pub ballot_received: &'static str,
+    pub tracking_code: &'static str,
+    pub ballot_found: &'static str,
+    pub ballot_not_found: &'static str,
+    pub invalid_command_verify: &'static str,
+    pub log_head: &'static str,
+    pub schedule_error: &'static str,
+    pub election_resumed: &'static str,
+    pub log_intact: &'static str,
+    pub log_entries: &'static str,
+    pub log_broken: &'static str,
+    pub log_scores_mismatch: &'static str,
+    pub results_published: &'static str,
+    pub results_valid: &'static str,
+    pub results_invalid: &'static str,
+    pub results_unreadable: &'static str,
+    pub invalid_command_verify_results: &'static str,
+    pub tally_revealed: &'static str,
+    pub reveal_refused: &'static str,
+    pub tally_key_unreadable: &'static str,
+    pub private_key_written: &'static str,
+    pub public_key_written: &'static str,
+    pub invalid_command_reveal: &'static str,
+    pub permission_denied: &'static str,
+    pub candidate_added: &'static str,
+    pub candidate_refused: &'static str,
+    pub invalid_command_add_candidate: &'static str,
+    pub revoked: &'static str,
+    pub nothing_to_revoke: &'static str,
+    pub revocation_not_allowed: &'static str,
+    pub revocation_refused: &'static str,
+    pub invalid_command_revoke: &'static str,
+    pub no_proxy: &'static str,
+    pub on_behalf_of: &'static str,
+    pub proxy_registered: &'static str,
+    pub proxy_refused: &'static str,
+    pub proxy_self: &'static str,
+    pub proxy_already_delegated: &'static str,
+    pub proxy_limit_reached: &'static str,
+    pub proxy_not_registered: &'static str,
+    pub proxy_already_voted: &'static str,
+    pub proxy_election_closed: &'static str,
+    pub invalid_command_proxy: &'static str,
 }
 
 
@@ -10,17 +10,125 @@ pub const ENGLISH : Lexicon = Lexicon{
             has_voted_blank: "has cast a blank vote",
             has_already_voted: "has already voted",
             has_voted_for: "has voted for",
+            unknown_candidates_ignored: "unknown candidates ignored:",
             actual_score: "Current scores",
             menu: r#"
-There are 4 available commands:
+There are 29 available commands:
 1) voter Tux Nixos -> Vote for Nixos as Tux
-2) voter Tux -> Vote blank as Tux
-3) votants -> Show the list of voters
-4) scores -> Display candidate scores
+2) voter Tux Nixos Debian Arch -> Rank Nixos, then Debian, then Arch as Tux
+3) voter Tux Nixos,Debian -> Approve Nixos and Debian as Tux (with --method approval)
+4) voter Tux Nixos=5 Debian=2 -> Grade Nixos 5 and Debian 2 out of 5 as Tux (with --method judgment)
+5) voter Tux -> Vote blank as Tux
+6) voter Tux Nixos --for Gnu -> Vote for Nixos on behalf of Gnu, who gave Tux a proxy
+7) revoke Tux <code> -> Withdraw Tux's vote before closing, with its tracking code or voting code (with --revote, which also lets voters vote again)
+8) votants -> Show the list of voters
+9) scores -> Display candidate scores
+10) irv -> Display the instant-runoff tally round by round
+11) pairwise -> Display pairwise duels, the Condorcet winner or the Schulze winner
+12) positional borda -> Display positional scores (borda, dowdall or a scale such as 3-2-1)
+13) stv -> Display the single transferable vote for --seats seats (gregory or meek)
+14) grades -> Display the grade distribution, majority grade and average of each candidate
+15) close-round -> Close the current round (a second round between the top two is opened without an absolute majority)
+16) rounds -> Display the results of closed rounds and the current round
+17) open -> Open the election
+18) close -> Close the election and reveal the results
+19) reveal tally.key -> Decrypt the tally of a closed encrypted election with the election's private key
+20) certify -> Certify the results and freeze the machine
+21) phase -> Display the current phase of the election
+22) time -> Display the time remaining before the scheduled opening or closing
+23) turnout -> Display the turnout against the electoral roll
+24) verify <code> -> Check that the ballot with this tracking code is in the ballot box
+25) verify-log -> Recompute the ballot log chain and the scores from the logged ballots
+26) verify-results <public key> -> Check the signature of the published results
+27) add-candidate Gentoo -> Add a candidate while the election is a draft
+28) proxy Tux Gnu -> Register Gnu's proxy to Tux (at most --proxy-limit proxies per holder, or --proxies file.csv)
+29) admin <token> <command> -> Run a restricted command (votants, scores before closing, open, close, reveal, certify, close-round, add-candidate, proxy)
+With --issue-codes, give your one-time voting code instead of your name: voter K7QM-2XRB-T9DE Nixos
 "#,
             invalid_command_vote: "Invalid 'voter' command, please specify a voter.",
             unokwn_command: "Unknown command. Please enter a valid command.",
             scores: "Scores",
+            round: "Round",
+            eliminated: "eliminated",
+            transfers: "transfers",
+            exhausted: "exhausted ballots",
+            winner: "Winner",
+            no_winner: "No winner (tie)",
+            pairwise: "Pairwise duels",
+            condorcet_winner: "Condorcet winner",
+            no_condorcet_winner: "No Condorcet winner (cycle)",
+            schulze_winners: "Schulze winner(s)",
+            positional_scores: "Positional scores",
+            invalid_positional_rule: "Invalid 'positional' command, expected borda, dowdall or a scale such as 3-2-1.",
+            quota: "quota",
+            elected: "Elected",
+            invalid_surplus_transfer: "Invalid 'stv' command, expected gregory or meek.",
+            grade_labels: ["Reject", "Poor", "Fair", "Good", "Very good", "Excellent"],
+            majority_grade: "majority grade",
+            average: "average",
+            ranking: "Ranking",
+            second_round: "No absolute majority, second round between",
+            current_round: "current",
+            phase: "Phase",
+            phase_draft: "Draft",
+            phase_open: "Open",
+            phase_closed: "Closed",
+            phase_certified: "Certified",
+            transition_refused: "Transition refused",
+            election_not_open: "The election is not open yet, vote refused for",
+            election_closed: "The election is closed, vote refused for",
+            results_hidden: "Results are available once the election is closed.",
+            opens_in: "Opening in",
+            closes_in: "Closing in",
+            no_deadline: "No opening or closing is scheduled.",
+            not_registered: "is not on the electoral roll, vote refused for",
+            unknown_code: "Unknown voting code, vote refused for",
+            code_already_used: "Voting code already used, vote refused for",
+            turnout: "Turnout",
+            no_roll: "voters, no electoral roll",
+            ballot_received: "Ballot received from",
+            tracking_code: "tracking code",
+            ballot_found: "This ballot is in the ballot box and counted in the tally.",
+            ballot_not_found: "No ballot matches this tracking code.",
+            invalid_command_verify: "Invalid 'verify' command, please give a tracking code.",
+            log_head: "Final ballot log hash",
+            schedule_error: "Schedule error",
+            election_resumed: "Resuming the saved election (start with --reset for a new one).",
+            log_intact: "Ballot log intact",
+            log_entries: "entries",
+            log_broken: "Broken chain at entries",
+            log_scores_mismatch: "Stored scores do not match the logged ballots for rounds",
+            results_published: "Signed results published in",
+            results_valid: "Valid signature: these results are authentic.",
+            results_invalid: "Invalid signature: these results were edited or signed with another key.",
+            results_unreadable: "Unreadable results file or public key.",
+            invalid_command_verify_results: "Invalid 'verify-results' command, expected: verify-results <public key>.",
+            tally_revealed: "Tally decrypted.",
+            reveal_refused: "Decryption refused: the election is not encrypted and closed, or the key does not match.",
+            tally_key_unreadable: "Unreadable election private key.",
+            private_key_written: "Private key",
+            public_key_written: "public key",
+            invalid_command_reveal: "Invalid 'reveal' command, expected: reveal <private key file>.",
+            permission_denied: "Permission denied: this command is restricted to the administrator (admin <token> <command>).",
+            candidate_added: "Candidate added",
+            candidate_refused: "Candidate refused, the election is no longer a draft or the candidate already exists",
+            invalid_command_add_candidate: "Invalid 'add-candidate' command, please give a candidate.",
+            revoked: "Vote withdrawn for",
+            nothing_to_revoke: "No vote to withdraw for",
+            revocation_not_allowed: "Changing votes is not allowed in this election, withdrawal refused for",
+            revocation_refused: "Wrong tracking code or voting code, withdrawal refused for",
+            invalid_command_revoke: "Invalid 'revoke' command, expected: revoke <voter> <code>.",
+            no_proxy: "No proxy, vote refused for",
+            on_behalf_of: "on behalf of",
+            proxy_registered: "Proxy registered",
+            proxy_refused: "Proxy refused",
+            proxy_self: "a voter cannot be their own proxy",
+            proxy_already_delegated: "the principal has already given a proxy",
+            proxy_limit_reached: "the proxy already holds the maximum number of proxies",
+            proxy_not_registered: "both proxy and principal must be on the electoral roll",
+            proxy_already_voted: "the principal has already voted",
+            proxy_election_closed: "the election is closed",
+            invalid_command_proxy: "Invalid 'proxy' command, expected: proxy <proxy> <principal>.",
         
 };
 
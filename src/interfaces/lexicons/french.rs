@@ -10,17 +10,125 @@ pub const FRENCH : Lexicon = Lexicon{
         has_voted_blank: "a voté blanc",
         has_already_voted: "a déjà voté",
         has_voted_for: "a voté pour",
+        unknown_candidates_ignored: "candidats inconnus ignorés :",
         actual_score: "Scores actuels",
         menu: r#"
-Il y a 4 commandes disponibles :
+Il y a 29 commandes disponibles :
 1) voter Tux Nixos -> Voter pour Nixos en tant que Tux
-2) voter Tux -> Voter blanc en tant que Tux
-3) votants -> Afficher la liste des votants
-4) scores -> Afficher les scores des candidats
+2) voter Tux Nixos Debian Arch -> Classer Nixos, puis Debian, puis Arch en tant que Tux
+3) voter Tux Nixos,Debian -> Approuver Nixos et Debian en tant que Tux (avec --method approval)
+4) voter Tux Nixos=5 Debian=2 -> Noter Nixos 5 et Debian 2 sur 5 en tant que Tux (avec --method judgment)
+5) voter Tux -> Voter blanc en tant que Tux
+6) voter Tux Nixos --for Gnu -> Voter pour Nixos au nom de Gnu, qui a donné procuration à Tux
+7) revoke Tux <code> -> Retirer le vote de Tux avant la clôture, avec son code de suivi ou son code de vote (avec --revote, qui permet aussi de voter à nouveau)
+8) votants -> Afficher la liste des votants
+9) scores -> Afficher les scores des candidats
+10) irv -> Afficher le dépouillement par vote alternatif, tour par tour
+11) pairwise -> Afficher les duels, le vainqueur de Condorcet ou à défaut celui de Schulze
+12) positional borda -> Afficher les scores positionnels (borda, dowdall ou un barème comme 3-2-1)
+13) stv -> Afficher le vote unique transférable pour --seats sièges (gregory ou meek)
+14) grades -> Afficher la répartition des mentions, la mention majoritaire et la moyenne de chaque candidat
+15) close-round -> Clore le tour en cours (sans majorité absolue, un second tour est ouvert entre les deux premiers)
+16) rounds -> Afficher les résultats des tours clos et du tour en cours
+17) open -> Ouvrir le scrutin
+18) close -> Clore le scrutin et dévoiler les résultats
+19) reveal tally.key -> Déchiffrer le dépouillement d'un scrutin chiffré et clos avec la clé privée de l'élection
+20) certify -> Certifier les résultats et figer la machine
+21) phase -> Afficher la phase du scrutin
+22) time -> Afficher le temps restant avant l'ouverture ou la clôture programmée
+23) turnout -> Afficher la participation rapportée à la liste électorale
+24) verify <code> -> Vérifier que le bulletin portant ce code de suivi est dans l'urne
+25) verify-log -> Recalculer le chaînage du registre et les scores à partir des bulletins enregistrés
+26) verify-results <clé publique> -> Vérifier la signature des résultats publiés
+27) add-candidate Gentoo -> Ajouter un candidat tant que le scrutin est en brouillon
+28) proxy Tux Gnu -> Enregistrer la procuration de Gnu à Tux (au plus --proxy-limit procurations par mandataire, ou --proxies fichier.csv)
+29) admin <jeton> <commande> -> Exécuter une commande réservée (votants, scores avant la clôture, open, close, reveal, certify, close-round, add-candidate, proxy)
+Avec --issue-codes, indiquez votre code de vote à usage unique au lieu de votre nom : voter K7QM-2XRB-T9DE Nixos
 "#,
         invalid_command_vote: "Commande 'voter' invalide, veuillez spécifier un électeur.",
         unokwn_command: "Commande inconnue. Tapez une commande valide.",
         scores: "Scores",
+        round: "Tour",
+        eliminated: "éliminé",
+        transfers: "reports",
+        exhausted: "bulletins épuisés",
+        winner: "Vainqueur",
+        no_winner: "Pas de vainqueur (égalité)",
+        pairwise: "Duels",
+        condorcet_winner: "Vainqueur de Condorcet",
+        no_condorcet_winner: "Pas de vainqueur de Condorcet (cycle)",
+        schulze_winners: "Vainqueur(s) selon Schulze",
+        positional_scores: "Scores positionnels",
+        invalid_positional_rule: "Commande 'positional' invalide, attendu : borda, dowdall ou un barème comme 3-2-1.",
+        quota: "quota",
+        elected: "Élus",
+        invalid_surplus_transfer: "Commande 'stv' invalide, attendu : gregory ou meek.",
+        grade_labels: ["À rejeter", "Insuffisant", "Passable", "Bien", "Très bien", "Excellent"],
+        majority_grade: "mention majoritaire",
+        average: "moyenne",
+        ranking: "Classement",
+        second_round: "Pas de majorité absolue, second tour entre",
+        current_round: "en cours",
+        phase: "Phase",
+        phase_draft: "Brouillon",
+        phase_open: "Ouvert",
+        phase_closed: "Clos",
+        phase_certified: "Certifié",
+        transition_refused: "Transition refusée",
+        election_not_open: "Le scrutin n'est pas encore ouvert, vote refusé pour",
+        election_closed: "Le scrutin est clos, vote refusé pour",
+        results_hidden: "Les résultats seront disponibles à la clôture du scrutin.",
+        opens_in: "Ouverture dans",
+        closes_in: "Clôture dans",
+        no_deadline: "Aucune ouverture ni clôture n'est programmée.",
+        not_registered: "n'est pas inscrit sur la liste électorale, vote refusé pour",
+        unknown_code: "Code de vote inconnu, vote refusé pour",
+        code_already_used: "Code de vote déjà utilisé, vote refusé pour",
+        turnout: "Participation",
+        no_roll: "votants, pas de liste électorale",
+        ballot_received: "Bulletin reçu de",
+        tracking_code: "code de suivi",
+        ballot_found: "Ce bulletin est dans l'urne et compté au dépouillement.",
+        ballot_not_found: "Aucun bulletin ne correspond à ce code de suivi.",
+        invalid_command_verify: "Commande 'verify' invalide, veuillez indiquer un code de suivi.",
+        log_head: "Empreinte finale du registre",
+        schedule_error: "Erreur du calendrier",
+        election_resumed: "Reprise du scrutin enregistré (relancer avec --reset pour repartir d'un scrutin vierge).",
+        log_intact: "Registre intègre",
+        log_entries: "entrées",
+        log_broken: "Chaînage rompu aux entrées",
+        log_scores_mismatch: "Scores conservés non conformes aux bulletins enregistrés pour les tours",
+        results_published: "Résultats signés publiés dans",
+        results_valid: "Signature valide : ces résultats sont authentiques.",
+        results_invalid: "Signature invalide : ces résultats ont été modifiés ou signés avec une autre clé.",
+        results_unreadable: "Fichier de résultats ou clé publique illisible.",
+        invalid_command_verify_results: "Commande 'verify-results' invalide, attendu : verify-results <clé publique>.",
+        tally_revealed: "Dépouillement déchiffré.",
+        reveal_refused: "Déchiffrement refusé : le scrutin n'est pas chiffré et clos, ou la clé ne correspond pas.",
+        tally_key_unreadable: "Clé privée de l'élection illisible.",
+        private_key_written: "Clé privée",
+        public_key_written: "clé publique",
+        invalid_command_reveal: "Commande 'reveal' invalide, attendu : reveal <fichier de la clé privée>.",
+        permission_denied: "Permission refusée : commande réservée à l'administrateur (admin <jeton> <commande>).",
+        candidate_added: "Candidat ajouté",
+        candidate_refused: "Candidat refusé, le scrutin n'est plus en brouillon ou le candidat existe déjà",
+        invalid_command_add_candidate: "Commande 'add-candidate' invalide, veuillez spécifier un candidat.",
+        revoked: "Vote retiré pour",
+        nothing_to_revoke: "Aucun vote à retirer pour",
+        revocation_not_allowed: "Le changement de vote n'est pas permis pour ce scrutin, retrait refusé pour",
+        revocation_refused: "Code de suivi ou code de vote incorrect, retrait refusé pour",
+        invalid_command_revoke: "Commande 'revoke' invalide, attendu : revoke <votant> <code>.",
+        no_proxy: "Aucune procuration, vote refusé pour",
+        on_behalf_of: "au nom de",
+        proxy_registered: "Procuration enregistrée",
+        proxy_refused: "Procuration refusée",
+        proxy_self: "un votant ne peut pas être son propre mandataire",
+        proxy_already_delegated: "le mandant a déjà donné procuration",
+        proxy_limit_reached: "le mandataire détient déjà le nombre maximal de procurations",
+        proxy_not_registered: "le mandataire et le mandant doivent être inscrits sur la liste électorale",
+        proxy_already_voted: "le mandant a déjà voté",
+        proxy_election_closed: "le scrutin est clos",
+        invalid_command_proxy: "Commande 'proxy' invalide, attendu : proxy <mandataire> <mandant>.",
     
 };
 
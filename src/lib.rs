@@ -1,8 +1,15 @@
 pub mod configuration;
 pub mod app_builder;
+mod ballot_log;
 mod domain;
+mod electoral_roll;
+mod encryption;
+mod proxies;
+mod results;
 mod storage;
 mod storages;
+mod tallies;
 mod use_cases;
+mod voting_codes;
 mod interfaces;
 mod services;
\ No newline at end of file
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap as Map;
+
+use anyhow::Context;
+use tokio::fs;
+
+use crate::domain::Voter;
+
+/// Nombre de procurations qu'un mandataire peut détenir, faute d'autre indication.
+pub const DEFAULT_PROXY_LIMIT: usize = 1;
+
+const CSV_HEADER: &str = "proxy";
+
+/// Procurations enregistrées : chaque mandant désigne un mandataire, qui vote à sa place.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProxyRegistry {
+    pub limit: usize,
+    pub mandates: Map<Voter, Voter>,
+}
+
+/// Motif de refus d'une procuration.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ProxyRejection {
+    SelfProxy,
+    AlreadyDelegated,
+    LimitReached,
+    NotRegistered,
+    AlreadyVoted,
+    ElectionClosed,
+}
+
+impl Default for ProxyRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_PROXY_LIMIT)
+    }
+}
+
+impl ProxyRegistry {
+    pub fn new(limit: usize) -> Self {
+        Self { limit, mandates: Map::new() }
+    }
+
+    /// Un mandant ne donne qu'une procuration, un mandataire en détient au plus `limit`.
+    pub fn register(&mut self, proxy: Voter, principal: Voter) -> Result<(), ProxyRejection> {
+        if proxy == principal {
+            return Err(ProxyRejection::SelfProxy);
+        }
+        if self.mandates.contains_key(&principal) {
+            return Err(ProxyRejection::AlreadyDelegated);
+        }
+        if self.mandates_of(&proxy) >= self.limit {
+            return Err(ProxyRejection::LimitReached);
+        }
+
+        self.mandates.insert(principal, proxy);
+        Ok(())
+    }
+
+    pub fn holds(&self, proxy: &Voter, principal: &Voter) -> bool {
+        self.mandates.get(principal) == Some(proxy)
+    }
+
+    pub fn mandates_of(&self, proxy: &Voter) -> usize {
+        self.mandates.values().filter(|holder| *holder == proxy).count()
+    }
+}
+
+/// Lit les procurations depuis un fichier CSV : mandataire puis mandant sur chaque
+/// ligne, en-tête `proxy` facultatif.
+pub async fn load_proxies(path: &str) -> anyhow::Result<Vec<(Voter, Voter)>> {
+    let content = fs::read_to_string(path)
+        .await
+        .with_context(|| format!("impossible de lire les procurations {}", path))?;
+
+    parse_csv(&content).with_context(|| format!("procurations {} invalides", path))
+}
+
+fn parse_csv(content: &str) -> anyhow::Result<Vec<(Voter, Voter)>> {
+    let mut mandates = vec![];
+
+    for (index, line) in content.lines().enumerate() {
+        let mut columns = line.split(',').map(str::trim);
+        match (columns.next().unwrap_or(""), columns.next()) {
+            ("", None) => {}
+            (proxy, _) if index == 0 && proxy.eq_ignore_ascii_case(CSV_HEADER) => {}
+            (proxy, Some(principal)) if !proxy.is_empty() && !principal.is_empty() => {
+                mandates.push((Voter(proxy.to_string()), Voter(principal.to_string())));
+            }
+            _ => anyhow::bail!("ligne {} : mandataire et mandant attendus", index + 1),
+        }
+    }
+
+    Ok(mandates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voter(name: &str) -> Voter {
+        Voter(String::from(name))
+    }
+
+    #[test]
+    fn test_register_enforces_limit() {
+        let mut registry = ProxyRegistry::new(1);
+
+        assert_eq!(registry.register(voter("Alice"), voter("Alice")), Err(ProxyRejection::SelfProxy));
+        assert_eq!(registry.register(voter("Alice"), voter("Bob")), Ok(()));
+        assert_eq!(registry.register(voter("Carol"), voter("Bob")), Err(ProxyRejection::AlreadyDelegated));
+        assert_eq!(registry.register(voter("Alice"), voter("Carol")), Err(ProxyRejection::LimitReached));
+        assert!(registry.holds(&voter("Alice"), &voter("Bob")));
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let mandates = parse_csv("proxy,principal\nAlice, Bob\n\n").expect("procurations invalides");
+
+        assert_eq!(mandates, vec![(voter("Alice"), voter("Bob"))]);
+        assert!(parse_csv("Alice\n").is_err());
+    }
+}
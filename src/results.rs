@@ -0,0 +1,189 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::domain::{RoundOutcome, Schedule, Scoreboard, VotingMachine};
+use crate::storages::file::write_private_key;
+
+/// Tour clos d'un scrutin à deux tours, tel qu'il figure au procès-verbal.
+#[derive(Debug, Clone, Serialize)]
+pub struct RoundRecord {
+    pub scoreboard: Scoreboard,
+    pub outcome: RoundOutcome,
+}
+
+/// Procès-verbal du scrutin, tel qu'il est signé puis publié.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultsDocument {
+    pub scoreboard: Scoreboard,
+    pub rounds: Vec<RoundRecord>,
+    pub voted: usize,
+    pub registered: Option<usize>,
+    pub chain_head: String,
+    pub opens_at: Option<DateTime<Utc>>,
+    pub closes_at: Option<DateTime<Utc>>,
+    pub closed_at: DateTime<Utc>,
+}
+
+impl ResultsDocument {
+    pub fn new(voting_machine: &VotingMachine, schedule: &Schedule, closed_at: DateTime<Utc>) -> Self {
+        Self {
+            scoreboard: voting_machine.get_scoreboard().clone(),
+            rounds: voting_machine
+                .get_rounds()
+                .iter()
+                .map(|round| RoundRecord { scoreboard: round.scoreboard.clone(), outcome: round.outcome.clone() })
+                .collect(),
+            voted: voting_machine.voted(),
+            registered: voting_machine.turnout().map(|turnout| turnout.registered.0),
+            chain_head: voting_machine.get_log().head().to_string(),
+            opens_at: schedule.opens_at,
+            closes_at: schedule.closes_at,
+            closed_at,
+        }
+    }
+}
+
+/// Résultats signés : la signature porte sur le JSON de `results`, clés triées.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedResults {
+    pub results: serde_json::Value,
+    pub public_key: String,
+    pub signature: String,
+}
+
+impl SignedResults {
+    pub fn sign(document: &ResultsDocument, key: &SigningKey) -> anyhow::Result<Self> {
+        let results = serde_json::to_value(document)?;
+        let signature = key.sign(&serde_json::to_vec(&results)?);
+
+        Ok(Self {
+            results,
+            public_key: hex::encode(key.verifying_key().as_bytes()),
+            signature: hex::encode(signature.to_bytes()),
+        })
+    }
+
+    /// Vérifie la signature avec la clé publique fournie, pas avec celle du document.
+    pub fn verify(&self, public_key: &str) -> anyhow::Result<bool> {
+        let public_key: [u8; 32] = hex::decode(public_key)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("clé publique de longueur invalide"))?;
+        let signature: [u8; 64] = hex::decode(&self.signature)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("signature de longueur invalide"))?;
+
+        let key = VerifyingKey::from_bytes(&public_key)?;
+        let payload = serde_json::to_vec(&self.results)?;
+
+        Ok(key.verify(&payload, &Signature::from_bytes(&signature)).is_ok())
+    }
+}
+
+/// Signe et publie les résultats à la clôture, avec une clé Ed25519 locale.
+#[derive(Debug, Clone)]
+pub struct ResultsPublisher {
+    key_path: String,
+    results_path: String,
+}
+
+impl ResultsPublisher {
+    pub fn new(key_path: &str, results_path: &str) -> Self {
+        Self {
+            key_path: key_path.to_string(),
+            results_path: results_path.to_string(),
+        }
+    }
+
+    pub fn get_results_path(&self) -> &str {
+        &self.results_path
+    }
+
+    /// Lit la clé de signature, ou la génère au premier usage. La clé privée n'est
+    /// lisible que par son propriétaire ; la clé publique est écrite à côté, dans un fichier `.pub`.
+    async fn signing_key(&self) -> anyhow::Result<SigningKey> {
+        if let Ok(secret) = fs::read_to_string(&self.key_path).await {
+            let secret: [u8; 32] = hex::decode(secret.trim())?
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("clé de signature {} invalide", self.key_path))?;
+            return Ok(SigningKey::from_bytes(&secret));
+        }
+
+        let key = SigningKey::generate(&mut OsRng);
+        write_private_key(&self.key_path, &hex::encode(key.to_bytes())).await?;
+        fs::write(format!("{}.pub", self.key_path), hex::encode(key.verifying_key().as_bytes())).await?;
+
+        Ok(key)
+    }
+
+    pub async fn publish(&self, document: &ResultsDocument) -> anyhow::Result<SignedResults> {
+        let signed = SignedResults::sign(document, &self.signing_key().await?)?;
+        fs::write(&self.results_path, serde_json::to_string_pretty(&signed)?).await?;
+
+        Ok(signed)
+    }
+}
+
+/// Vérifie un fichier de résultats signés contre une clé publique en hexadécimal.
+pub async fn verify_results_file(path: &str, public_key: &str) -> anyhow::Result<bool> {
+    let content = fs::read_to_string(path)
+        .await
+        .with_context(|| format!("impossible de lire les résultats {}", path))?;
+    let signed: SignedResults = serde_json::from_str(&content)?;
+
+    signed.verify(public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{Candidate, Phase};
+
+    use super::*;
+
+    fn document() -> ResultsDocument {
+        let voting_machine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Closed);
+        ResultsDocument::new(&voting_machine, &Schedule::default(), Utc::now())
+    }
+
+    #[test]
+    fn test_document_includes_closed_rounds() {
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise")), Candidate(String::from("Tux"))];
+        let mut voting_machine = VotingMachine::new(candidates).with_phase(Phase::Open);
+        voting_machine.close_round();
+
+        let document = serde_json::to_value(ResultsDocument::new(&voting_machine, &Schedule::default(), Utc::now())).expect("serialisation impossible");
+
+        assert_eq!(document["rounds"].as_array().map(Vec::len), Some(1));
+        assert_eq!(document["rounds"][0]["outcome"]["SecondRound"][0], "Louis");
+    }
+
+    #[test]
+    fn test_edited_results_fail_verification() {
+        let key = SigningKey::generate(&mut OsRng);
+        let mut signed = SignedResults::sign(&document(), &key).expect("signature impossible");
+        let public_key = hex::encode(key.verifying_key().as_bytes());
+
+        assert!(signed.verify(&public_key).expect("verification impossible"));
+
+        signed.results["voted"] = serde_json::json!(42);
+
+        assert!(!signed.verify(&public_key).expect("verification impossible"));
+    }
+
+    #[tokio::test]
+    async fn test_publish_then_verify_file() {
+        let directory = std::env::temp_dir();
+        let key_path = directory.join("c1_test_results.key");
+        let results_path = directory.join("c1_test_results.json");
+        let _ = fs::remove_file(&key_path).await;
+
+        let publisher = ResultsPublisher::new(key_path.to_str().unwrap(), results_path.to_str().unwrap());
+        publisher.publish(&document()).await.expect("publication impossible");
+        let public_key = fs::read_to_string(format!("{}.pub", key_path.to_str().unwrap())).await.expect("clé publique absente");
+
+        assert!(verify_results_file(results_path.to_str().unwrap(), &public_key).await.expect("verification impossible"));
+    }
+}
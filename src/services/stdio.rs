@@ -14,8 +14,8 @@ pub struct StdioService<Store>
 #[async_trait]
 impl <Store : Storage + Send + Sync> Service<Store> for StdioService<Store>{
 
-    fn new(port:u16,lexicon:Lexicon,controller:VotingController<Store>) -> Self {
-        Self { lexicon: lexicon, controller: controller }
+    fn new(_port:u16,lexicon:Lexicon,controller:VotingController<Store>) -> Self {
+        Self { lexicon, controller }
     }
 
     async fn serve(&self) -> Result<(), anyhow::Error>
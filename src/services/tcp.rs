@@ -25,7 +25,7 @@ impl<Store: Storage + Send + Sync + Clone + 'static> Service<Store> for TcpServi
             let (mut stream, _) = listener.accept().await?;
             
             let lexicon = self.lexicon.clone();
-            let mut controller = self.controller.clone();
+            let controller = self.controller.clone();
 
             tokio::spawn(async move {
                 let (reader, mut writer) = stream.split();
@@ -34,7 +34,7 @@ impl<Store: Storage + Send + Sync + Clone + 'static> Service<Store> for TcpServi
                 loop {
                     match lines.next_line().await {
                         Ok(Some(line)) => {
-                            match handle_line(line.as_str(), &mut controller, &lexicon).await {
+                            match handle_line(line.as_str(), &controller, &lexicon).await {
                                 Ok(response) => {
                                     if let Err(e) = writer.write_all(response.as_bytes()).await {
                                         eprintln!("Erreur d'écriture TCP : {}", e);
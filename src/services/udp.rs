@@ -18,7 +18,7 @@ pub struct UdpService<Store>
 impl <Store : Storage + Send + Sync> Service<Store> for UdpService<Store>{
 
     fn new(port:u16,lexicon:Lexicon,controller:VotingController<Store>) -> Self {
-        Self { port: port, lexicon: lexicon, controller: controller }
+        Self { port, lexicon, controller }
     }
 
 
@@ -32,7 +32,9 @@ impl <Store : Storage + Send + Sync> Service<Store> for UdpService<Store>{
         loop {
             let (size, sender) = socket.recv_from(&mut buffer).await?;
             let received_message = str::from_utf8(&buffer[0..size])?;
-            println!("Received '{}' from {}", received_message.trim(), sender);
+            // Seule la commande est journalisée : les arguments peuvent contenir un vote.
+            let command = received_message.split_whitespace().next().unwrap_or("");
+            println!("Received '{}' from {}", command, sender);
     
             let result = handle_line(received_message, &self.controller, &self.lexicon).await?;
             socket
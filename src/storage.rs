@@ -1,9 +1,161 @@
 use async_trait::async_trait;
-use crate::domain::VotingMachine;
+use chrono::{DateTime, Utc};
+use crate::domain::{Deposit, Phase, Schedule, Scoreboard, VoteOutcome, Voter, VotingMachine};
 
+/// Chaque implémentation garde la machine et la protège elle-même : les lectures
+/// ne copient que ce qu'elles consultent et peuvent se faire pendant un vote.
 #[async_trait]
-pub trait Storage where Self: Sized {
+pub trait Storage where Self: Sized + Send + Sync {
+    /// Vrai si un scrutin est déjà enregistré : `new` le reprendra.
+    async fn exists() -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    /// Reprend le scrutin déjà enregistré, s'il y en a un ; sinon commence avec `machine`.
     async fn new(machine: VotingMachine) -> anyhow::Result<Self>;
-    async fn get_voting_machine(&self) -> anyhow::Result<VotingMachine>;
-    async fn put_voting_machine(&mut self, machine: VotingMachine) -> anyhow::Result<()>;
+    /// Commence avec `machine`, même si un scrutin est déjà enregistré.
+    async fn reset(machine: VotingMachine) -> anyhow::Result<Self> {
+        Self::new(machine).await
+    }
+
+    /// Consulte la machine sans la copier.
+    async fn read<T: Send>(&self, query: impl for<'a> FnOnce(&'a VotingMachine) -> T + Send) -> anyhow::Result<T>;
+
+    /// Applique `action` à la machine, seule à y accéder, puis enregistre ce qui a changé.
+    /// Une action qui échoue laisse la machine intacte ; rien n'est alors enregistré.
+    /// Si l'enregistrement échoue, la machine revient à son dernier état enregistré.
+    async fn update<T: Send>(&self, action: impl for<'a> FnOnce(&'a mut VotingMachine) -> anyhow::Result<T> + Send) -> anyhow::Result<T>;
+
+    /// Porte le votant sur la liste d'émargement et dépose son bulletin, en une seule
+    /// écriture : seul ce dépôt est enregistré, pas la machine entière. Rend faux, sans
+    /// rien enregistrer, si la machine ne l'admet plus à l'instant `now`, le votant ayant
+    /// déjà voté ou l'heure de clôture du calendrier étant passée par exemple.
+    async fn record_ballot(&self, deposit: Deposit, schedule: &Schedule, now: DateTime<Utc>) -> anyhow::Result<bool>;
+
+    /// Retire le bulletin du votant si `credential` le permet, et n'enregistre que le retrait.
+    async fn revoke(&self, voter: &Voter, credential: &str) -> anyhow::Result<VoteOutcome>;
+
+    /// Tableau des scores, sans copier le reste de la machine.
+    async fn scores(&self) -> anyhow::Result<Scoreboard>;
+
+    async fn get_voting_machine(&self) -> anyhow::Result<VotingMachine> {
+        self.read(VotingMachine::clone).await
+    }
+
+    async fn get_phase(&self) -> anyhow::Result<Phase> {
+        self.read(VotingMachine::get_phase).await
+    }
+}
+
+
+/// Cas communs à toutes les implémentations : chaque stockage doit aboutir à la machine
+/// qu'obtient le domaine en votant directement.
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::future::Future;
+
+    use crate::domain::{BallotPaper, Candidate, Casting, TrackingCode};
+    use crate::voting_codes::CodeSheet;
+
+    use super::*;
+
+    fn ballot_paper(voter: &str, candidate: &str) -> BallotPaper {
+        BallotPaper{ voter: Voter(voter.to_string()), candidate: Some(Candidate(candidate.to_string())) }
+    }
+
+    /// Dépose le vote comme le fait `cast` : préparé en lecture, puis enregistré.
+    async fn cast(store: &impl Storage, ballot_paper: BallotPaper, casting: Casting) -> VoteOutcome {
+        let prepared = store.read(|voting_machine| voting_machine.prepare_vote(ballot_paper, &casting)).await.expect("err lors de la lecture");
+        match prepared {
+            Ok((deposit, outcome)) => {
+                assert!(store.record_ballot(deposit, &Schedule::default(), Utc::now()).await.expect("Erreur lors de l'insertion du bulletin"));
+                outcome
+            }
+            Err(rejection) => rejection,
+        }
+    }
+
+    async fn assert_stored(store: &impl Storage, expected: &VotingMachine) {
+        assert_eq!(&store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), expected);
+        assert_eq!(&store.scores().await.expect("err lors de la recuperation des scores"), expected.get_scoreboard());
+    }
+
+    /// Passe chaque cas à un stockage neuf, que `create` ouvre sous le nom du cas.
+    pub async fn run<S: Storage, F: Future<Output = anyhow::Result<S>>>(create: impl Fn(&'static str, VotingMachine) -> F) {
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let open = VotingMachine::new(candidates).with_phase(Phase::Open);
+
+        // Un dépôt préparé deux fois n'est enregistré qu'une fois.
+        let mut expected = open.clone();
+        let store = create("votes", open.clone()).await.expect("Erreur lors de la creation du stockage");
+        let (deposit, _) = store.read(|voting_machine| voting_machine.prepare_vote(ballot_paper("Tux", "Louis"), &Casting::default())).await.expect("err lors de la lecture").expect("vote refuse");
+        assert!(store.record_ballot(deposit.clone(), &Schedule::default(), Utc::now()).await.expect("Erreur lors de l'insertion du bulletin"));
+        assert!(!store.record_ballot(deposit, &Schedule::default(), Utc::now()).await.expect("Erreur lors de l'insertion du bulletin"));
+        expected.vote(ballot_paper("Tux", "Louis"));
+
+        // Un dépôt préparé avant l'heure de clôture n'est plus enregistré après.
+        let (deposit, _) = store.read(|voting_machine| voting_machine.prepare_vote(ballot_paper("Ada", "Louis"), &Casting::default())).await.expect("err lors de la lecture").expect("vote refuse");
+        let closed = Schedule { opens_at: None, closes_at: Some(Utc::now()) };
+        assert!(!store.record_ballot(deposit, &closed, Utc::now()).await.expect("Erreur lors de l'insertion du bulletin"));
+        assert_stored(&store, &expected).await;
+
+        assert_eq!(cast(&store, ballot_paper("Gnu", "Louise"), Casting::default()).await, expected.vote(ballot_paper("Gnu", "Louise")));
+        assert_eq!(cast(&store, ballot_paper("Tux", "Louise"), Casting::default()).await, expected.vote(ballot_paper("Tux", "Louise")));
+        assert_stored(&store, &expected).await;
+
+        // Une action qui échoue après avoir modifié la machine n'en laisse rien.
+        let failed: anyhow::Result<()> = store.update(|voting_machine| {
+            voting_machine.add_candidate(Candidate(String::from("Ada")));
+            voting_machine.vote(ballot_paper("Ada", "Louis"));
+            anyhow::bail!("action refusee")
+        }).await;
+        assert!(failed.is_err());
+        assert_stored(&store, &expected).await;
+
+        // Vote par procuration.
+        let mut expected = open.clone();
+        expected.register_proxy(Voter(String::from("Gnu")), Voter(String::from("Tux"))).expect("procuration refusee");
+        let store = create("proxies", open.clone()).await.expect("Erreur lors de la creation du stockage");
+        store.update(|voting_machine| Ok(voting_machine.register_proxy(Voter(String::from("Gnu")), Voter(String::from("Tux"))))).await.expect("Erreur lors de la procuration").expect("procuration refusee");
+        let casting = Casting { proxy: Some(Voter(String::from("Gnu"))), receipt: None };
+        let outcome = expected.vote_for(&Voter(String::from("Gnu")), |voting_machine| voting_machine.vote(ballot_paper("Tux", "Louis")));
+        assert_eq!(cast(&store, ballot_paper("Tux", "Louis"), casting).await, outcome);
+        assert_stored(&store, &expected).await;
+
+        // Changement de vote, puis retrait avec le code de suivi.
+        let revote = open.clone().with_revote(true);
+        let code = TrackingCode(String::from("ABCD-EFGH-JKLM"));
+        let mut expected = revote.clone();
+        let store = create("revote", revote).await.expect("Erreur lors de la creation du stockage");
+        cast(&store, ballot_paper("Tux", "Louis"), Casting::default()).await;
+        cast(&store, ballot_paper("Tux", "Louise"), Casting { proxy: None, receipt: Some(code.clone()) }).await;
+        cast(&store, ballot_paper("Gnu", "Louis"), Casting::default()).await;
+        expected.vote(ballot_paper("Tux", "Louis"));
+        expected.track(code.clone(), |voting_machine| voting_machine.vote(ballot_paper("Tux", "Louise")));
+        expected.vote(ballot_paper("Gnu", "Louis"));
+        assert_eq!(store.revoke(&Voter(String::from("Tux")), &code.0).await.expect("Erreur lors du retrait"), expected.revoke(&Voter(String::from("Tux")), &code.0));
+        assert_stored(&store, &expected).await;
+
+        // Scrutin secret : plus d'un lot, et des bulletins en attente.
+        let secret = open.clone().with_secret(true);
+        let mut expected = secret.clone();
+        let store = create("secret", secret).await.expect("Erreur lors de la creation du stockage");
+        for index in 0..12 {
+            let candidate = if index % 3 == 0 { "Louise" } else { "Louis" };
+            let voter = format!("Votant {}", index);
+            assert_eq!(cast(&store, ballot_paper(&voter, candidate), Casting::default()).await, expected.vote(ballot_paper(&voter, candidate)));
+        }
+        assert_stored(&store, &expected).await;
+
+        // Codes de vote : la machine ne retient que leur empreinte.
+        let codes = vec![String::from("ABCD-EFGH-JKLM"), String::from("NPQR-STUV-WXYZ")];
+        let coded = open.with_codes(Some(CodeSheet::issue(&codes)));
+        let mut expected = coded.clone();
+        let store = create("codes", coded).await.expect("Erreur lors de la creation du stockage");
+        for code in &codes {
+            assert_eq!(cast(&store, ballot_paper(code, "Louis"), Casting::default()).await, expected.vote(ballot_paper(code, "Louis")));
+        }
+        assert_eq!(cast(&store, ballot_paper(&codes[0], "Louise"), Casting::default()).await, expected.vote(ballot_paper(&codes[0], "Louise")));
+        assert_stored(&store, &expected).await;
+    }
 }
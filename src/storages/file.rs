@@ -1,53 +1,640 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use curve25519_dalek::ristretto::RistrettoPoint;
 use serde::{Deserialize, Serialize};
+use anyhow::Context;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
 use tokio::{
-	fs::File,
-	io::{AsyncReadExt, AsyncWriteExt},
+	fs::{self, File},
+	io::AsyncWriteExt,
 
 };
 use std::collections::BTreeMap as Map;
 use std::collections::BTreeSet as Set;
+use crate::domain::Attendance;
+use crate::domain::Ballot;
 use crate::domain::Candidate;
+use crate::domain::CastBallot;
+use crate::domain::Deposit;
+use crate::domain::ElectoralRoll;
+use crate::domain::Grade;
+use crate::domain::HeadCounts;
+use crate::domain::Phase;
+use crate::domain::RoundOutcome;
+use crate::domain::RoundResult;
 use crate::domain::Score;
+use crate::domain::Schedule;
 use crate::domain::Scoreboard;
+use crate::domain::VoteOutcome;
 use crate::domain::Voter;
+use crate::domain::Weight;
+use crate::domain::WeightedBallot;
+use crate::voting_codes::CodeSheet;
 use crate::{domain::VotingMachine, storage::Storage};
 use crate::domain::AttendenceSheet;
+use crate::encryption::{from_hex, to_hex, Ciphertext, EncryptedScoreboard, HomomorphicTally, PublicKey};
+use crate::ballot_log::{BallotLog, LogEntry, StagedBallot};
+use crate::storages::journal::{Journal, Layout};
+use crate::proxies::{ProxyRegistry, DEFAULT_PROXY_LIMIT};
 
+/// La machine est enregistrée dans le fichier ; chaque dépôt ou retrait de bulletin
+/// n'ajoute qu'une ligne au journal d'à côté, versé dans le fichier par les autres
+/// modifications et tous les `SNAPSHOT_EVERY` votes.
 #[derive(Clone)]
 pub struct FileStore{
-    filepath: String
+    journal: Arc<RwLock<Journal>>,
 }
 const FILEPATH : &str = "machine.json";
 
+/// Journal des votes, à côté du fichier de la machine.
+fn journal_path(filepath: &str) -> String {
+    format!("{}.jsonl", filepath)
+}
+
 impl FileStore{
     
+    /// Repart d'un scrutin vierge : la machine enregistrée dans `filepath` est écrasée.
     pub async fn create(machine: VotingMachine, filepath: &str) -> anyhow::Result<Self> {
-        let mut file = match File::open(filepath).await {
-            Ok(file) => file,
-            Err(_) => File::create(filepath).await?
-        };
-
-		let voting_machine_json = serde_json::to_string(&VotingMachineDao::from(machine))?;
-		file.write_all(voting_machine_json.as_bytes()).await?;
+        let journal = Journal::create(machine, &journal_path(filepath), filepath, Layout::Machine).await?;
+        Ok(Self { journal: Arc::new(RwLock::new(journal)) })
+    }
 
-        Ok(Self {
-            filepath: filepath.to_string(),
-        })
+    /// Reprend le scrutin enregistré dans `filepath` s'il existe, à condition qu'il
+    /// ait les réglages de `machine` ; sinon, commence avec `machine`.
+    pub async fn open(machine: VotingMachine, filepath: &str) -> anyhow::Result<Self> {
+        let journal = Journal::open(machine, &journal_path(filepath), filepath, Layout::Machine).await?;
+        Ok(Self { journal: Arc::new(RwLock::new(journal)) })
     }
     
 }
+
+/// Fichier où la machine est écrite avant d'être renommée sur `filepath`.
+fn temp_path(filepath: &str) -> String {
+    format!("{}.tmp", filepath)
+}
+
+/// Écrit dans un fichier temporaire, le synchronise sur disque puis le renomme :
+/// une écriture interrompue laisse le fichier précédent intact.
+pub async fn write_atomically(filepath: &str, contents: &[u8]) -> anyhow::Result<()> {
+    let temp_path = temp_path(filepath);
+
+    let mut file = File::create(&temp_path).await?;
+    file.write_all(contents).await?;
+    file.sync_all().await?;
+    drop(file);
+
+    fs::rename(&temp_path, filepath).await?;
+    sync_parent(filepath).await
+}
+
+/// Écrit une clé privée dans un nouveau fichier, lisible par son seul propriétaire.
+pub async fn write_private_key(filepath: &str, contents: &str) -> anyhow::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let mut file = options
+        .open(filepath)
+        .await
+        .with_context(|| format!("impossible d'écrire la clé {}", filepath))?;
+    file.write_all(contents.as_bytes()).await?;
+    file.sync_all().await?;
+    Ok(())
+}
+
+/// Synchronise le répertoire, pour que le renommage survive lui aussi à une coupure.
+#[cfg(unix)]
+async fn sync_parent(filepath: &str) -> anyhow::Result<()> {
+    let parent = match Path::new(filepath).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent).await?.sync_all().await?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn sync_parent(_filepath: &str) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// Au démarrage : le fichier temporaire d'une écriture interrompue est supprimé,
+/// la machine précédente étant restée intacte.
+pub async fn discard_interrupted_write(filepath: &str) -> anyhow::Result<()> {
+    let temp_path = temp_path(filepath);
+    if fs::try_exists(&temp_path).await? {
+        eprintln!("Écriture interrompue de {} : {} supprimé", filepath, temp_path);
+        fs::remove_file(&temp_path).await?;
+    }
+
+    Ok(())
+}
 #[derive(Serialize, Deserialize)]
 struct ScoreboardDao{
     scores : Map<String, usize>,
     blank_score : usize,
     invalid_score: usize,
+    #[serde(default)]
+    head_counts: Option<HeadCountsDao>,
+}
+#[derive(Serialize, Deserialize)]
+struct HeadCountsDao{
+    scores : Map<String, usize>,
+    blank_score : usize,
+    invalid_score: usize,
+}
+#[derive(Serialize, Deserialize)]
+pub enum BallotDao{
+    Blank,
+    Invalid,
+    Ranked(Vec<String>),
+    Approval(Vec<String>),
+    Graded(Map<String, usize>),
+    Encrypted(Box<EncryptedScoreboardDao>),
+}
+/// Point Ristretto compressé, en hexadécimal : un point invalide rend la machine illisible.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(try_from = "String", into = "String")]
+pub struct PointDao(RistrettoPoint);
+#[derive(Serialize, Deserialize)]
+pub struct CiphertextDao(PointDao, PointDao);
+#[derive(Serialize, Deserialize)]
+pub struct EncryptedScoreboardDao{
+    scores : Map<String, CiphertextDao>,
+    blank_score : CiphertextDao,
+    invalid_score: CiphertextDao,
+}
+#[derive(Serialize, Deserialize)]
+struct CodeSheetDao{
+    issued: Set<String>,
+    consumed: Set<String>,
+}
+#[derive(Serialize, Deserialize)]
+struct HomomorphicTallyDao{
+    public_key: PointDao,
+    scoreboard: EncryptedScoreboardDao,
+    #[serde(default)]
+    revealed: bool,
+}
+#[derive(Serialize, Deserialize)]
+enum RoundOutcomeDao{
+    Elected(String),
+    SecondRound(String, String),
+    NoWinner,
+}
+#[derive(Serialize, Deserialize)]
+struct RoundDao{
+    voters: Set<String>,
+    #[serde(default)]
+    proxy_votes: Map<String, String>,
+    scoreboard: ScoreboardDao,
+    ballots: Vec<BallotDao>,
+    #[serde(default)]
+    ballot_weights: Vec<usize>,
+    outcome: RoundOutcomeDao,
+}
+#[derive(Serialize, Deserialize)]
+struct CastBallotDao{
+    ballot: BallotDao,
+    receipt: Option<String>,
+}
+#[derive(Serialize, Deserialize)]
+struct LogEntryDao{
+    round: usize,
+    ballot: BallotDao,
+    #[serde(default = "default_weight")]
+    weight: usize,
+    #[serde(default)]
+    revoked: bool,
+    /// Empreinte du code de suivi du bulletin.
+    #[serde(default)]
+    receipt: Option<String>,
+    previous: String,
+    hash: String,
+}
+#[derive(Serialize, Deserialize)]
+pub struct StagedBallotDao{
+    round: usize,
+    ballot: BallotDao,
+    weight: usize,
+    receipt: Option<String>,
+}
+#[derive(Serialize, Deserialize)]
+pub enum AttendanceDao{
+    InPerson,
+    ByProxy(String),
+}
+/// Dépôt d'un bulletin, tel qu'un journal l'enregistre.
+#[derive(Serialize, Deserialize)]
+pub struct DepositDao{
+    /// Empreinte du code de vote quand il n'y a pas d'émargement.
+    voter: String,
+    attendance: Option<AttendanceDao>,
+    ballot: BallotDao,
+    #[serde(default = "default_weight")]
+    weight: usize,
+    #[serde(default)]
+    receipt: Option<String>,
+}
+#[derive(Serialize, Deserialize, Default)]
+enum PhaseDao{
+    Draft,
+    /// Les fichiers antérieurs aux phases acceptaient les votes.
+    #[default]
+    Open,
+    Closed,
+    Certified,
 }
 #[derive(Serialize, Deserialize)]
 pub struct VotingMachineDao{
    voters: Set<String>,
+   #[serde(default)]
+   proxy_votes: Map<String, String>,
    scoreboard: ScoreboardDao,
+   #[serde(default)]
+   ballots: Vec<BallotDao>,
+   /// Poids des bulletins de l'urne, dans le même ordre ; vide si tous pèsent 1.
+   #[serde(default)]
+   ballot_weights: Vec<usize>,
+   #[serde(default = "default_seats")]
+   seats: usize,
+   #[serde(default)]
+   rounds: Vec<RoundDao>,
+   #[serde(default)]
+   phase: PhaseDao,
+   #[serde(default)]
+   roll: Option<Set<String>>,
+   /// Poids des électeurs qui ne pèsent pas 1.
+   #[serde(default)]
+   weights: Map<String, usize>,
+   #[serde(default)]
+   secret: bool,
+   #[serde(default)]
+   log: Vec<LogEntryDao>,
+   /// Bulletins secrets pas encore chaînés, dans l'ordre de leur lot.
+   #[serde(default)]
+   staged: Vec<StagedBallotDao>,
+   #[serde(default)]
+   encryption: Option<HomomorphicTallyDao>,
+   #[serde(default)]
+   codes: Option<CodeSheetDao>,
+   #[serde(default)]
+   revote: bool,
+   #[serde(default)]
+   cast_ballots: Map<String, CastBallotDao>,
+   #[serde(default = "default_proxy_limit")]
+   proxy_limit: usize,
+   #[serde(default)]
+   mandates: Map<String, String>,
+}
+fn default_seats() -> usize {
+    1
+}
+fn default_weight() -> usize {
+    Weight::default().0
+}
+fn default_proxy_limit() -> usize {
+    DEFAULT_PROXY_LIMIT
+}
+
+/// Les votants restent une simple liste ; les votes par procuration sont
+/// rangés à part, du mandant vers le mandataire.
+fn split_attendance(voters: AttendenceSheet) -> (Set<String>, Map<String, String>) {
+    let mut names = Set::new();
+    let mut proxy_votes = Map::new();
+
+    for (voter, attendance) in voters.0 {
+        if let Attendance::ByProxy(proxy) = attendance {
+            proxy_votes.insert(voter.0.clone(), proxy.0);
+        }
+        names.insert(voter.0);
+    }
+
+    (names, proxy_votes)
+}
+
+fn merge_attendance(voters: Set<String>, mut proxy_votes: Map<String, String>) -> AttendenceSheet {
+    AttendenceSheet(
+        voters
+            .into_iter()
+            .map(|voter| {
+                let attendance = match proxy_votes.remove(&voter) {
+                    Some(proxy) => Attendance::ByProxy(Voter(proxy)),
+                    None => Attendance::InPerson,
+                };
+                (Voter(voter), attendance)
+            })
+            .collect(),
+    )
+}
+
+/// Les bulletins de l'urne et leurs poids sont rangés à part ; les poids ne sont
+/// écrits que si l'un d'eux n'est pas 1.
+fn split_urn(ballots: Vec<WeightedBallot>) -> (Vec<BallotDao>, Vec<usize>) {
+    let weighted = ballots.iter().any(|stored| stored.weight != Weight::default());
+    let weights = match weighted {
+        true => ballots.iter().map(|stored| stored.weight.0).collect(),
+        false => vec![],
+    };
+
+    (ballots.into_iter().map(|stored| BallotDao::from(stored.ballot)).collect(), weights)
+}
+
+fn merge_urn(ballots: Vec<BallotDao>, weights: Vec<usize>) -> Vec<WeightedBallot> {
+    ballots
+        .into_iter()
+        .enumerate()
+        .map(|(position, ballot)| WeightedBallot {
+            ballot: Ballot::from(ballot),
+            weight: weights.get(position).copied().map(Weight).unwrap_or_default(),
+        })
+        .collect()
+}
+impl From<Ballot> for BallotDao {
+    fn from(ballot: Ballot) -> Self
+    {
+        match ballot {
+            Ballot::Blank => BallotDao::Blank,
+            Ballot::Invalid => BallotDao::Invalid,
+            Ballot::Ranked(ranking) => BallotDao::Ranked(ranking.into_iter().map(|candidate| candidate.0).collect()),
+            Ballot::Approval(approved) => BallotDao::Approval(approved.into_iter().map(|candidate| candidate.0).collect()),
+            Ballot::Graded(grades) => BallotDao::Graded(grades.into_iter().map(|(candidate, grade)| (candidate.0, grade.0)).collect()),
+            Ballot::Encrypted(scoreboard) => BallotDao::Encrypted(Box::new(EncryptedScoreboardDao::from(*scoreboard))),
+        }
+    }
+}
+
+impl From<BallotDao> for Ballot {
+    fn from(ballot: BallotDao) -> Self
+    {
+        match ballot {
+            BallotDao::Blank => Ballot::Blank,
+            BallotDao::Invalid => Ballot::Invalid,
+            BallotDao::Ranked(ranking) => Ballot::Ranked(ranking.into_iter().map(Candidate).collect()),
+            BallotDao::Approval(approved) => Ballot::Approval(approved.into_iter().map(Candidate).collect()),
+            BallotDao::Graded(grades) => Ballot::Graded(grades.into_iter().map(|(candidate, grade)| (Candidate(candidate), Grade(grade))).collect()),
+            BallotDao::Encrypted(scoreboard) => Ballot::Encrypted(Box::new(EncryptedScoreboard::from(*scoreboard))),
+        }
+    }
+}
+
+impl TryFrom<String> for PointDao {
+    type Error = anyhow::Error;
+
+    fn try_from(point: String) -> anyhow::Result<Self>
+    {
+        from_hex(&point).map(Self)
+    }
+}
+
+impl From<PointDao> for String {
+    fn from(point: PointDao) -> Self
+    {
+        to_hex(&point.0)
+    }
+}
+
+impl From<Ciphertext> for CiphertextDao {
+    fn from(ciphertext: Ciphertext) -> Self
+    {
+        Self(PointDao(ciphertext.c1), PointDao(ciphertext.c2))
+    }
+}
+
+impl From<CiphertextDao> for Ciphertext {
+    fn from(ciphertext: CiphertextDao) -> Self
+    {
+        Self{ c1: ciphertext.0.0, c2: ciphertext.1.0 }
+    }
+}
+
+impl From<EncryptedScoreboard> for EncryptedScoreboardDao {
+    fn from(scoreboard: EncryptedScoreboard) -> Self
+    {
+        Self{
+            scores: scoreboard.scores.into_iter().map(|(candidate, ciphertext)| (candidate.0, CiphertextDao::from(ciphertext))).collect(),
+            blank_score: CiphertextDao::from(scoreboard.blank_score),
+            invalid_score: CiphertextDao::from(scoreboard.invalid_score),
+        }
+    }
+}
+
+impl From<EncryptedScoreboardDao> for EncryptedScoreboard {
+    fn from(scoreboard: EncryptedScoreboardDao) -> Self
+    {
+        Self{
+            scores: scoreboard.scores.into_iter().map(|(candidate, ciphertext)| (Candidate(candidate), Ciphertext::from(ciphertext))).collect(),
+            blank_score: Ciphertext::from(scoreboard.blank_score),
+            invalid_score: Ciphertext::from(scoreboard.invalid_score),
+        }
+    }
+}
+
+impl From<HomomorphicTally> for HomomorphicTallyDao {
+    fn from(tally: HomomorphicTally) -> Self
+    {
+        Self{
+            public_key: PointDao(tally.public_key.0),
+            scoreboard: EncryptedScoreboardDao::from(tally.scoreboard),
+            revealed: tally.revealed,
+        }
+    }
+}
+
+impl From<HomomorphicTallyDao> for HomomorphicTally {
+    fn from(tally: HomomorphicTallyDao) -> Self
+    {
+        Self{
+            public_key: PublicKey(tally.public_key.0),
+            scoreboard: EncryptedScoreboard::from(tally.scoreboard),
+            revealed: tally.revealed,
+        }
+    }
+}
+
+impl From<Phase> for PhaseDao {
+    fn from(phase: Phase) -> Self
+    {
+        match phase {
+            Phase::Draft => PhaseDao::Draft,
+            Phase::Open => PhaseDao::Open,
+            Phase::Closed => PhaseDao::Closed,
+            Phase::Certified => PhaseDao::Certified,
+        }
+    }
+}
+
+impl From<PhaseDao> for Phase {
+    fn from(phase: PhaseDao) -> Self
+    {
+        match phase {
+            PhaseDao::Draft => Phase::Draft,
+            PhaseDao::Open => Phase::Open,
+            PhaseDao::Closed => Phase::Closed,
+            PhaseDao::Certified => Phase::Certified,
+        }
+    }
+}
+
+impl From<RoundResult> for RoundDao {
+    fn from(round: RoundResult) -> Self
+    {
+        let (voters, proxy_votes) = split_attendance(round.voters);
+        let (ballots, ballot_weights) = split_urn(round.ballots);
+
+        Self{
+            voters,
+            proxy_votes,
+            scoreboard: ScoreboardDao::from(round.scoreboard),
+            ballots,
+            ballot_weights,
+            outcome: match round.outcome {
+                RoundOutcome::Elected(candidate) => RoundOutcomeDao::Elected(candidate.0),
+                RoundOutcome::SecondRound(first, second) => RoundOutcomeDao::SecondRound(first.0, second.0),
+                RoundOutcome::NoWinner => RoundOutcomeDao::NoWinner,
+            },
+        }
+    }
+}
+
+impl From<LogEntry> for LogEntryDao {
+    fn from(entry: LogEntry) -> Self
+    {
+        Self{
+            round: entry.round,
+            ballot: BallotDao::from(entry.ballot),
+            weight: entry.weight.0,
+            revoked: entry.revoked,
+            receipt: entry.receipt,
+            previous: entry.previous,
+            hash: entry.hash,
+        }
+    }
+}
+
+impl From<LogEntryDao> for LogEntry {
+    fn from(entry: LogEntryDao) -> Self
+    {
+        Self{
+            round: entry.round,
+            ballot: Ballot::from(entry.ballot),
+            weight: Weight(entry.weight),
+            revoked: entry.revoked,
+            receipt: entry.receipt,
+            previous: entry.previous,
+            hash: entry.hash,
+        }
+    }
+}
+
+impl From<StagedBallot> for StagedBallotDao {
+    fn from(staged: StagedBallot) -> Self
+    {
+        Self{
+            round: staged.round,
+            ballot: BallotDao::from(staged.ballot),
+            weight: staged.weight.0,
+            receipt: staged.receipt,
+        }
+    }
 }
+
+impl From<StagedBallotDao> for StagedBallot {
+    fn from(staged: StagedBallotDao) -> Self
+    {
+        Self{
+            round: staged.round,
+            ballot: Ballot::from(staged.ballot),
+            weight: Weight(staged.weight),
+            receipt: staged.receipt,
+        }
+    }
+}
+
+impl From<Attendance> for AttendanceDao {
+    fn from(attendance: Attendance) -> Self
+    {
+        match attendance {
+            Attendance::InPerson => AttendanceDao::InPerson,
+            Attendance::ByProxy(proxy) => AttendanceDao::ByProxy(proxy.0),
+        }
+    }
+}
+
+impl From<AttendanceDao> for Attendance {
+    fn from(attendance: AttendanceDao) -> Self
+    {
+        match attendance {
+            AttendanceDao::InPerson => Attendance::InPerson,
+            AttendanceDao::ByProxy(proxy) => Attendance::ByProxy(Voter(proxy)),
+        }
+    }
+}
+
+impl From<Deposit> for DepositDao {
+    fn from(deposit: Deposit) -> Self
+    {
+        Self{
+            voter: deposit.voter.0,
+            attendance: deposit.attendance.map(AttendanceDao::from),
+            ballot: BallotDao::from(deposit.ballot.ballot),
+            weight: deposit.ballot.weight.0,
+            receipt: deposit.receipt,
+        }
+    }
+}
+
+impl From<DepositDao> for Deposit {
+    fn from(deposit: DepositDao) -> Self
+    {
+        Self{
+            voter: Voter(deposit.voter),
+            attendance: deposit.attendance.map(Attendance::from),
+            ballot: WeightedBallot { ballot: Ballot::from(deposit.ballot), weight: Weight(deposit.weight) },
+            receipt: deposit.receipt,
+        }
+    }
+}
+
+impl From<CastBallot> for CastBallotDao {
+    fn from(cast: CastBallot) -> Self
+    {
+        Self{
+            ballot: BallotDao::from(cast.ballot),
+            receipt: cast.receipt,
+        }
+    }
+}
+
+impl From<CastBallotDao> for CastBallot {
+    fn from(cast: CastBallotDao) -> Self
+    {
+        Self{
+            ballot: Ballot::from(cast.ballot),
+            receipt: cast.receipt,
+        }
+    }
+}
+
+impl From<RoundDao> for RoundResult {
+    fn from(round: RoundDao) -> Self
+    {
+        Self{
+            voters: merge_attendance(round.voters, round.proxy_votes),
+            scoreboard: Scoreboard::from(round.scoreboard),
+            ballots: merge_urn(round.ballots, round.ballot_weights),
+            outcome: match round.outcome {
+                RoundOutcomeDao::Elected(candidate) => RoundOutcome::Elected(Candidate(candidate)),
+                RoundOutcomeDao::SecondRound(first, second) => RoundOutcome::SecondRound(Candidate(first), Candidate(second)),
+                RoundOutcomeDao::NoWinner => RoundOutcome::NoWinner,
+            },
+        }
+    }
+}
+
 impl From<Scoreboard> for ScoreboardDao {
     fn from(scoreboard :  Scoreboard) -> Self
     {
@@ -59,8 +646,12 @@ impl From<Scoreboard> for ScoreboardDao {
         Self{
             blank_score : scoreboard.blank_score.0,
             invalid_score: scoreboard.invalid_score.0,
-            scores : scores,
-
+            scores,
+            head_counts: scoreboard.head_counts.map(|head_counts| HeadCountsDao {
+                scores: head_counts.scores.into_iter().map(|(candidate, score)| (candidate.0, score.0)).collect(),
+                blank_score: head_counts.blank_score.0,
+                invalid_score: head_counts.invalid_score.0,
+            }),
         }
     }
 }
@@ -76,8 +667,12 @@ impl From<ScoreboardDao> for Scoreboard {
         Self{
             blank_score : Score(scoreboard.blank_score),
             invalid_score: Score(scoreboard.invalid_score),
-            scores : scores,
-
+            scores,
+            head_counts: scoreboard.head_counts.map(|head_counts| HeadCounts {
+                scores: head_counts.scores.into_iter().map(|(candidate, score)| (Candidate(candidate), Score(score))).collect(),
+                blank_score: Score(head_counts.blank_score),
+                invalid_score: Score(head_counts.invalid_score),
+            }),
         }
     }
 }
@@ -85,15 +680,39 @@ impl From<ScoreboardDao> for Scoreboard {
 impl From<VotingMachine> for VotingMachineDao {
     fn from(voting_machine :  VotingMachine) -> Self
     {
-        let mut  voters = Set::new();
+        Self::from(&voting_machine)
+    }
+}
 
-        for voter in voting_machine.get_voters().clone().0 {
-            voters.insert(voter.0);
-        }
+impl From<&VotingMachine> for VotingMachineDao {
+    fn from(voting_machine :  &VotingMachine) -> Self
+    {
+        let (voters, proxy_votes) = split_attendance(voting_machine.get_voters().clone());
+        let (ballots, ballot_weights) = split_urn(voting_machine.get_ballots().clone());
 
         Self{
-            voters : voters,
-            scoreboard: ScoreboardDao::from(voting_machine.get_scoreboard().clone())
+            voters,
+            proxy_votes,
+            scoreboard: ScoreboardDao::from(voting_machine.get_scoreboard().clone()),
+            ballots,
+            ballot_weights,
+            seats: voting_machine.get_seats(),
+            rounds: voting_machine.get_rounds().iter().cloned().map(RoundDao::from).collect(),
+            phase: PhaseDao::from(voting_machine.get_phase()),
+            roll: voting_machine.get_roll().map(|roll| roll.0.keys().map(|voter| voter.0.clone()).collect()),
+            weights: voting_machine
+                .get_roll()
+                .map(|roll| roll.0.iter().filter(|(_, weight)| **weight != Weight::default()).map(|(voter, weight)| (voter.0.clone(), weight.0)).collect())
+                .unwrap_or_default(),
+            secret: voting_machine.is_secret(),
+            log: voting_machine.get_log().entries().iter().cloned().map(LogEntryDao::from).collect(),
+            staged: voting_machine.get_log().staged().iter().cloned().map(StagedBallotDao::from).collect(),
+            encryption: voting_machine.get_encryption().cloned().map(HomomorphicTallyDao::from),
+            codes: voting_machine.get_codes().map(|codes| CodeSheetDao { issued: codes.issued.clone(), consumed: codes.consumed.clone() }),
+            revote: voting_machine.allows_revote(),
+            cast_ballots: voting_machine.get_cast_ballots().iter().map(|(voter, cast)| (voter.0.clone(), CastBallotDao::from(cast.clone()))).collect(),
+            proxy_limit: voting_machine.get_proxies().limit,
+            mandates: voting_machine.get_proxies().mandates.iter().map(|(principal, proxy)| (principal.0.clone(), proxy.0.clone())).collect(),
         }
     }
 }
@@ -101,50 +720,86 @@ impl From<VotingMachine> for VotingMachineDao {
 impl From<VotingMachineDao> for VotingMachine{
     fn from(voting_machine :  VotingMachineDao) -> Self
     {
-        let mut voters = Set::new();
-
-        for voter in voting_machine.voters {
-            voters.insert(Voter(voter));
-        }
-
         VotingMachine::recover_from(
-     AttendenceSheet(voters),
+     merge_attendance(voting_machine.voters, voting_machine.proxy_votes),
             Scoreboard::from(voting_machine.scoreboard)
         )
+        .with_ballots(merge_urn(voting_machine.ballots, voting_machine.ballot_weights))
+        .with_seats(voting_machine.seats)
+        .with_rounds(voting_machine.rounds.into_iter().map(RoundResult::from).collect())
+        .with_phase(Phase::from(voting_machine.phase))
+        .with_roll(voting_machine.roll.map(|roll| {
+            ElectoralRoll(
+                roll.into_iter()
+                    .map(|voter| {
+                        let weight = voting_machine.weights.get(&voter).copied().map(Weight).unwrap_or_default();
+                        (Voter(voter), weight)
+                    })
+                    .collect(),
+            )
+        }))
+        .with_secret(voting_machine.secret)
+        .with_log(
+            BallotLog::from_entries(voting_machine.log.into_iter().map(LogEntry::from).collect())
+                .with_staged(voting_machine.staged.into_iter().map(StagedBallot::from).collect()),
+        )
+        .with_encryption(voting_machine.encryption.map(HomomorphicTally::from))
+        .with_codes(voting_machine.codes.map(|codes| CodeSheet { issued: codes.issued, consumed: codes.consumed }))
+        .with_revote(voting_machine.revote)
+        .with_cast_ballots(voting_machine.cast_ballots.into_iter().map(|(voter, cast)| (Voter(voter), CastBallot::from(cast))).collect())
+        .with_proxies(ProxyRegistry {
+            limit: voting_machine.proxy_limit,
+            mandates: voting_machine.mandates.into_iter().map(|(principal, proxy)| (Voter(principal), Voter(proxy))).collect(),
+        })
     }
 }
 
 
 #[async_trait]
 impl Storage for FileStore {
+    async fn exists() -> anyhow::Result<bool> {
+        Ok(fs::try_exists(FILEPATH).await?)
+    }
+
     async fn new(machine: VotingMachine) -> anyhow::Result<Self>
+    {
+        Self::open(machine, FILEPATH).await
+    }
+
+    async fn reset(machine: VotingMachine) -> anyhow::Result<Self>
     {
         Self::create(machine, FILEPATH).await
     }
 
-    async fn get_voting_machine(&self) -> anyhow::Result<VotingMachine> {
-        let mut my_file = File::open(self.filepath.as_str()).await?;
+    async fn read<T: Send>(&self, query: impl for<'a> FnOnce(&'a VotingMachine) -> T + Send) -> anyhow::Result<T> {
+        Ok(query(self.journal.read().await.machine()))
+    }
+
+    async fn update<T: Send>(&self, action: impl for<'a> FnOnce(&'a mut VotingMachine) -> anyhow::Result<T> + Send) -> anyhow::Result<T> {
+        self.journal.write().await.update(action).await
+    }
 
-        let mut my_slice = vec![];
-        my_file.read_to_end(&mut my_slice).await?;
+    async fn record_ballot(&self, deposit: Deposit, schedule: &Schedule, now: DateTime<Utc>) -> anyhow::Result<bool> {
+        self.journal.write().await.record_ballot(deposit, schedule, now).await
+    }
 
-        let my_object: VotingMachineDao = serde_json::from_slice(&my_slice)?;
+    async fn revoke(&self, voter: &Voter, credential: &str) -> anyhow::Result<VoteOutcome> {
+        self.journal.write().await.revoke(voter, credential).await
+    }
 
-        Ok(VotingMachine::from(my_object))
+    async fn scores(&self) -> anyhow::Result<Scoreboard> {
+        Ok(self.journal.read().await.machine().get_scoreboard().clone())
     }
-    
-    async fn put_voting_machine(&mut self, machine: VotingMachine) -> anyhow::Result<()> {
-		let mut file = File::create(self.filepath.clone()).await?;
-		let voting_machine_json = serde_json::to_string(&VotingMachineDao::from(machine))?;
-		file.write_all(voting_machine_json.as_bytes()).await?;
-		Ok(())
-	}
 }
 
-
 #[cfg(test)]
 mod tests {
+    use crate::domain::BallotPaper;
     use crate::domain::Candidate;
+    use crate::domain::Casting;
+    use crate::domain::RankedBallotPaper;
+    use crate::domain::TrackingCode;
+    use crate::encryption::ElectionKey;
 
     use super::*;
 
@@ -153,15 +808,249 @@ mod tests {
 
         let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]);
 
-        let mut store: FileStore  = FileStore::new(voting_machine.clone()).await.expect("Erreur lors de la creation de la memoire");
+        let filepath = temp_filepath("c1_test_get_return_what_we_inserted.json").await;
+        let store: FileStore  = FileStore::create(VotingMachine::new(vec![]), &filepath).await.expect("Erreur lors de la creation de la memoire");
 
-        store.put_voting_machine(voting_machine.clone()).await.expect("Erreur lors de l'insertion de la machine");
+        store.update(|machine| { *machine = voting_machine.clone(); Ok(()) }).await.expect("Erreur lors de l'insertion de la machine");
 
         let expected_machine = store.get_voting_machine().await.expect("err lors de la recuperation de la machine");
 
         assert_eq!(expected_machine, voting_machine);
     }
 
+    async fn temp_filepath(name: &str) -> String {
+        let filepath = std::env::temp_dir().join(name).to_str().unwrap().to_string();
+        let _ = fs::remove_file(&filepath).await;
+        let _ = fs::remove_file(journal_path(&filepath)).await;
+        let _ = fs::remove_file(format!("{}.staged", journal_path(&filepath))).await;
+        filepath
+    }
+
+    #[tokio::test]
+    async fn test_vote_appends_without_rewriting_machine() {
+        let filepath = temp_filepath("c1_test_file_append.json").await;
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Open);
+        let store = FileStore::create(voting_machine.clone(), &filepath).await.expect("Erreur lors de la creation de la memoire");
+        let saved = fs::read(&filepath).await.expect("Erreur lors de la lecture");
 
+        for voter in ["Tux", "Gnu"] {
+            let ballot_paper = BallotPaper{ voter: Voter(voter.to_string()), candidate: Some(Candidate(String::from("Louis"))) };
+            let (deposit, _) = store.read(|voting_machine| voting_machine.prepare_vote(ballot_paper, &Casting::default())).await.expect("err lors de la lecture").expect("vote refuse");
+            assert!(store.record_ballot(deposit, &Schedule::default(), Utc::now()).await.expect("Erreur lors de l'insertion du bulletin"));
+        }
 
+        assert_eq!(fs::read(&filepath).await.expect("Erreur lors de la lecture"), saved);
+        assert_eq!(fs::read_to_string(journal_path(&filepath)).await.expect("Erreur lors de la lecture").lines().count(), 2);
+
+        let live = store.get_voting_machine().await.expect("err lors de la recuperation de la machine");
+        let store = FileStore::open(voting_machine, &filepath).await.expect("Erreur lors de la reprise");
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), live);
+    }
+
+    #[tokio::test]
+    async fn test_open_reads_machine_without_sequence() {
+        let filepath = temp_filepath("c1_test_file_without_sequence.json").await;
+        let mut voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Open);
+        voting_machine.vote(BallotPaper{ voter: Voter(String::from("Tux")), candidate: None });
+        fs::write(&filepath, serde_json::to_vec(&VotingMachineDao::from(&voting_machine)).expect("Erreur lors de la serialisation")).await.expect("Erreur lors de l'ecriture");
+
+        let store = FileStore::open(voting_machine.clone(), &filepath).await.expect("Erreur lors de la reprise");
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), voting_machine);
+    }
+
+    #[tokio::test]
+    async fn test_storage_suite() {
+        crate::storage::tests::run(|case, machine| async move {
+            let filepath = temp_filepath(&format!("c1_test_file_suite_{}.json", case)).await;
+            FileStore::create(machine, &filepath).await
+        }).await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_private_key_is_readable_by_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let filepath = temp_filepath("c1_test_private.key").await;
+        write_private_key(&filepath, "secret").await.expect("Erreur lors de l'ecriture");
+
+        let mode = fs::metadata(&filepath).await.expect("Erreur lors de la lecture").permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(write_private_key(&filepath, "autre").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_write_keeps_previous_machine() {
+        let filepath = temp_filepath("c1_test_interrupted_write.json").await;
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]);
+        let store = FileStore::create(voting_machine.clone(), &filepath).await.expect("Erreur lors de la creation de la memoire");
+
+        // Coupure avant le renommage : seul le fichier temporaire est à moitié écrit.
+        fs::write(temp_path(&filepath), b"{\"voters\":[\"Tu").await.expect("Erreur lors de l'ecriture");
+
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), voting_machine);
+
+        FileStore::open(voting_machine, &filepath).await.expect("Erreur lors de la reprise");
+        assert!(!fs::try_exists(temp_path(&filepath)).await.expect("Erreur lors de la verification"));
+    }
+
+    #[tokio::test]
+    async fn test_truncated_file_is_reported_as_corrupted() {
+        let filepath = temp_filepath("c1_test_truncated_file.json").await;
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]);
+        FileStore::create(voting_machine.clone(), &filepath).await.expect("Erreur lors de la creation de la memoire");
+
+        let json = fs::read(&filepath).await.expect("Erreur lors de la lecture");
+        fs::write(&filepath, &json[..json.len() / 2]).await.expect("Erreur lors de l'ecriture");
+
+        let error = FileStore::open(voting_machine, &filepath).await.err().expect("machine tronquee ecrasee");
+        assert!(format!("{:#}", error).contains("corrompue"));
+        assert_eq!(fs::read(&filepath).await.expect("Erreur lors de la lecture").len(), json.len() / 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_keeps_saved_machine() {
+        let filepath = temp_filepath("c1_test_failed_write.json").await;
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Open);
+        let store = FileStore::create(voting_machine.clone(), &filepath).await.expect("Erreur lors de la creation de la memoire");
+
+        // Le fichier temporaire ne peut pas être créé à la place d'un répertoire.
+        fs::create_dir_all(temp_path(&filepath)).await.expect("Erreur lors de la creation du repertoire");
+        let outcome = store.update(|machine| Ok(machine.vote(BallotPaper{ voter: Voter(String::from("Tux")), candidate: None }))).await;
+        fs::remove_dir(temp_path(&filepath)).await.expect("Erreur lors de la suppression du repertoire");
+
+        assert!(outcome.is_err());
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), voting_machine);
+    }
+
+    #[tokio::test]
+    async fn test_open_resumes_saved_election() {
+        let filepath = temp_filepath("c1_test_open_resumes.json").await;
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Open);
+        let store = FileStore::open(voting_machine.clone(), &filepath).await.expect("Erreur lors de la creation de la memoire");
+
+        let mut voted = voting_machine.clone();
+        voted.vote(BallotPaper{ voter: Voter(String::from("Tux")), candidate: None });
+        store.update(|machine| Ok(machine.vote(BallotPaper{ voter: Voter(String::from("Tux")), candidate: None }))).await.expect("Erreur lors de l'insertion de la machine");
+
+        let store = FileStore::open(voting_machine.clone(), &filepath).await.expect("Erreur lors de la reprise");
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), voted);
+
+        let store = FileStore::create(voting_machine.clone(), &filepath).await.expect("Erreur lors de la remise a zero");
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), voting_machine);
+    }
+
+    #[tokio::test]
+    async fn test_open_refuses_other_candidates() {
+        let filepath = temp_filepath("c1_test_open_other_candidates.json").await;
+        FileStore::open(VotingMachine::new(vec![Candidate(String::from("Louis"))]), &filepath).await.expect("Erreur lors de la creation de la memoire");
+
+        let error = FileStore::open(VotingMachine::new(vec![Candidate(String::from("Louise"))]), &filepath).await.err().expect("candidats differents acceptes");
+
+        assert!(error.to_string().contains("--reset"));
+    }
+
+    #[tokio::test]
+    async fn test_open_refuses_other_settings() {
+        let filepath = temp_filepath("c1_test_open_other_settings.json").await;
+        let voting_machine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_seats(2);
+        FileStore::open(voting_machine.clone(), &filepath).await.expect("Erreur lors de la creation de la memoire");
+
+        let error = FileStore::open(voting_machine.clone().with_secret(true), &filepath).await.err().expect("reglages differents acceptes");
+        assert!(error.to_string().contains("Secret"));
+
+        let error = FileStore::open(voting_machine.clone().with_seats(1), &filepath).await.err().expect("reglages differents acceptes");
+        assert!(error.to_string().contains("Seats"));
+
+        FileStore::open(voting_machine, &filepath).await.expect("Erreur lors de la reprise");
+    }
+
+    /// Sérialise puis relit la machine, qui doit revenir à l'identique.
+    fn round_trip(voting_machine: &VotingMachine) -> (String, VotingMachineDao) {
+        let json = serde_json::to_string(&VotingMachineDao::from(voting_machine.clone())).expect("Erreur lors de la serialisation");
+        let parse = || serde_json::from_str::<VotingMachineDao>(&json).expect("Erreur lors de la deserialisation");
+
+        let recovered = VotingMachine::from(parse());
+        assert_eq!(&recovered, voting_machine);
+        assert!(recovered.audit_log().is_intact());
+        let dao = parse();
+        (json, dao)
+    }
+
+    #[test]
+    fn test_dao_keeps_fully_populated_machine() {
+        let voters = ["Tux", "Gnu", "Beastie", "Wilber"].map(|name| Voter(String::from(name)));
+        let roll = ElectoralRoll(voters.iter().zip([2, 1, 1, 3]).map(|(voter, weight)| (voter.clone(), Weight(weight))).collect());
+        let mut voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis")), Candidate(String::from("Louise")), Candidate(String::from("Tux"))])
+            .with_phase(Phase::Open)
+            .with_roll(Some(roll))
+            .with_revote(true);
+
+        voting_machine.register_proxy(Voter(String::from("Tux")), Voter(String::from("Gnu"))).expect("procuration refusee");
+        voting_machine.track(TrackingCode(String::from("ABCD-EFGH-JKLM")), |voting_machine| {
+            voting_machine.vote_ranked(RankedBallotPaper{
+                voter: Voter(String::from("Tux")),
+                candidates: vec![Candidate(String::from("Louise")), Candidate(String::from("Louis"))],
+            })
+        });
+        voting_machine.vote(BallotPaper{ voter: Voter(String::from("Tux")), candidate: Some(Candidate(String::from("Louise"))) });
+        voting_machine.vote_for(&Voter(String::from("Tux")), |voting_machine| {
+            voting_machine.vote(BallotPaper{ voter: Voter(String::from("Gnu")), candidate: Some(Candidate(String::from("Louis"))) })
+        });
+        voting_machine.vote(BallotPaper{ voter: Voter(String::from("Beastie")), candidate: Some(Candidate(String::from("Tux"))) });
+        voting_machine.close_round();
+        voting_machine.track(TrackingCode(String::from("NPQR-STUV-WXYZ")), |voting_machine| {
+            voting_machine.vote(BallotPaper{ voter: Voter(String::from("Wilber")), candidate: Some(Candidate(String::from("Louis"))) })
+        });
+
+        let (_, dao) = round_trip(&voting_machine);
+        assert_eq!(dao.rounds.len(), 1);
+        assert_eq!(dao.rounds[0].ballot_weights, vec![2, 1, 1]);
+        assert_eq!(dao.ballot_weights, vec![3]);
+        assert!(!dao.cast_ballots.is_empty());
+    }
+
+    #[test]
+    fn test_dao_keeps_ciphertexts_only() {
+        let key = ElectionKey::generate();
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let mut voting_machine :  VotingMachine = VotingMachine::new(candidates.clone())
+            .with_phase(Phase::Open)
+            .with_encryption(Some(HomomorphicTally::new(key.public_key(), &candidates)));
+        voting_machine.vote(BallotPaper{ voter: Voter(String::from("Tux")), candidate: Some(Candidate(String::from("Louise"))) });
+
+        let (json, _) = round_trip(&voting_machine);
+        assert!(!json.contains("Ranked"));
+    }
+
+    #[test]
+    fn test_dao_keeps_consumed_codes_hashed() {
+        let codes = CodeSheet::issue(&[String::from("ABCD-EFGH-JKLM"), String::from("NPQR-STUV-WXYZ")]);
+        let mut voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Open).with_codes(Some(codes));
+        voting_machine.vote(BallotPaper{ voter: Voter(String::from("ABCD-EFGH-JKLM")), candidate: None });
+
+        let (json, _) = round_trip(&voting_machine);
+        assert!(!json.contains("ABCD-EFGH-JKLM"));
+    }
+
+    #[test]
+    fn test_dao_keeps_secret_ballots_unordered() {
+        let mut voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))]).with_phase(Phase::Open).with_secret(true);
+        voting_machine.vote_ranked(RankedBallotPaper{
+            voter: Voter(String::from("Tux")),
+            candidates: vec![Candidate(String::from("Louise"))],
+        });
+        voting_machine.vote_ranked(RankedBallotPaper{
+            voter: Voter(String::from("Gnu")),
+            candidates: vec![Candidate(String::from("Louis"))],
+        });
+
+        let (_, dao) = round_trip(&voting_machine);
+        assert!(matches!(
+            dao.ballots.as_slice(),
+            [BallotDao::Ranked(first), BallotDao::Ranked(second)] if first == &["Louis"] && second == &["Louise"]
+        ));
+        assert!(dao.cast_ballots.is_empty());
+        assert!(!dao.staged.is_empty());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,607 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use tokio::{
+	fs::{self, File, OpenOptions},
+	io::AsyncWriteExt,
+	sync::RwLock,
+};
+
+use crate::ballot_log::StagedBallot;
+use crate::domain::{Attendance, Deposit, Schedule, Scoreboard, VoteOutcome, Voter, VotingMachine};
+use crate::storage::Storage;
+use crate::storages::file::{discard_interrupted_write, write_atomically, AttendanceDao, DepositDao, StagedBallotDao, VotingMachineDao};
+
+const JOURNAL_PATH : &str = "journal.jsonl";
+const SNAPSHOT_PATH : &str = "journal.snapshot.json";
+
+/// Nombre d'enregistrements du journal au-delà duquel un instantané est réécrit.
+pub const SNAPSHOT_EVERY: usize = 1000;
+
+/// Journal en ajout seul : chaque dépôt ou retrait de bulletin ajoute une ligne,
+/// rejouée par la machine au démarrage sur le dernier instantané. Les autres
+/// opérations, rares, réécrivent l'instantané.
+///
+/// En scrutin secret, le journal ne doit pas dire qui a voté quoi : une ligne ne porte
+/// que le votant, et les bulletins en attente de leur lot sont rangés, triés, dans un
+/// fichier à part, réécrit à chaque vote. Le lot n'entre au journal qu'une fois chaîné.
+#[derive(Clone)]
+pub struct JournalStore{
+    journal: Arc<RwLock<Journal>>,
+}
+
+/// Journal et instantané, avec la machine qu'ils contiennent. `FileStore` s'en sert aussi,
+/// avec son fichier de machine pour instantané.
+pub(crate) struct Journal{
+    journal_path: String,
+    snapshot_path: String,
+    layout: Layout,
+    snapshot_every: usize,
+    machine: VotingMachine,
+    sequence: u64,
+    since_snapshot: usize,
+}
+
+/// Forme du fichier d'instantané.
+#[derive(Clone, Copy)]
+pub(crate) enum Layout{
+    /// La machine sous `machine`, à côté du numéro.
+    Journal,
+    /// Les champs de la machine et le numéro au même niveau : le fichier de `FileStore`,
+    /// qui reste lisible sans numéro.
+    Machine,
+}
+
+/// Instantané de la machine, avec le numéro du dernier enregistrement qu'il contient.
+#[derive(Serialize, Deserialize)]
+struct Snapshot{
+    sequence: u64,
+    machine: VotingMachineDao,
+}
+
+/// Instantané de la forme `Layout::Machine`.
+#[derive(Serialize, Deserialize)]
+struct MachineSnapshot{
+    #[serde(default)]
+    sequence: u64,
+    #[serde(flatten)]
+    machine: VotingMachineDao,
+}
+
+/// Une ligne du journal, avec la tête du registre une fois l'opération appliquée :
+/// la reprise vérifie qu'elle la retrouve.
+#[derive(Serialize, Deserialize)]
+struct Record{
+    sequence: u64,
+    operation: Operation,
+    head: String,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Operation{
+    Deposit(DepositDao),
+    /// Votant admis en scrutin secret, et le lot de bulletins que son vote a complété.
+    Enrolled { voter: String, attendance: Option<AttendanceDao>, sealed: Vec<StagedBallotDao> },
+    Withdrawal(String),
+}
+
+/// Bulletins secrets en attente après l'enregistrement `sequence`.
+#[derive(Serialize, Deserialize, Default)]
+struct Staged{
+    sequence: u64,
+    staged: Vec<StagedBallotDao>,
+}
+
+/// Fichier des bulletins secrets en attente, à côté du journal.
+fn staged_path(journal_path: &str) -> String {
+    format!("{}.staged", journal_path)
+}
+
+impl JournalStore{
+
+    /// Repart d'un scrutin vierge : instantané et journal existants sont écrasés.
+    pub async fn create(machine: VotingMachine, journal_path: &str, snapshot_path: &str) -> anyhow::Result<Self> {
+        let journal = Journal::create(machine, journal_path, snapshot_path, Layout::Journal).await?;
+        Ok(Self { journal: Arc::new(RwLock::new(journal)) })
+    }
+
+    /// Reprend le scrutin enregistré s'il existe, à condition qu'il ait les réglages
+    /// de `machine` ; sinon, commence avec `machine`.
+    pub async fn open(machine: VotingMachine, journal_path: &str, snapshot_path: &str) -> anyhow::Result<Self> {
+        let journal = Journal::open(machine, journal_path, snapshot_path, Layout::Journal).await?;
+        Ok(Self { journal: Arc::new(RwLock::new(journal)) })
+    }
+}
+
+impl Journal {
+
+    pub(crate) async fn create(machine: VotingMachine, journal_path: &str, snapshot_path: &str, layout: Layout) -> anyhow::Result<Self> {
+        if fs::try_exists(staged_path(journal_path)).await? {
+            fs::remove_file(staged_path(journal_path)).await?;
+        }
+
+        let mut journal = Journal {
+            journal_path: journal_path.to_string(),
+            snapshot_path: snapshot_path.to_string(),
+            layout,
+            snapshot_every: SNAPSHOT_EVERY,
+            machine,
+            sequence: 0,
+            since_snapshot: 0,
+        };
+        journal.compact().await?;
+
+        Ok(journal)
+    }
+
+    pub(crate) async fn open(machine: VotingMachine, journal_path: &str, snapshot_path: &str, layout: Layout) -> anyhow::Result<Self> {
+        discard_interrupted_write(snapshot_path).await?;
+        discard_interrupted_write(&staged_path(journal_path)).await?;
+
+        if !fs::try_exists(snapshot_path).await? {
+            if fs::try_exists(journal_path).await? {
+                anyhow::bail!("journal {} sans instantané {}, relancer avec --reset pour repartir d'un scrutin vierge", journal_path, snapshot_path);
+            }
+            return Self::create(machine, journal_path, snapshot_path, layout).await;
+        }
+
+        let mut journal = Self::replay(journal_path, snapshot_path, layout).await.context("relancer avec --reset pour repartir d'un scrutin vierge")?;
+        if let Err(mismatch) = journal.machine.compare_settings(&machine) {
+            let saved = match layout {
+                Layout::Journal => format!("le journal {}", journal_path),
+                Layout::Machine => format!("la machine {}", snapshot_path),
+            };
+            anyhow::bail!("{} ne correspond pas à la configuration ({:?} différent), relancer avec --reset pour repartir d'un scrutin vierge", saved, mismatch);
+        }
+
+        // Un instantané neuf écarte aussi une éventuelle dernière ligne tronquée.
+        journal.compact().await?;
+
+        Ok(journal)
+    }
+
+    pub(crate) fn machine(&self) -> &VotingMachine {
+        &self.machine
+    }
+
+    /// Lit l'instantané : son numéro et la machine.
+    async fn read_snapshot(snapshot_path: &str, layout: Layout) -> anyhow::Result<(u64, VotingMachine)> {
+        let contents = fs::read(snapshot_path).await.with_context(|| format!("impossible de lire la machine {}", snapshot_path))?;
+        let (sequence, machine) = match layout {
+            Layout::Journal => {
+                let snapshot: Snapshot = serde_json::from_slice(&contents).with_context(|| format!("instantané {} corrompu", snapshot_path))?;
+                (snapshot.sequence, snapshot.machine)
+            }
+            Layout::Machine => {
+                let snapshot: MachineSnapshot = serde_json::from_slice(&contents)
+                    .with_context(|| format!("machine {} corrompue ({} octets), écriture interrompue ou fichier modifié", snapshot_path, contents.len()))?;
+                (snapshot.sequence, snapshot.machine)
+            }
+        };
+
+        let machine = VotingMachine::from(machine);
+        machine
+            .check_settings()
+            .map_err(|conflict| anyhow::anyhow!("machine {} incohérente : {:?}", snapshot_path, conflict))?;
+
+        Ok((sequence, machine))
+    }
+
+
+    async fn replay(journal_path: &str, snapshot_path: &str, layout: Layout) -> anyhow::Result<Self> {
+        let (snapshot, mut machine) = Self::read_snapshot(snapshot_path, layout).await?;
+        let journal = match fs::try_exists(journal_path).await? {
+            true => fs::read_to_string(journal_path).await?,
+            false => String::new(),
+        };
+        let staged: Staged = match fs::try_exists(staged_path(journal_path)).await? {
+            true => serde_json::from_slice(&fs::read(staged_path(journal_path)).await?)
+                .with_context(|| format!("bulletins en attente {} corrompus", staged_path(journal_path)))?,
+            false => Staged::default(),
+        };
+
+        let mut sequence = snapshot;
+        let mut since_snapshot = 0;
+        let lines: Vec<&str> = journal.lines().filter(|line| !line.trim().is_empty()).collect();
+
+        for (index, line) in lines.iter().enumerate() {
+            let is_last = index + 1 == lines.len();
+            let record: Record = match serde_json::from_str(line) {
+                Ok(record) => record,
+                // Seule la dernière ligne peut avoir été coupée en cours d'écriture.
+                Err(_) if is_last => break,
+                Err(e) => return Err(e).with_context(|| format!("journal {} corrompu à la ligne {}", journal_path, index + 1)),
+            };
+            // Enregistrements déjà repris dans l'instantané, si le journal n'a pas été vidé.
+            if record.sequence <= snapshot {
+                continue;
+            }
+            if record.sequence != sequence + 1 {
+                anyhow::bail!("journal {} corrompu : enregistrement {} attendu, {} trouvé", journal_path, sequence + 1, record.sequence);
+            }
+            // Un vote secret n'est acquis qu'une fois ses bulletins en attente écrits.
+            if machine.is_secret() && record.sequence > staged.sequence {
+                match is_last {
+                    true => break,
+                    false => anyhow::bail!("journal {} corrompu : bulletins en attente de l'enregistrement {} absents", journal_path, record.sequence),
+                }
+            }
+
+            replay(&mut machine, record.operation);
+            if machine.get_log().head() != record.head {
+                anyhow::bail!("journal {} corrompu : le registre ne se retrouve pas à la ligne {}", journal_path, index + 1);
+            }
+            sequence = record.sequence;
+            since_snapshot += 1;
+        }
+
+        if machine.is_secret() && staged.sequence >= snapshot {
+            machine.restage(staged.staged.into_iter().map(StagedBallot::from).collect());
+        }
+
+        Ok(Self {
+            journal_path: journal_path.to_string(),
+            snapshot_path: snapshot_path.to_string(),
+            layout,
+            snapshot_every: SNAPSHOT_EVERY,
+            machine,
+            sequence,
+            since_snapshot,
+        })
+    }
+
+    /// Ajoute au journal l'opération déjà appliquée à la machine, puis, en scrutin
+    /// secret, réécrit les bulletins en attente. Si l'un des deux échoue, la machine
+    /// revient à son dernier état enregistré.
+    async fn record(&mut self, operation: Operation) -> anyhow::Result<()> {
+        let record = Record { sequence: self.sequence + 1, operation, head: self.machine.get_log().head().to_string() };
+
+        if let Err(e) = self.append(&record).await {
+            self.restore().await?;
+            return Err(e);
+        }
+        if self.machine.is_secret() {
+            if let Err(e) = self.write_staged(record.sequence).await {
+                self.restore().await?;
+                return Err(e);
+            }
+        }
+        self.sequence = record.sequence;
+        self.since_snapshot += 1;
+
+        // L'enregistrement est acquis : un instantané manqué sera retenté au suivant.
+        if self.since_snapshot >= self.snapshot_every {
+            if let Err(e) = self.compact().await {
+                eprintln!("Erreur d'instantané de {} : {}", self.snapshot_path, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Réécrit l'instantané, sous un nouveau numéro, après une opération qui n'a pas
+    /// de ligne dans le journal. Si l'écriture échoue, la machine revient à son
+    /// dernier état enregistré.
+    async fn rewrite(&mut self) -> anyhow::Result<()> {
+        // Le numéro neuf écarte les bulletins en attente écrits avant l'opération.
+        self.sequence += 1;
+
+        if let Err(e) = self.compact().await {
+            self.restore().await?;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Revient à ce que contiennent instantané et journal, puis les compacte : une
+    /// ligne à moitié écrite ne doit pas précéder les suivantes.
+    async fn restore(&mut self) -> anyhow::Result<()> {
+        let saved = Self::replay(&self.journal_path, &self.snapshot_path, self.layout).await?;
+        self.machine = saved.machine;
+        self.sequence = saved.sequence;
+        self.compact().await
+    }
+
+    /// Réécrit l'instantané puis vide le journal. L'instantané porte le numéro du
+    /// dernier enregistrement : une coupure entre les deux ne rejoue rien deux fois.
+    async fn compact(&mut self) -> anyhow::Result<()> {
+        let (sequence, machine) = (self.sequence, VotingMachineDao::from(&self.machine));
+        let contents = match self.layout {
+            Layout::Journal => serde_json::to_vec(&Snapshot { sequence, machine })?,
+            Layout::Machine => serde_json::to_vec(&MachineSnapshot { sequence, machine })?,
+        };
+        write_atomically(&self.snapshot_path, &contents).await?;
+
+        File::create(&self.journal_path).await?.sync_all().await?;
+        self.since_snapshot = 0;
+
+        Ok(())
+    }
+
+    async fn append(&mut self, record: &Record) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.journal_path).await?;
+        file.write_all(&line).await?;
+        file.sync_data().await?;
+
+        Ok(())
+    }
+
+    async fn write_staged(&self, sequence: u64) -> anyhow::Result<()> {
+        let staged = Staged { sequence, staged: self.machine.get_log().staged().iter().cloned().map(StagedBallotDao::from).collect() };
+        write_atomically(&staged_path(&self.journal_path), &serde_json::to_vec(&staged)?).await
+    }
+
+    /// Applique `action` à une copie gardée de la machine : si elle échoue, la machine
+    /// est remise telle quelle. Sinon l'instantané est réécrit.
+    pub(crate) async fn update<T: Send>(&mut self, action: impl for<'a> FnOnce(&'a mut VotingMachine) -> anyhow::Result<T> + Send) -> anyhow::Result<T> {
+        let saved = self.machine.clone();
+        let result = match action(&mut self.machine) {
+            Ok(result) => result,
+            Err(e) => {
+                self.machine = saved;
+                return Err(e);
+            }
+        };
+
+        self.rewrite().await?;
+        Ok(result)
+    }
+
+    /// Applique le dépôt et l'ajoute au journal ; en scrutin secret, la ligne ne porte
+    /// que le votant et le lot que son bulletin a complété.
+    pub(crate) async fn record_ballot(&mut self, deposit: Deposit, schedule: &Schedule, now: DateTime<Utc>) -> anyhow::Result<bool> {
+        if !self.machine.admits(&deposit, schedule, now) {
+            return Ok(false);
+        }
+
+        let chained = self.machine.get_log().entries().len();
+        self.machine.deposit(deposit.clone());
+
+        let operation = match self.machine.is_secret() {
+            false => Operation::Deposit(DepositDao::from(deposit)),
+            true => Operation::Enrolled {
+                voter: deposit.voter.0,
+                attendance: deposit.attendance.map(AttendanceDao::from),
+                sealed: self.machine.get_log().entries()[chained..]
+                    .iter()
+                    .map(|entry| StagedBallotDao::from(StagedBallot { round: entry.round, ballot: entry.ballot.clone(), weight: entry.weight, receipt: entry.receipt.clone() }))
+                    .collect(),
+            },
+        };
+
+        self.record(operation).await?;
+        Ok(true)
+    }
+
+    pub(crate) async fn revoke(&mut self, voter: &Voter, credential: &str) -> anyhow::Result<VoteOutcome> {
+        let outcome = self.machine.revoke(voter, credential);
+
+        if outcome == VoteOutcome::Revoked(voter.clone()) {
+            let holder = self.machine.holder(voter);
+            self.record(Operation::Withdrawal(holder.0)).await?;
+        }
+
+        Ok(outcome)
+    }
+}
+
+/// Rejoue une ligne du journal sur la machine.
+fn replay(machine: &mut VotingMachine, operation: Operation) {
+    match operation {
+        Operation::Deposit(deposit) => machine.deposit(Deposit::from(deposit)),
+        Operation::Enrolled { voter, attendance, sealed } => {
+            machine.enroll(&Voter(voter), attendance.map(Attendance::from));
+            machine.restage(sealed.into_iter().map(StagedBallot::from).collect());
+        }
+        Operation::Withdrawal(voter) => {
+            machine.withdraw(&Voter(voter));
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for JournalStore {
+    async fn exists() -> anyhow::Result<bool> {
+        Ok(fs::try_exists(SNAPSHOT_PATH).await? || fs::try_exists(JOURNAL_PATH).await?)
+    }
+
+    async fn new(machine: VotingMachine) -> anyhow::Result<Self>
+    {
+        Self::open(machine, JOURNAL_PATH, SNAPSHOT_PATH).await
+    }
+
+    async fn reset(machine: VotingMachine) -> anyhow::Result<Self>
+    {
+        Self::create(machine, JOURNAL_PATH, SNAPSHOT_PATH).await
+    }
+
+    async fn read<T: Send>(&self, query: impl for<'a> FnOnce(&'a VotingMachine) -> T + Send) -> anyhow::Result<T> {
+        Ok(query(self.journal.read().await.machine()))
+    }
+
+    async fn update<T: Send>(&self, action: impl for<'a> FnOnce(&'a mut VotingMachine) -> anyhow::Result<T> + Send) -> anyhow::Result<T> {
+        self.journal.write().await.update(action).await
+    }
+
+    async fn record_ballot(&self, deposit: Deposit, schedule: &Schedule, now: DateTime<Utc>) -> anyhow::Result<bool> {
+        self.journal.write().await.record_ballot(deposit, schedule, now).await
+    }
+
+    async fn revoke(&self, voter: &Voter, credential: &str) -> anyhow::Result<VoteOutcome> {
+        self.journal.write().await.revoke(voter, credential).await
+    }
+
+    async fn scores(&self) -> anyhow::Result<Scoreboard> {
+        Ok(self.journal.read().await.machine.get_scoreboard().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{BallotPaper, Candidate, Casting, Phase, TrackingCode};
+
+    use super::*;
+
+    async fn temp_paths(name: &str) -> (String, String) {
+        let journal_path = std::env::temp_dir().join(format!("{}.jsonl", name)).to_str().unwrap().to_string();
+        let snapshot_path = std::env::temp_dir().join(format!("{}.snapshot.json", name)).to_str().unwrap().to_string();
+        let _ = fs::remove_file(&journal_path).await;
+        let _ = fs::remove_file(&snapshot_path).await;
+        let _ = fs::remove_file(staged_path(&journal_path)).await;
+        (journal_path, snapshot_path)
+    }
+
+    #[tokio::test]
+    async fn test_storage_suite() {
+        crate::storage::tests::run(|case, machine| async move {
+            let (journal_path, snapshot_path) = temp_paths(&format!("c1_test_journal_suite_{}", case)).await;
+            JournalStore::create(machine, &journal_path, &snapshot_path).await
+        }).await;
+    }
+
+    fn voted(voting_machine: &VotingMachine, voters: &[&str]) -> VotingMachine {
+        let mut voting_machine = voting_machine.clone();
+        for voter in voters {
+            voting_machine.vote(BallotPaper{ voter: Voter(voter.to_string()), candidate: Some(Candidate(String::from("Louis"))) });
+        }
+        voting_machine
+    }
+
+    async fn vote(store: &JournalStore, voter: &str) {
+        let ballot_paper = BallotPaper{ voter: Voter(voter.to_string()), candidate: Some(Candidate(String::from("Louis"))) };
+        let (deposit, _) = store.read(|voting_machine| voting_machine.prepare_vote(ballot_paper, &Casting::default())).await.expect("err lors de la lecture").expect("vote refuse");
+        assert!(store.record_ballot(deposit, &Schedule::default(), Utc::now()).await.expect("Erreur lors de l'insertion du bulletin"));
+    }
+
+    #[tokio::test]
+    async fn test_open_replays_journal() {
+        let (journal_path, snapshot_path) = temp_paths("c1_test_journal_replay").await;
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Open);
+
+        let store = JournalStore::open(voting_machine.clone(), &journal_path, &snapshot_path).await.expect("Erreur lors de la creation de la memoire");
+        vote(&store, "Tux").await;
+        vote(&store, "Gnu").await;
+
+        let journal = fs::read_to_string(&journal_path).await.expect("Erreur lors de la lecture");
+        assert_eq!(journal.lines().count(), 2);
+        assert!(!journal.lines().nth(1).unwrap().contains("Tux"));
+
+        let store = JournalStore::open(voting_machine.clone(), &journal_path, &snapshot_path).await.expect("Erreur lors de la reprise");
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), voted(&voting_machine, &["Tux", "Gnu"]));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_empties_journal() {
+        let (journal_path, snapshot_path) = temp_paths("c1_test_journal_snapshot").await;
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Open);
+
+        let store = JournalStore::open(voting_machine.clone(), &journal_path, &snapshot_path).await.expect("Erreur lors de la creation de la memoire");
+        store.journal.write().await.snapshot_every = 2;
+        vote(&store, "Tux").await;
+        vote(&store, "Gnu").await;
+        vote(&store, "Ada").await;
+
+        assert_eq!(fs::read_to_string(&journal_path).await.expect("Erreur lors de la lecture").lines().count(), 1);
+
+        let store = JournalStore::open(voting_machine.clone(), &journal_path, &snapshot_path).await.expect("Erreur lors de la reprise");
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), voted(&voting_machine, &["Tux", "Gnu", "Ada"]));
+    }
+
+    #[tokio::test]
+    async fn test_torn_last_record_is_dropped() {
+        let (journal_path, snapshot_path) = temp_paths("c1_test_journal_torn").await;
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Open);
+
+        let store = JournalStore::open(voting_machine.clone(), &journal_path, &snapshot_path).await.expect("Erreur lors de la creation de la memoire");
+        vote(&store, "Tux").await;
+
+        // Coupure en plein ajout du second enregistrement.
+        let mut file = OpenOptions::new().append(true).open(&journal_path).await.expect("Erreur lors de l'ouverture");
+        file.write_all(b"{\"sequence\":2,\"changes\":[{\"Spl").await.expect("Erreur lors de l'ecriture");
+
+        let store = JournalStore::open(voting_machine.clone(), &journal_path, &snapshot_path).await.expect("Erreur lors de la reprise");
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), voted(&voting_machine, &["Tux"]));
+        assert_eq!(fs::read_to_string(&journal_path).await.expect("Erreur lors de la lecture"), "");
+    }
+
+    #[tokio::test]
+    async fn test_secret_journal_keeps_ballots_apart_from_voters() {
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let voting_machine :  VotingMachine = VotingMachine::new(candidates).with_phase(Phase::Open).with_secret(true);
+
+        for snapshot_every in [SNAPSHOT_EVERY, 4] {
+            let (journal_path, snapshot_path) = temp_paths("c1_test_journal_secret").await;
+            let store = JournalStore::open(voting_machine.clone(), &journal_path, &snapshot_path).await.expect("Erreur lors de la creation de la memoire");
+            store.journal.write().await.snapshot_every = snapshot_every;
+
+            for index in 0..12 {
+                let candidate = if index % 3 == 0 { "Louise" } else { "Louis" };
+                let ballot_paper = BallotPaper{ voter: Voter(format!("Votant {}", index)), candidate: Some(Candidate(String::from(candidate))) };
+                let (deposit, _) = store.read(|voting_machine| voting_machine.prepare_vote(ballot_paper, &Casting::default())).await.expect("err lors de la lecture").expect("vote refuse");
+                assert!(store.record_ballot(deposit, &Schedule::default(), Utc::now()).await.expect("Erreur lors de l'insertion du bulletin"));
+            }
+
+            if snapshot_every == SNAPSHOT_EVERY {
+                let journal = fs::read_to_string(&journal_path).await.expect("Erreur lors de la lecture");
+                let lines: Vec<&str> = journal.lines().collect();
+                assert_eq!(lines.len(), 12);
+                assert!(lines.iter().enumerate().all(|(index, line)| line.contains("Ranked") == (index == 9)));
+            }
+
+            let live = store.get_voting_machine().await.expect("err lors de la recuperation de la machine");
+            assert_eq!(live.get_log().staged().len(), 2);
+
+            let store = JournalStore::open(voting_machine.clone(), &journal_path, &snapshot_path).await.expect("Erreur lors de la reprise");
+            assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), live);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_secret_vote_without_staged_ballots_is_dropped() {
+        let (journal_path, snapshot_path) = temp_paths("c1_test_journal_secret_dropped").await;
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Open).with_secret(true);
+
+        let store = JournalStore::open(voting_machine.clone(), &journal_path, &snapshot_path).await.expect("Erreur lors de la creation de la memoire");
+        vote(&store, "Tux").await;
+        let live = store.get_voting_machine().await.expect("err lors de la recuperation de la machine");
+
+        // Coupure entre l'ajout de la ligne et l'écriture des bulletins en attente.
+        let head = live.get_log().head().to_string();
+        let line = format!("{{\"sequence\":2,\"operation\":{{\"Enrolled\":{{\"voter\":\"Gnu\",\"attendance\":\"InPerson\",\"sealed\":[]}}}},\"head\":\"{}\"}}\n", head);
+        let mut file = OpenOptions::new().append(true).open(&journal_path).await.expect("Erreur lors de l'ouverture");
+        file.write_all(line.as_bytes()).await.expect("Erreur lors de l'ecriture");
+
+        let store = JournalStore::open(voting_machine.clone(), &journal_path, &snapshot_path).await.expect("Erreur lors de la reprise");
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), live);
+    }
+
+    #[tokio::test]
+    async fn test_withdrawal_is_replayed() {
+        let (journal_path, snapshot_path) = temp_paths("c1_test_journal_withdrawal").await;
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Open).with_revote(true);
+        let code = TrackingCode(String::from("ABCD-EFGH-JKLM"));
+
+        let store = JournalStore::open(voting_machine.clone(), &journal_path, &snapshot_path).await.expect("Erreur lors de la creation de la memoire");
+        let casting = Casting { proxy: None, receipt: Some(code.clone()) };
+        let ballot_paper = BallotPaper{ voter: Voter(String::from("Tux")), candidate: Some(Candidate(String::from("Louis"))) };
+        let (deposit, _) = store.read(|voting_machine| voting_machine.prepare_vote(ballot_paper, &casting)).await.expect("err lors de la lecture").expect("vote refuse");
+        store.record_ballot(deposit, &Schedule::default(), Utc::now()).await.expect("Erreur lors de l'insertion du bulletin");
+        vote(&store, "Gnu").await;
+
+        let outcome = store.revoke(&Voter(String::from("Tux")), &code.0).await.expect("Erreur lors du retrait");
+        assert_eq!(outcome, VoteOutcome::Revoked(Voter(String::from("Tux"))));
+        assert_eq!(fs::read_to_string(&journal_path).await.expect("Erreur lors de la lecture").lines().count(), 3);
+
+        let live = store.get_voting_machine().await.expect("err lors de la recuperation de la machine");
+        let store = JournalStore::open(voting_machine.clone(), &journal_path, &snapshot_path).await.expect("Erreur lors de la reprise");
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), live);
+        assert_eq!(live.get_ballots().len(), 1);
+    }
+}
@@ -1,27 +1,61 @@
-use anyhow::Ok;
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use crate::{domain::VotingMachine, storage::Storage};
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+
+use crate::domain::{Deposit, Schedule, Scoreboard, VoteOutcome, Voter, VotingMachine};
+use crate::storage::Storage;
 
+/// Les copies d'un même `MemoryStore` partagent la machine.
 #[derive(Clone)]
 pub struct MemoryStore{
-   machine: VotingMachine,
+   machine: Arc<RwLock<VotingMachine>>,
 }
 
 #[async_trait]
 impl Storage for MemoryStore {
     async fn new(machine: VotingMachine) -> anyhow::Result<Self>
     {
-        Ok(Self { machine })
+        Ok(Self { machine: Arc::new(RwLock::new(machine)) })
+    }
+
+    async fn read<T: Send>(&self, query: impl for<'a> FnOnce(&'a VotingMachine) -> T + Send) -> anyhow::Result<T>
+    {
+        Ok(query(&*self.machine.read().await))
+    }
+
+    async fn update<T: Send>(&self, action: impl for<'a> FnOnce(&'a mut VotingMachine) -> anyhow::Result<T> + Send) -> anyhow::Result<T>
+    {
+        let mut machine = self.machine.write().await;
+        let saved = machine.clone();
+
+        let result = action(&mut machine);
+        if result.is_err() {
+            *machine = saved;
+        }
+        result
     }
 
-    async fn get_voting_machine(&self) -> anyhow::Result<VotingMachine>
+    async fn record_ballot(&self, deposit: Deposit, schedule: &Schedule, now: DateTime<Utc>) -> anyhow::Result<bool>
     {
-        Ok(self.machine.clone())
+        let mut machine = self.machine.write().await;
+        if !machine.admits(&deposit, schedule, now) {
+            return Ok(false);
+        }
+
+        machine.deposit(deposit);
+        Ok(true)
+    }
+
+    async fn revoke(&self, voter: &Voter, credential: &str) -> anyhow::Result<VoteOutcome>
+    {
+        Ok(self.machine.write().await.revoke(voter, credential))
     }
-    async fn put_voting_machine(&mut self, machine: VotingMachine) -> anyhow::Result<()>
+
+    async fn scores(&self) -> anyhow::Result<Scoreboard>
     {
-        self.machine = machine;
-        Ok(())
+        Ok(self.machine.read().await.get_scoreboard().clone())
     }
 }
 
@@ -32,14 +66,19 @@ mod tests {
 
     use super::*;
 
+    #[tokio::test]
+    async fn test_storage_suite() {
+        crate::storage::tests::run(|_, machine| MemoryStore::new(machine)).await;
+    }
+
     #[tokio::test]
     async fn test_get_return_what_we_inserted() {
 
         let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]);
 
-        let mut store : MemoryStore = MemoryStore::new(voting_machine.clone()).await.expect("Erreur lors de la creation de la memoire");
+        let store : MemoryStore = MemoryStore::new(VotingMachine::new(vec![])).await.expect("Erreur lors de la creation de la memoire");
 
-        store.put_voting_machine(voting_machine.clone()).await.expect("Erreur lors de l'insertion de la machine");
+        store.update(|machine| { *machine = voting_machine.clone(); Ok(()) }).await.expect("Erreur lors de l'insertion de la machine");
 
         let expected_machine = store.get_voting_machine().await.expect("err lors de la recuperation de la machine");
 
@@ -51,14 +90,15 @@ mod tests {
 
         let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]);
 
-        let mut store1 : MemoryStore = MemoryStore::new(voting_machine.clone()).await.expect("Erreur lors de la creation de la memoire");
-        let store2 : MemoryStore = MemoryStore::new(voting_machine.clone()).await.expect("Erreur lors de la creation de la memoire");
+        let store1 : MemoryStore = MemoryStore::new(voting_machine.clone()).await.expect("Erreur lors de la creation de la memoire");
+        let store2 : MemoryStore = store1.clone();
 
-        store1.put_voting_machine(voting_machine.clone()).await.expect("Erreur lors de l'insertion de la machine");
+        store1.update(|machine| Ok(machine.add_candidate(Candidate(String::from("Louise"))))).await.expect("Erreur lors de l'insertion de la machine");
 
         let machine2 = store1.get_voting_machine().await.expect("err lors de la recuperation de la machine dans l'instance 1");
         let machine1 = store2.get_voting_machine().await.expect("err lors de la recuperation de la machine dans l'ibstance 2");
 
         assert_eq!(machine1, machine2);
+        assert_eq!(store2.scores().await.expect("err lors de la recuperation des scores").scores.len(), 2);
     }
 }
\ No newline at end of file
@@ -1,2 +1,4 @@
 pub mod memory;
-pub mod file;
\ No newline at end of file
+pub mod file;
+pub mod journal;
+pub mod sqlite;
\ No newline at end of file
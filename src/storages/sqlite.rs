@@ -0,0 +1,829 @@
+use std::collections::BTreeMap as Map;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use tokio::sync::RwLock;
+
+use crate::ballot_log::StagedBallot;
+use crate::domain::{Attendance, Ballot, Candidate, Deposit, Grade, Schedule, Scoreboard, VoteOutcome, Voter, VotingMachine, Weight, WeightedBallot};
+use crate::storage::Storage;
+use crate::storages::file::{BallotDao, VotingMachineDao};
+use crate::storages::journal::SNAPSHOT_EVERY;
+
+const FILEPATH : &str = "machine.sqlite3";
+
+/// Un vote ajoute une ligne à `voters` et une à `ballots`, un retrait une ligne à
+/// `withdrawals`, numérotées à la suite. L'unique ligne de `machine` est un instantané
+/// de la machine, avec le numéro de la dernière ligne qu'il contient et le réglage du
+/// changement de vote.
+///
+/// Un votant n'a qu'une ligne par tour, de révision 0 : la base refuse elle-même un
+/// second vote. Seul un scrutin qui permet de changer de vote admet les révisions
+/// suivantes, une par nouveau vote du tour.
+///
+/// Un bulletin garde sa forme dans `kind` et ses choix dans `choices`, un par ligne,
+/// à leur place sur le bulletin, avec la note s'il y en a une ; seul un bulletin
+/// chiffré garde son contenu dans `encrypted`. Un bulletin remplacé par un nouveau
+/// vote ou retiré reçoit dans `withdrawn_at` le numéro de l'opération qui l'a écarté.
+///
+/// En scrutin secret, un bulletin ne porte pas le numéro du vote : la table est rangée
+/// par une clé tirée au hasard, et `batch` donne le vote qui a chaîné son lot, nul tant
+/// que le bulletin est en attente.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS machine (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        sequence INTEGER NOT NULL,
+        revote INTEGER NOT NULL DEFAULT 0,
+        state TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS candidates (
+        name TEXT PRIMARY KEY
+    );
+    CREATE TABLE IF NOT EXISTS voters (
+        sequence INTEGER PRIMARY KEY,
+        round INTEGER NOT NULL DEFAULT 0,
+        name TEXT NOT NULL,
+        revision INTEGER NOT NULL DEFAULT 0 CHECK (revision >= 0),
+        attendance TEXT CHECK (attendance IN ('InPerson', 'ByProxy')),
+        proxy TEXT,
+        head TEXT NOT NULL,
+        UNIQUE (round, name, revision)
+    );
+    CREATE TRIGGER IF NOT EXISTS single_vote BEFORE INSERT ON voters
+    WHEN NEW.revision > 0 AND NOT COALESCE((SELECT revote FROM machine WHERE id = 1), 0)
+    BEGIN
+        SELECT RAISE(ABORT, 'changement de vote non permis');
+    END;
+    CREATE TABLE IF NOT EXISTS ballots (
+        id TEXT PRIMARY KEY,
+        sequence INTEGER,
+        batch INTEGER,
+        round INTEGER NOT NULL,
+        kind TEXT NOT NULL CHECK (kind IN ('Blank', 'Invalid', 'Ranked', 'Approval', 'Graded', 'Encrypted')),
+        encrypted TEXT CHECK ((kind = 'Encrypted') = (encrypted IS NOT NULL)),
+        weight INTEGER NOT NULL,
+        receipt TEXT,
+        withdrawn_at INTEGER
+    ) WITHOUT ROWID;
+    CREATE TABLE IF NOT EXISTS choices (
+        ballot TEXT NOT NULL REFERENCES ballots (id),
+        position INTEGER NOT NULL,
+        candidate TEXT NOT NULL,
+        grade INTEGER,
+        PRIMARY KEY (ballot, position)
+    ) WITHOUT ROWID;
+    CREATE TABLE IF NOT EXISTS withdrawals (
+        sequence INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        head TEXT NOT NULL
+    );
+";
+
+const DROP_TABLES: &str = "
+    DROP TABLE IF EXISTS machine;
+    DROP TABLE IF EXISTS candidates;
+    DROP TABLE IF EXISTS voters;
+    DROP TABLE IF EXISTS choices;
+    DROP TABLE IF EXISTS ballots;
+    DROP TABLE IF EXISTS withdrawals;
+";
+
+/// Base SQLite embarquée. Un vote n'écrit que ses deux lignes ; l'instantané n'est
+/// réécrit que par les autres opérations, rares, et tous les `SNAPSHOT_EVERY` votes.
+/// Au démarrage, la machine rejoue sur l'instantané les lignes plus récentes.
+///
+/// La connexion, bloquante, n'est utilisée que depuis `spawn_blocking`.
+#[derive(Clone)]
+pub struct SqliteStore{
+    ledger: Arc<RwLock<Ledger>>,
+    connection: Arc<Mutex<Connection>>,
+}
+
+/// La machine, avec le numéro de la dernière ligne enregistrée.
+struct Ledger{
+    machine: VotingMachine,
+    sequence: i64,
+    since_snapshot: usize,
+    snapshot_every: usize,
+}
+
+/// Lignes qu'une opération ajoute aux tables.
+enum Operation{
+    Vote { voter: String, attendance: Option<Attendance>, ballot: BallotRow, secret: bool, revote: bool, sealed: bool, head: String },
+    Withdrawal { voter: String, round: usize, head: String },
+}
+
+/// Vote ou retrait relu dans les tables ; le votant et la tête du registre qu'il a
+/// laissée l'accompagnent.
+enum Line{
+    Vote(Option<Attendance>),
+    Withdrawal,
+}
+
+/// Ligne de `ballots`, avec ses lignes de `choices`.
+struct BallotRow{
+    round: usize,
+    kind: String,
+    choices: Vec<Choice>,
+    encrypted: Option<String>,
+    weight: usize,
+    receipt: Option<String>,
+}
+
+/// Ligne de `choices`, à sa place dans le bulletin.
+struct Choice{
+    candidate: String,
+    grade: Option<usize>,
+}
+
+/// Instantané de la machine et liste de ses candidats.
+struct Snapshot{
+    sequence: i64,
+    revote: bool,
+    state: String,
+    candidates: Vec<String>,
+    /// Scrutin secret sans bulletin en attente : les lots sont tous chaînés.
+    settled: bool,
+}
+
+impl BallotRow {
+    fn new(round: usize, ballot: WeightedBallot, receipt: Option<String>) -> anyhow::Result<Self> {
+        let listed = |candidates: Vec<Candidate>| candidates.into_iter().map(|candidate| Choice { candidate: candidate.0, grade: None }).collect();
+        let (kind, choices, encrypted) = match ballot.ballot {
+            Ballot::Blank => ("Blank", vec![], None),
+            Ballot::Invalid => ("Invalid", vec![], None),
+            Ballot::Ranked(ranking) => ("Ranked", listed(ranking), None),
+            Ballot::Approval(approved) => ("Approval", listed(approved), None),
+            Ballot::Graded(grades) => ("Graded", grades.into_iter().map(|(candidate, grade)| Choice { candidate: candidate.0, grade: Some(grade.0) }).collect(), None),
+            encrypted @ Ballot::Encrypted(_) => ("Encrypted", vec![], Some(serde_json::to_string(&BallotDao::from(encrypted))?)),
+        };
+        Ok(Self { round, kind: kind.to_string(), choices, encrypted, weight: ballot.weight.0, receipt })
+    }
+
+    fn weighted(&self) -> anyhow::Result<WeightedBallot> {
+        let candidates = || self.choices.iter().map(|choice| Candidate(choice.candidate.clone())).collect();
+        let ballot = match (self.kind.as_str(), &self.encrypted) {
+            ("Blank", None) => Ballot::Blank,
+            ("Invalid", None) => Ballot::Invalid,
+            ("Ranked", None) => Ballot::Ranked(candidates()),
+            ("Approval", None) => Ballot::Approval(candidates()),
+            ("Graded", None) => Ballot::Graded(
+                self.choices
+                    .iter()
+                    .map(|choice| Ok((Candidate(choice.candidate.clone()), Grade(choice.grade.with_context(|| format!("note de {} absente", choice.candidate))?))))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            ("Encrypted", Some(encrypted)) => Ballot::from(serde_json::from_str::<BallotDao>(encrypted)?),
+            (kind, _) => anyhow::bail!("bulletin {} invalide", kind),
+        };
+        Ok(WeightedBallot { ballot, weight: Weight(self.weight) })
+    }
+
+    fn insert(&self, transaction: &Transaction, sequence: Option<i64>) -> anyhow::Result<()> {
+        let id = hex::encode(rand::random::<[u8; 16]>());
+        transaction.execute(
+            "INSERT INTO ballots (id, sequence, round, kind, encrypted, weight, receipt) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![id, sequence, self.round, self.kind, self.encrypted, self.weight, self.receipt],
+        )?;
+        for (position, choice) in self.choices.iter().enumerate() {
+            transaction.execute(
+                "INSERT INTO choices (ballot, position, candidate, grade) VALUES (?1, ?2, ?3, ?4)",
+                params![id, position, choice.candidate, choice.grade],
+            )?;
+        }
+        Ok(())
+    }
+
+    fn into_staged(self) -> anyhow::Result<StagedBallot> {
+        let weighted = self.weighted()?;
+        Ok(StagedBallot { round: self.round, ballot: weighted.ballot, weight: weighted.weight, receipt: self.receipt })
+    }
+}
+
+/// Bulletins en attente, triés comme la machine les range.
+fn staged(rows: Vec<BallotRow>) -> anyhow::Result<Vec<StagedBallot>> {
+    let mut staged = rows.into_iter().map(BallotRow::into_staged).collect::<anyhow::Result<Vec<_>>>()?;
+    staged.sort();
+    Ok(staged)
+}
+
+fn attendance_columns(attendance: &Option<Attendance>) -> (Option<&'static str>, Option<&str>) {
+    match attendance {
+        Some(Attendance::InPerson) => (Some("InPerson"), None),
+        Some(Attendance::ByProxy(proxy)) => (Some("ByProxy"), Some(proxy.0.as_str())),
+        None => (None, None),
+    }
+}
+
+fn attendance_from_columns(attendance: Option<String>, proxy: Option<String>) -> anyhow::Result<Option<Attendance>> {
+    match (attendance.as_deref(), proxy) {
+        (Some("InPerson"), None) => Ok(Some(Attendance::InPerson)),
+        (Some("ByProxy"), Some(proxy)) => Ok(Some(Attendance::ByProxy(Voter(proxy)))),
+        (None, None) => Ok(None),
+        (attendance, proxy) => anyhow::bail!("émargement {:?} par {:?} invalide", attendance, proxy),
+    }
+}
+
+/// Écarte les bulletins encore valables du votant pour ce tour.
+fn withdraw_ballots(transaction: &Transaction, sequence: i64, round: usize, voter: &str) -> anyhow::Result<()> {
+    transaction.execute(
+        "UPDATE ballots SET withdrawn_at = ?1
+         WHERE withdrawn_at IS NULL AND round = ?2 AND sequence IN (SELECT sequence FROM voters WHERE round = ?2 AND name = ?3)",
+        params![sequence, round, voter],
+    )?;
+    Ok(())
+}
+
+impl Operation {
+    fn insert(&self, transaction: &Transaction, sequence: i64) -> anyhow::Result<()> {
+        match self {
+            Operation::Vote { voter, attendance, ballot, secret, revote, sealed, head } => {
+                // Sans changement de vote, la révision 0 déjà prise fait refuser l'insertion.
+                let revision: i64 = match revote {
+                    true => transaction.query_row("SELECT COUNT(*) FROM voters WHERE round = ?1 AND name = ?2", params![ballot.round, voter], |row| row.get(0))?,
+                    false => 0,
+                };
+                if *revote {
+                    withdraw_ballots(transaction, sequence, ballot.round, voter)?;
+                }
+                let (attendance, proxy) = attendance_columns(attendance);
+                transaction
+                    .execute(
+                        "INSERT INTO voters (sequence, round, name, revision, attendance, proxy, head) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        params![sequence, ballot.round, voter, revision, attendance, proxy, head],
+                    )
+                    .with_context(|| format!("{} a déjà voté", voter))?;
+
+                // En scrutin secret, rien ne relie le bulletin au vote.
+                ballot.insert(transaction, (!secret).then_some(sequence))?;
+                if *sealed {
+                    transaction.execute("UPDATE ballots SET batch = ?1 WHERE batch IS NULL", params![sequence])?;
+                }
+            }
+            Operation::Withdrawal { voter, round, head } => {
+                withdraw_ballots(transaction, sequence, *round, voter)?;
+                transaction.execute("INSERT INTO withdrawals (sequence, name, head) VALUES (?1, ?2, ?3)", params![sequence, voter, head])?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Snapshot {
+    fn write(&self, transaction: &Transaction) -> anyhow::Result<()> {
+        transaction.execute("INSERT OR REPLACE INTO machine (id, sequence, revote, state) VALUES (1, ?1, ?2, ?3)", params![self.sequence, self.revote, self.state])?;
+
+        transaction.execute("DELETE FROM candidates", [])?;
+        for name in &self.candidates {
+            transaction.execute("INSERT INTO candidates (name) VALUES (?1)", params![name])?;
+        }
+
+        // La clôture chaîne le dernier lot, incomplet : ses bulletins ne sont plus en attente.
+        if self.settled {
+            transaction.execute("UPDATE ballots SET batch = ?1 WHERE batch IS NULL", params![self.sequence])?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Ledger {
+    fn snapshot(&self, sequence: i64) -> anyhow::Result<Snapshot> {
+        Ok(Snapshot {
+            sequence,
+            revote: self.machine.allows_revote(),
+            state: serde_json::to_string(&VotingMachineDao::from(&self.machine))?,
+            candidates: self.machine.get_candidates().into_iter().map(|candidate| candidate.0).collect(),
+            settled: self.machine.is_secret() && self.machine.get_log().staged().is_empty(),
+        })
+    }
+
+    /// Relit l'instantané puis rejoue, dans l'ordre, les votes et retraits plus récents.
+    fn load(connection: &Connection) -> anyhow::Result<Option<Self>> {
+        let snapshot: Option<(i64, String)> = connection
+            .query_row("SELECT sequence, state FROM machine WHERE id = 1", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()?;
+        let Some((snapshot, state)) = snapshot else {
+            return Ok(None);
+        };
+        let dao: VotingMachineDao = serde_json::from_str(&state).context("instantané illisible")?;
+        let mut machine = VotingMachine::from(dao);
+
+        let mut lines: Map<i64, (Line, String, String)> = Map::new();
+        let mut statement = connection.prepare("SELECT sequence, name, attendance, proxy, head FROM voters WHERE sequence > ?1")?;
+        let voters = statement.query_map(params![snapshot], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))?;
+        for voter in voters {
+            let (sequence, name, attendance, proxy, head) = voter?;
+            lines.insert(sequence, (Line::Vote(attendance_from_columns(attendance, proxy)?), name, head));
+        }
+        let mut statement = connection.prepare("SELECT sequence, name, head FROM withdrawals WHERE sequence > ?1")?;
+        let withdrawals = statement.query_map(params![snapshot], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        for withdrawal in withdrawals {
+            let (sequence, name, head) = withdrawal?;
+            lines.insert(sequence, (Line::Withdrawal, name, head));
+        }
+
+        // Bulletins d'un vote, bulletins secrets par vote qui a chaîné leur lot, bulletins en attente.
+        const REPLAYED: &str = "sequence > ?1 OR batch > ?1 OR (sequence IS NULL AND batch IS NULL)";
+        let mut choices: Map<String, Vec<Choice>> = Map::new();
+        let mut statement = connection.prepare(&format!(
+            "SELECT ballot, candidate, grade FROM choices WHERE ballot IN (SELECT id FROM ballots WHERE {}) ORDER BY ballot, position",
+            REPLAYED,
+        ))?;
+        let rows = statement.query_map(params![snapshot], |row| Ok((row.get(0)?, Choice { candidate: row.get(1)?, grade: row.get(2)? })))?;
+        for row in rows {
+            let (id, choice) = row?;
+            choices.entry(id).or_default().push(choice);
+        }
+
+        let mut ballots: Map<i64, BallotRow> = Map::new();
+        let mut batches: Map<i64, Vec<BallotRow>> = Map::new();
+        let mut pending: Vec<BallotRow> = vec![];
+        let mut statement = connection.prepare(&format!("SELECT id, sequence, batch, round, kind, encrypted, weight, receipt FROM ballots WHERE {}", REPLAYED))?;
+        let rows = statement.query_map(params![snapshot], |row| {
+            let id: String = row.get(0)?;
+            let ballot = BallotRow {
+                round: row.get(3)?,
+                kind: row.get(4)?,
+                choices: choices.remove(&id).unwrap_or_default(),
+                encrypted: row.get(5)?,
+                weight: row.get(6)?,
+                receipt: row.get(7)?,
+            };
+            Ok((row.get::<_, Option<i64>>(1)?, row.get::<_, Option<i64>>(2)?, ballot))
+        })?;
+        for row in rows {
+            match row? {
+                (Some(sequence), _, ballot) => {
+                    ballots.insert(sequence, ballot);
+                }
+                (None, Some(batch), ballot) => batches.entry(batch).or_default().push(ballot),
+                (None, None, ballot) => pending.push(ballot),
+            }
+        }
+
+        let mut sequence = snapshot;
+        let mut since_snapshot = 0;
+        for (number, (line, name, head)) in lines {
+            match line {
+                Line::Vote(attendance) => {
+                    match machine.is_secret() {
+                        false => {
+                            let ballot = ballots.remove(&number).with_context(|| format!("bulletin du vote {} absent", number))?;
+                            machine.deposit(Deposit { voter: Voter(name), attendance, ballot: ballot.weighted()?, receipt: ballot.receipt });
+                        }
+                        true => {
+                            machine.enroll(&Voter(name), attendance);
+                            if let Some(sealed) = batches.remove(&number) {
+                                machine.restage(staged(sealed)?);
+                            }
+                        }
+                    }
+                }
+                Line::Withdrawal => {
+                    machine.withdraw(&Voter(name));
+                }
+            }
+
+            if machine.get_log().head() != head {
+                anyhow::bail!("le registre ne se retrouve pas à la ligne {}", number);
+            }
+            sequence = number;
+            since_snapshot += 1;
+        }
+
+        if machine.is_secret() {
+            machine.restage(staged(pending)?);
+        }
+
+        Ok(Some(Self { machine, sequence, since_snapshot, snapshot_every: SNAPSHOT_EVERY }))
+    }
+}
+
+fn connect(filepath: &str) -> anyhow::Result<Connection> {
+    let connection = Connection::open(filepath).with_context(|| format!("impossible d'ouvrir la base {}", filepath))?;
+    connection.execute_batch(SCHEMA)?;
+    Ok(connection)
+}
+
+/// Exécute `work` sur la connexion dans un fil à part : SQLite bloque le fil qui l'appelle.
+async fn blocking<T: Send + 'static>(connection: &Arc<Mutex<Connection>>, work: impl FnOnce(&mut Connection) -> anyhow::Result<T> + Send + 'static) -> anyhow::Result<T> {
+    let connection = connection.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut connection = connection.lock().map_err(|_| anyhow::anyhow!("base SQLite inutilisable après une erreur"))?;
+        work(&mut connection)
+    })
+    .await?
+}
+
+impl SqliteStore{
+
+    /// Repart d'un scrutin vierge : les tables sont recréées.
+    pub async fn create(machine: VotingMachine, filepath: &str) -> anyhow::Result<Self> {
+        let ledger = Ledger { machine, sequence: 0, since_snapshot: 0, snapshot_every: SNAPSHOT_EVERY };
+        let snapshot = ledger.snapshot(0)?;
+        let filepath = filepath.to_string();
+
+        let connection = tokio::task::spawn_blocking(move || -> anyhow::Result<Connection> {
+            let mut connection = Connection::open(&filepath).with_context(|| format!("impossible d'ouvrir la base {}", filepath))?;
+            let transaction = connection.transaction()?;
+            transaction.execute_batch(DROP_TABLES)?;
+            transaction.execute_batch(SCHEMA)?;
+            snapshot.write(&transaction)?;
+            transaction.commit()?;
+            Ok(connection)
+        })
+        .await??;
+
+        Ok(Self { ledger: Arc::new(RwLock::new(ledger)), connection: Arc::new(Mutex::new(connection)) })
+    }
+
+    /// Reprend le scrutin enregistré dans la base s'il existe, à condition qu'il
+    /// ait les réglages de `machine` ; sinon, commence avec `machine`.
+    pub async fn open(machine: VotingMachine, filepath: &str) -> anyhow::Result<Self> {
+        let path = filepath.to_string();
+        let (connection, ledger) = tokio::task::spawn_blocking(move || -> anyhow::Result<_> {
+            let connection = connect(&path)?;
+            let ledger = Ledger::load(&connection);
+            Ok((connection, ledger))
+        })
+        .await??;
+
+        let ledger = match ledger.with_context(|| format!("base {} corrompue, relancer avec --reset pour repartir d'un scrutin vierge", filepath))? {
+            Some(ledger) => ledger,
+            None => return Self::create(machine, filepath).await,
+        };
+        if let Err(mismatch) = ledger.machine.compare_settings(&machine) {
+            anyhow::bail!("la base {} ne correspond pas à la configuration ({:?} différent), relancer avec --reset pour repartir d'un scrutin vierge", filepath, mismatch);
+        }
+
+        Ok(Self { ledger: Arc::new(RwLock::new(ledger)), connection: Arc::new(Mutex::new(connection)) })
+    }
+
+    /// Ajoute, en une transaction, les lignes de l'opération déjà appliquée à la machine,
+    /// et l'instantané s'il est temps. Si la transaction échoue, la machine revient au
+    /// contenu des tables.
+    async fn record(&self, ledger: &mut Ledger, operation: Operation) -> anyhow::Result<()> {
+        let sequence = ledger.sequence + 1;
+        let snapshot = match ledger.since_snapshot + 1 >= ledger.snapshot_every {
+            true => Some(ledger.snapshot(sequence)?),
+            false => None,
+        };
+        let compacted = snapshot.is_some();
+
+        let written = blocking(&self.connection, move |connection| {
+            let transaction = connection.transaction()?;
+            operation.insert(&transaction, sequence)?;
+            if let Some(snapshot) = snapshot {
+                snapshot.write(&transaction)?;
+            }
+            transaction.commit()?;
+            Ok(())
+        })
+        .await;
+        if let Err(e) = written {
+            self.restore(ledger).await?;
+            return Err(e);
+        }
+
+        ledger.sequence = sequence;
+        ledger.since_snapshot = match compacted {
+            true => 0,
+            false => ledger.since_snapshot + 1,
+        };
+        Ok(())
+    }
+
+    /// Réécrit l'instantané, après une opération qui n'a pas de ligne à elle.
+    async fn rewrite(&self, ledger: &mut Ledger) -> anyhow::Result<()> {
+        let snapshot = ledger.snapshot(ledger.sequence)?;
+
+        let written = blocking(&self.connection, move |connection| {
+            let transaction = connection.transaction()?;
+            snapshot.write(&transaction)?;
+            transaction.commit()?;
+            Ok(())
+        })
+        .await;
+        if let Err(e) = written {
+            self.restore(ledger).await?;
+            return Err(e);
+        }
+
+        ledger.since_snapshot = 0;
+        Ok(())
+    }
+
+    /// Revient au contenu des tables.
+    async fn restore(&self, ledger: &mut Ledger) -> anyhow::Result<()> {
+        let saved = blocking(&self.connection, |connection| Ledger::load(connection)).await?.context("base SQLite vidée")?;
+        *ledger = Ledger { snapshot_every: ledger.snapshot_every, ..saved };
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStore {
+    async fn exists() -> anyhow::Result<bool> {
+        if !std::path::Path::new(FILEPATH).exists() {
+            return Ok(false);
+        }
+        tokio::task::spawn_blocking(|| -> anyhow::Result<bool> {
+            let connection = connect(FILEPATH)?;
+            Ok(connection.query_row("SELECT EXISTS (SELECT 1 FROM machine)", [], |row| row.get(0))?)
+        })
+        .await?
+    }
+
+    async fn new(machine: VotingMachine) -> anyhow::Result<Self>
+    {
+        Self::open(machine, FILEPATH).await
+    }
+
+    async fn reset(machine: VotingMachine) -> anyhow::Result<Self>
+    {
+        Self::create(machine, FILEPATH).await
+    }
+
+    async fn read<T: Send>(&self, query: impl for<'a> FnOnce(&'a VotingMachine) -> T + Send) -> anyhow::Result<T> {
+        Ok(query(&self.ledger.read().await.machine))
+    }
+
+    async fn update<T: Send>(&self, action: impl for<'a> FnOnce(&'a mut VotingMachine) -> anyhow::Result<T> + Send) -> anyhow::Result<T> {
+        let mut ledger = self.ledger.write().await;
+        let saved = ledger.machine.clone();
+        let result = match action(&mut ledger.machine) {
+            Ok(result) => result,
+            Err(e) => {
+                ledger.machine = saved;
+                return Err(e);
+            }
+        };
+
+        self.rewrite(&mut ledger).await?;
+        Ok(result)
+    }
+
+    async fn record_ballot(&self, deposit: Deposit, schedule: &Schedule, now: DateTime<Utc>) -> anyhow::Result<bool> {
+        let mut ledger = self.ledger.write().await;
+        if !ledger.machine.admits(&deposit, schedule, now) {
+            return Ok(false);
+        }
+
+        let ballot = BallotRow::new(ledger.machine.get_rounds().len(), deposit.ballot.clone(), deposit.receipt.clone())?;
+        let chained = ledger.machine.get_log().entries().len();
+        ledger.machine.deposit(deposit.clone());
+
+        let secret = ledger.machine.is_secret();
+        let operation = Operation::Vote {
+            voter: deposit.voter.0,
+            attendance: deposit.attendance,
+            ballot,
+            secret,
+            revote: ledger.machine.allows_revote(),
+            sealed: secret && ledger.machine.get_log().entries().len() > chained,
+            head: ledger.machine.get_log().head().to_string(),
+        };
+
+        self.record(&mut ledger, operation).await?;
+        Ok(true)
+    }
+
+    async fn revoke(&self, voter: &Voter, credential: &str) -> anyhow::Result<VoteOutcome> {
+        let mut ledger = self.ledger.write().await;
+        let outcome = ledger.machine.revoke(voter, credential);
+
+        if outcome == VoteOutcome::Revoked(voter.clone()) {
+            let operation = Operation::Withdrawal { voter: ledger.machine.holder(voter).0, round: ledger.machine.get_rounds().len(), head: ledger.machine.get_log().head().to_string() };
+            self.record(&mut ledger, operation).await?;
+        }
+
+        Ok(outcome)
+    }
+
+    async fn scores(&self) -> anyhow::Result<Scoreboard> {
+        Ok(self.ledger.read().await.machine.get_scoreboard().clone())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{BallotPaper, Casting, Score, Phase, TrackingCode};
+
+    use super::*;
+
+    async fn temp_filepath(name: &str) -> String {
+        let filepath = std::env::temp_dir().join(name).to_str().unwrap().to_string();
+        let _ = tokio::fs::remove_file(&filepath).await;
+        filepath
+    }
+
+    async fn vote(store: &SqliteStore, voter: &str, candidate: &str, casting: Casting) {
+        let ballot_paper = BallotPaper{ voter: Voter(voter.to_string()), candidate: Some(Candidate(candidate.to_string())) };
+        let (deposit, _) = store.read(|voting_machine| voting_machine.prepare_vote(ballot_paper, &casting)).await.expect("err lors de la lecture").expect("vote refuse");
+        assert!(store.record_ballot(deposit, &Schedule::default(), Utc::now()).await.expect("Erreur lors de l'insertion du bulletin"));
+    }
+
+    fn count(filepath: &str, query: &str) -> usize {
+        let connection = Connection::open(filepath).expect("Erreur lors de l'ouverture");
+        connection.query_row(query, [], |row| row.get(0)).expect("Erreur lors de la requete")
+    }
+
+    #[tokio::test]
+    async fn test_storage_suite() {
+        crate::storage::tests::run(|case, machine| async move {
+            let filepath = temp_filepath(&format!("c1_test_sqlite_suite_{}.sqlite3", case)).await;
+            SqliteStore::create(machine, &filepath).await
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn test_get_return_what_we_inserted() {
+
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]);
+
+        let filepath = temp_filepath("c1_test_sqlite_get.sqlite3").await;
+        let store: SqliteStore = SqliteStore::create(VotingMachine::new(vec![]), &filepath).await.expect("Erreur lors de la creation de la memoire");
+
+        store.update(|machine| { *machine = voting_machine.clone(); Ok(()) }).await.expect("Erreur lors de l'insertion de la machine");
+
+        let expected_machine = store.get_voting_machine().await.expect("err lors de la recuperation de la machine");
+
+        assert_eq!(expected_machine, voting_machine);
+    }
+
+    #[tokio::test]
+    async fn test_keep_file_informations_between_many_instance() {
+
+        let mut voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))]).with_phase(Phase::Open);
+
+        let filepath = temp_filepath("c1_test_sqlite_instances.sqlite3").await;
+        let store1: SqliteStore = SqliteStore::open(voting_machine.clone(), &filepath).await.expect("Erreur lors de la creation de la memoire");
+
+        voting_machine.vote(BallotPaper{ voter: Voter(String::from("Tux")), candidate: Some(Candidate(String::from("Louise"))) });
+        voting_machine.register_proxy(Voter(String::from("Tux")), Voter(String::from("Gnu"))).expect("procuration refusee");
+        voting_machine.vote_for(&Voter(String::from("Tux")), |voting_machine| {
+            voting_machine.vote(BallotPaper{ voter: Voter(String::from("Gnu")), candidate: None })
+        });
+        store1.update(|machine| { *machine = voting_machine.clone(); Ok(()) }).await.expect("Erreur lors de l'insertion de la machine");
+
+        let store2: SqliteStore = SqliteStore::open(voting_machine.clone(), &filepath).await.expect("Erreur lors de la reprise");
+
+        let machine1 = store1.get_voting_machine().await.expect("err lors de la recuperation de la machine dans l'instance 1");
+        let machine2 = store2.get_voting_machine().await.expect("err lors de la recuperation de la machine dans l'instance 2");
+
+        assert_eq!(machine1, voting_machine);
+        assert_eq!(machine2, voting_machine);
+    }
+
+    #[tokio::test]
+    async fn test_database_refuses_second_vote() {
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]);
+
+        let filepath = temp_filepath("c1_test_sqlite_unique.sqlite3").await;
+        let store: SqliteStore = SqliteStore::create(voting_machine.with_phase(Phase::Open), &filepath).await.expect("Erreur lors de la creation de la memoire");
+        vote(&store, "Tux", "Louis", Casting::default()).await;
+
+        assert_eq!(count(&filepath, "SELECT COUNT(*) FROM ballots"), 1);
+        let connection = Connection::open(&filepath).expect("Erreur lors de l'ouverture");
+        assert!(connection.execute("INSERT INTO voters (name, head) VALUES ('Tux', '')", []).is_err());
+        assert!(connection.execute("INSERT INTO voters (name, revision, head) VALUES ('Tux', 1, '')", []).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_database_refuses_second_vote_admitted_by_machine() {
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Open);
+
+        let filepath = temp_filepath("c1_test_sqlite_unique_app.sqlite3").await;
+        let store: SqliteStore = SqliteStore::create(voting_machine.clone(), &filepath).await.expect("Erreur lors de la creation de la memoire");
+        vote(&store, "Tux", "Louis", Casting::default()).await;
+        let live = store.get_voting_machine().await.expect("err lors de la recuperation de la machine");
+
+        // La machine en mémoire a oublié le vote : elle admet le second, la base le refuse.
+        store.ledger.write().await.machine = voting_machine.clone();
+        let ballot_paper = BallotPaper{ voter: Voter(String::from("Tux")), candidate: Some(Candidate(String::from("Louis"))) };
+        let (deposit, _) = voting_machine.prepare_vote(ballot_paper, &Casting::default()).expect("vote refuse");
+        assert!(store.record_ballot(deposit, &Schedule::default(), Utc::now()).await.is_err());
+
+        assert_eq!(count(&filepath, "SELECT COUNT(*) FROM voters"), 1);
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), live);
+    }
+
+    #[tokio::test]
+    async fn test_vote_leaves_snapshot_untouched() {
+        let voting_machine :  VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]).with_phase(Phase::Open);
+
+        let filepath = temp_filepath("c1_test_sqlite_rows.sqlite3").await;
+        let store: SqliteStore = SqliteStore::create(voting_machine.clone(), &filepath).await.expect("Erreur lors de la creation de la memoire");
+        let state = |filepath: &str| -> String {
+            Connection::open(filepath).expect("Erreur lors de l'ouverture").query_row("SELECT state FROM machine", [], |row| row.get(0)).expect("Erreur lors de la requete")
+        };
+        let before = state(&filepath);
+
+        vote(&store, "Tux", "Louis", Casting::default()).await;
+        vote(&store, "Gnu", "Louis", Casting::default()).await;
+
+        assert_eq!(state(&filepath), before);
+        assert_eq!(count(&filepath, "SELECT COUNT(*) FROM voters"), 2);
+
+        let live = store.get_voting_machine().await.expect("err lors de la recuperation de la machine");
+        let store = SqliteStore::open(voting_machine, &filepath).await.expect("Erreur lors de la reprise");
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), live);
+    }
+
+    #[tokio::test]
+    async fn test_secret_ballots_are_not_numbered() {
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let voting_machine :  VotingMachine = VotingMachine::new(candidates).with_phase(Phase::Open).with_secret(true);
+
+        for snapshot_every in [SNAPSHOT_EVERY, 4] {
+            let filepath = temp_filepath("c1_test_sqlite_secret.sqlite3").await;
+            let store: SqliteStore = SqliteStore::create(voting_machine.clone(), &filepath).await.expect("Erreur lors de la creation de la memoire");
+            store.ledger.write().await.snapshot_every = snapshot_every;
+
+            for index in 0..12 {
+                let candidate = if index % 3 == 0 { "Louise" } else { "Louis" };
+                vote(&store, &format!("Votant {}", index), candidate, Casting::default()).await;
+            }
+
+            assert_eq!(count(&filepath, "SELECT COUNT(*) FROM ballots WHERE sequence IS NOT NULL"), 0);
+            assert_eq!(count(&filepath, "SELECT COUNT(*) FROM ballots WHERE batch IS NULL"), 2);
+
+            let live = store.get_voting_machine().await.expect("err lors de la recuperation de la machine");
+            let reopened = SqliteStore::open(voting_machine.clone(), &filepath).await.expect("Erreur lors de la reprise");
+            assert_eq!(reopened.get_voting_machine().await.expect("err lors de la recuperation de la machine"), live);
+
+            // La clôture chaîne le dernier lot : la reprise ne doit pas le remettre en attente.
+            store.update(|machine| Ok(machine.change_phase(Phase::Closed))).await.expect("Erreur lors de la cloture");
+            let live = store.get_voting_machine().await.expect("err lors de la recuperation de la machine");
+            let reopened = SqliteStore::open(voting_machine.clone(), &filepath).await.expect("Erreur lors de la reprise");
+            assert_eq!(reopened.get_voting_machine().await.expect("err lors de la recuperation de la machine"), live);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_revote_and_withdrawal_are_replayed() {
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let voting_machine :  VotingMachine = VotingMachine::new(candidates).with_phase(Phase::Open).with_revote(true);
+        let code = TrackingCode(String::from("ABCD-EFGH-JKLM"));
+
+        let filepath = temp_filepath("c1_test_sqlite_revote.sqlite3").await;
+        let store: SqliteStore = SqliteStore::create(voting_machine.clone(), &filepath).await.expect("Erreur lors de la creation de la memoire");
+        vote(&store, "Tux", "Louis", Casting::default()).await;
+        vote(&store, "Tux", "Louise", Casting { proxy: None, receipt: Some(code.clone()) }).await;
+        vote(&store, "Gnu", "Louis", Casting::default()).await;
+
+        let outcome = store.revoke(&Voter(String::from("Tux")), &code.0).await.expect("Erreur lors du retrait");
+        assert_eq!(outcome, VoteOutcome::Revoked(Voter(String::from("Tux"))));
+        assert_eq!(count(&filepath, "SELECT COUNT(*) FROM withdrawals"), 1);
+
+        let live = store.get_voting_machine().await.expect("err lors de la recuperation de la machine");
+        let store = SqliteStore::open(voting_machine, &filepath).await.expect("Erreur lors de la reprise");
+        assert_eq!(store.get_voting_machine().await.expect("err lors de la recuperation de la machine"), live);
+        assert_eq!(live.get_ballots().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_live_ballots_match_scores() {
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let voting_machine :  VotingMachine = VotingMachine::new(candidates).with_phase(Phase::Open).with_revote(true);
+        let code = TrackingCode(String::from("ABCD-EFGH-JKLM"));
+
+        let filepath = temp_filepath("c1_test_sqlite_live.sqlite3").await;
+        let store: SqliteStore = SqliteStore::create(voting_machine, &filepath).await.expect("Erreur lors de la creation de la memoire");
+        vote(&store, "Tux", "Louis", Casting::default()).await;
+        vote(&store, "Tux", "Louise", Casting { proxy: None, receipt: Some(code.clone()) }).await;
+        vote(&store, "Gnu", "Louis", Casting::default()).await;
+        vote(&store, "Gnu", "Louise", Casting::default()).await;
+        vote(&store, "Ada", "Louis", Casting::default()).await;
+        let ballot_paper = BallotPaper{ voter: Voter(String::from("Bob")), candidate: None };
+        let (deposit, _) = store.read(|voting_machine| voting_machine.prepare_vote(ballot_paper, &Casting::default())).await.expect("err lors de la lecture").expect("vote refuse");
+        assert!(store.record_ballot(deposit, &Schedule::default(), Utc::now()).await.expect("Erreur lors de l'insertion du bulletin"));
+        store.revoke(&Voter(String::from("Tux")), &code.0).await.expect("Erreur lors du retrait");
+
+        let scoreboard = store.scores().await.expect("err lors du decompte");
+        let connection = Connection::open(&filepath).expect("Erreur lors de l'ouverture");
+        let mut statement = connection
+            .prepare(
+                "SELECT candidate, SUM(weight * COALESCE(grade, 1)) FROM choices JOIN ballots ON ballots.id = choices.ballot
+                 WHERE withdrawn_at IS NULL AND (kind <> 'Ranked' OR position = 0) GROUP BY candidate",
+            )
+            .expect("Erreur lors de la requete");
+        let counted: Map<Candidate, Score> = statement
+            .query_map([], |row| Ok((Candidate(row.get(0)?), Score(row.get(1)?))))
+            .expect("Erreur lors de la requete")
+            .collect::<Result<_, _>>()
+            .expect("Erreur lors de la lecture");
+        let scored: Map<Candidate, Score> = scoreboard.scores.into_iter().filter(|(_, score)| score.0 > 0).collect();
+
+        assert_eq!(counted, scored);
+        assert_eq!(count(&filepath, "SELECT COALESCE(SUM(weight), 0) FROM ballots WHERE withdrawn_at IS NULL AND kind = 'Blank'"), scoreboard.blank_score.0);
+        assert_eq!(count(&filepath, "SELECT COUNT(*) FROM ballots WHERE withdrawn_at IS NULL"), store.read(|machine| machine.get_ballots().len()).await.expect("err lors de la lecture"));
+        assert_eq!(count(&filepath, "SELECT COUNT(*) FROM ballots WHERE withdrawn_at IS NOT NULL"), 3);
+    }
+}
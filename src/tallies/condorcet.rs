@@ -0,0 +1,194 @@
+use std::collections::BTreeMap as Map;
+
+use serde::Serialize;
+
+use crate::domain::{Ballot, Candidate, Score, WeightedBallot};
+
+pub type PairwiseMatrix = Map<Candidate, Map<Candidate, Score>>;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Condorcet {
+    pub pairwise: PairwiseMatrix,
+    pub condorcet_winner: Option<Candidate>,
+    pub strongest_paths: PairwiseMatrix,
+    pub schulze_winners: Vec<Candidate>,
+}
+
+fn empty_matrix(candidates: &[Candidate]) -> PairwiseMatrix {
+    candidates
+        .iter()
+        .map(|a| {
+            let row = candidates
+                .iter()
+                .filter(|b| *b != a)
+                .map(|b| (b.clone(), Score(0)))
+                .collect();
+            (a.clone(), row)
+        })
+        .collect()
+}
+
+fn get(matrix: &PairwiseMatrix, a: &Candidate, b: &Candidate) -> usize {
+    matrix[a][b].0
+}
+
+/// Un candidat classé est préféré à tout candidat classé après lui
+/// ainsi qu'à tous les candidats absents du bulletin, pour le poids de son électeur.
+pub fn pairwise_matrix(candidates: &[Candidate], ballots: &[WeightedBallot]) -> PairwiseMatrix {
+    let mut matrix = empty_matrix(candidates);
+
+    for stored in ballots {
+        let Ballot::Ranked(ranking) = &stored.ballot else { continue };
+
+        for (position, preferred) in ranking.iter().enumerate() {
+            let Some(row) = matrix.get_mut(preferred) else { continue };
+            for candidate in candidates {
+                if candidate != preferred && !ranking[..position].contains(candidate) {
+                    if let Some(score) = row.get_mut(candidate) {
+                        *score = Score(score.0 + stored.weight.0);
+                    }
+                }
+            }
+        }
+    }
+
+    matrix
+}
+
+fn condorcet_winner(candidates: &[Candidate], matrix: &PairwiseMatrix) -> Option<Candidate> {
+    candidates
+        .iter()
+        .find(|a| {
+            candidates
+                .iter()
+                .filter(|b| b != a)
+                .all(|b| get(matrix, a, b) > get(matrix, b, a))
+        })
+        .cloned()
+}
+
+/// Chemins les plus forts de la méthode de Schulze (variante Floyd-Warshall).
+fn strongest_paths(candidates: &[Candidate], matrix: &PairwiseMatrix) -> PairwiseMatrix {
+    let mut paths = empty_matrix(candidates);
+
+    for a in candidates {
+        for b in candidates.iter().filter(|b| *b != a) {
+            if get(matrix, a, b) > get(matrix, b, a) {
+                paths.get_mut(a).unwrap().insert(b.clone(), Score(get(matrix, a, b)));
+            }
+        }
+    }
+
+    for k in candidates {
+        for a in candidates.iter().filter(|a| *a != k) {
+            for b in candidates.iter().filter(|b| *b != k && *b != a) {
+                let through_k = get(&paths, a, k).min(get(&paths, k, b));
+                if through_k > get(&paths, a, b) {
+                    paths.get_mut(a).unwrap().insert(b.clone(), Score(through_k));
+                }
+            }
+        }
+    }
+
+    paths
+}
+
+pub fn condorcet(candidates: &[Candidate], ballots: &[WeightedBallot]) -> Condorcet {
+    let pairwise = pairwise_matrix(candidates, ballots);
+    let condorcet_winner = condorcet_winner(candidates, &pairwise);
+    let strongest_paths = strongest_paths(candidates, &pairwise);
+
+    let schulze_winners = candidates
+        .iter()
+        .filter(|a| {
+            candidates
+                .iter()
+                .filter(|b| b != a)
+                .all(|b| get(&strongest_paths, a, b) >= get(&strongest_paths, b, a))
+        })
+        .cloned()
+        .collect();
+
+    Condorcet {
+        pairwise,
+        condorcet_winner,
+        strongest_paths,
+        schulze_winners,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::Weight;
+
+    use super::*;
+
+    fn candidate(name: &str) -> Candidate {
+        Candidate(String::from(name))
+    }
+
+    fn ranked(names: &[&str], times: usize) -> Vec<WeightedBallot> {
+        vec![WeightedBallot::from(Ballot::Ranked(names.iter().map(|name| candidate(name)).collect())); times]
+    }
+
+    #[test]
+    fn test_pairwise_matrix_counts_unranked_candidates_as_last() {
+        let candidates = vec![candidate("A"), candidate("B"), candidate("C")];
+        let ballots = ranked(&["A"], 1);
+
+        let matrix = pairwise_matrix(&candidates, &ballots);
+
+        assert_eq!(matrix[&candidate("A")][&candidate("B")], Score(1));
+        assert_eq!(matrix[&candidate("A")][&candidate("C")], Score(1));
+        assert_eq!(matrix[&candidate("B")][&candidate("C")], Score(0));
+        assert_eq!(matrix[&candidate("C")][&candidate("B")], Score(0));
+    }
+
+    #[test]
+    fn test_condorcet_winner() {
+        let candidates = vec![candidate("A"), candidate("B"), candidate("C")];
+        let mut ballots = ranked(&["A", "B", "C"], 2);
+        ballots.extend(ranked(&["B", "A", "C"], 1));
+        ballots.extend(ranked(&["C", "A", "B"], 1));
+
+        let result = condorcet(&candidates, &ballots);
+
+        assert_eq!(result.condorcet_winner, Some(candidate("A")));
+        assert_eq!(result.schulze_winners, vec![candidate("A")]);
+    }
+
+    #[test]
+    fn test_schulze_resolves_cycle() {
+        let candidates = vec![candidate("A"), candidate("B"), candidate("C")];
+        let mut ballots = ranked(&["A", "B", "C"], 4);
+        ballots.extend(ranked(&["B", "C", "A"], 3));
+        ballots.extend(ranked(&["C", "A", "B"], 2));
+
+        let result = condorcet(&candidates, &ballots);
+
+        assert_eq!(result.condorcet_winner, None);
+        assert_eq!(result.schulze_winners, vec![candidate("A")]);
+    }
+
+    #[test]
+    fn test_weights_count_in_duels() {
+        let candidates = vec![candidate("A"), candidate("B")];
+        let mut ballots = ranked(&["A", "B"], 2);
+        ballots.push(WeightedBallot { ballot: Ballot::Ranked(vec![candidate("B"), candidate("A")]), weight: Weight(3) });
+
+        let result = condorcet(&candidates, &ballots);
+
+        assert_eq!(result.pairwise[&candidate("B")][&candidate("A")], Score(3));
+        assert_eq!(result.condorcet_winner, Some(candidate("B")));
+    }
+
+    #[test]
+    fn test_result_is_serializable() {
+        let candidates = vec![candidate("A"), candidate("B")];
+        let ballots = ranked(&["A", "B"], 1);
+
+        let json = serde_json::to_string(&condorcet(&candidates, &ballots)).expect("serialisation impossible");
+
+        assert!(json.contains("\"condorcet_winner\":\"A\""));
+    }
+}
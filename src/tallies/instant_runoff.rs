@@ -0,0 +1,188 @@
+use std::collections::BTreeMap as Map;
+use std::collections::BTreeSet as Set;
+
+use serde::Serialize;
+
+use crate::domain::{Ballot, Candidate, Score, WeightedBallot};
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct RunoffRound {
+    pub counts: Map<Candidate, Score>,
+    pub exhausted: Score,
+    pub eliminated: Option<Candidate>,
+    pub transfers: Map<Candidate, Score>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct InstantRunoff {
+    pub rounds: Vec<RunoffRound>,
+    pub winner: Option<Candidate>,
+}
+
+fn top_choice<'a>(ranking: &'a [Candidate], continuing: &Set<Candidate>) -> Option<&'a Candidate> {
+    ranking.iter().find(|candidate| continuing.contains(*candidate))
+}
+
+/// Les égalités pour la dernière place sont départagées en remontant les tours
+/// précédents, puis par ordre alphabétique.
+fn pick_eliminated(mut losers: Vec<Candidate>, rounds: &[RunoffRound]) -> Candidate {
+    losers.sort_by(|a, b| {
+        rounds
+            .iter()
+            .rev()
+            .map(|round| round.counts[a].0.cmp(&round.counts[b].0))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.cmp(b))
+    });
+    losers.remove(0)
+}
+
+/// Chaque classement compte pour le poids de son électeur.
+pub fn instant_runoff(candidates: &[Candidate], ballots: &[WeightedBallot]) -> InstantRunoff {
+    let rankings: Vec<(&Vec<Candidate>, usize)> = ballots
+        .iter()
+        .filter_map(|stored| match &stored.ballot {
+            Ballot::Ranked(ranking) => Some((ranking, stored.weight.0)),
+            _ => None,
+        })
+        .collect();
+
+    let mut continuing: Set<Candidate> = candidates.iter().cloned().collect();
+    let mut rounds: Vec<RunoffRound> = vec![];
+
+    loop {
+        let mut counts: Map<Candidate, usize> = continuing.iter().map(|candidate| (candidate.clone(), 0)).collect();
+        let mut exhausted = 0;
+
+        for (ranking, weight) in &rankings {
+            match top_choice(ranking, &continuing) {
+                Some(candidate) => *counts.entry(candidate.clone()).or_insert(0) += weight,
+                None => exhausted += weight,
+            }
+        }
+
+        let active: usize = counts.values().sum();
+        let mut round = RunoffRound {
+            counts: counts.iter().map(|(candidate, count)| (candidate.clone(), Score(*count))).collect(),
+            exhausted: Score(exhausted),
+            eliminated: None,
+            transfers: Map::new(),
+        };
+
+        if let Some((leader, _)) = counts.iter().find(|(_, count)| **count * 2 > active) {
+            let winner = leader.clone();
+            rounds.push(round);
+            return InstantRunoff { rounds, winner: Some(winner) };
+        }
+
+        if continuing.len() <= 1 {
+            let winner = continuing.iter().next().cloned();
+            rounds.push(round);
+            return InstantRunoff { rounds, winner };
+        }
+
+        let lowest = counts.values().min().copied().unwrap_or(0);
+        let losers: Vec<Candidate> = counts
+            .iter()
+            .filter(|(_, count)| **count == lowest)
+            .map(|(candidate, _)| candidate.clone())
+            .collect();
+
+        if losers.len() == continuing.len() {
+            rounds.push(round);
+            return InstantRunoff { rounds, winner: None };
+        }
+
+        let eliminated = pick_eliminated(losers, &rounds);
+        continuing.remove(&eliminated);
+
+        for (ranking, weight) in &rankings {
+            let was_top = ranking.iter().find(|candidate| continuing.contains(*candidate) || **candidate == eliminated);
+            if was_top == Some(&eliminated) {
+                if let Some(next) = top_choice(ranking, &continuing) {
+                    let transfer = round.transfers.entry(next.clone()).or_insert(Score(0));
+                    *transfer = Score(transfer.0 + weight);
+                }
+            }
+        }
+
+        round.eliminated = Some(eliminated);
+        rounds.push(round);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::Weight;
+
+    use super::*;
+
+    fn candidate(name: &str) -> Candidate {
+        Candidate(String::from(name))
+    }
+
+    fn ranked(names: &[&str]) -> WeightedBallot {
+        WeightedBallot::from(Ballot::Ranked(names.iter().map(|name| candidate(name)).collect()))
+    }
+
+    #[test]
+    fn test_majority_in_first_round() {
+        let candidates = vec![candidate("Arch"), candidate("Debian")];
+        let ballots = vec![ranked(&["Arch"]), ranked(&["Arch", "Debian"]), ranked(&["Debian"])];
+
+        let result = instant_runoff(&candidates, &ballots);
+
+        assert_eq!(result.rounds.len(), 1);
+        assert_eq!(result.winner, Some(candidate("Arch")));
+    }
+
+    #[test]
+    fn test_transfers_after_elimination() {
+        let candidates = vec![candidate("Arch"), candidate("Debian"), candidate("Nixos")];
+        let ballots = vec![
+            ranked(&["Arch"]),
+            ranked(&["Arch"]),
+            ranked(&["Debian", "Arch"]),
+            ranked(&["Nixos", "Debian"]),
+            ranked(&["Nixos", "Debian"]),
+            WeightedBallot::from(Ballot::Blank),
+        ];
+
+        let result = instant_runoff(&candidates, &ballots);
+
+        assert_eq!(result.rounds.len(), 2);
+        assert_eq!(result.rounds[0].eliminated, Some(candidate("Debian")));
+        assert_eq!(result.rounds[0].transfers.get(&candidate("Arch")), Some(&Score(1)));
+        assert_eq!(result.rounds[1].counts[&candidate("Arch")], Score(3));
+        assert_eq!(result.winner, Some(candidate("Arch")));
+    }
+
+    #[test]
+    fn test_exhausted_ballots() {
+        let candidates = vec![candidate("Arch"), candidate("Debian"), candidate("Nixos")];
+        let ballots = vec![
+            ranked(&["Arch"]),
+            ranked(&["Arch"]),
+            ranked(&["Debian"]),
+            ranked(&["Nixos"]),
+            ranked(&["Nixos"]),
+        ];
+
+        let result = instant_runoff(&candidates, &ballots);
+
+        assert_eq!(result.rounds[1].exhausted, Score(1));
+        assert_eq!(result.winner, None);
+    }
+
+    #[test]
+    fn test_weights_decide_majority() {
+        let candidates = vec![candidate("Arch"), candidate("Debian")];
+        let heavy = WeightedBallot { ballot: ranked(&["Debian"]).ballot, weight: Weight(3) };
+        let ballots = vec![ranked(&["Arch"]), ranked(&["Arch"]), heavy];
+
+        let result = instant_runoff(&candidates, &ballots);
+
+        assert_eq!(result.rounds[0].counts[&candidate("Debian")], Score(3));
+        assert_eq!(result.winner, Some(candidate("Debian")));
+    }
+}
@@ -0,0 +1,195 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap as Map;
+
+use serde::Serialize;
+
+use crate::domain::{Ballot, Candidate, Grade, Points, Score, WeightedBallot, MAX_GRADE};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CandidateGrades {
+    /// Nombre de mentions reçues, de la plus basse (indice 0) à la plus haute.
+    pub distribution: Vec<Score>,
+    pub median: Option<Grade>,
+    pub average: Points,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MajorityJudgment {
+    pub grades: Map<Candidate, CandidateGrades>,
+    pub ranking: Vec<Candidate>,
+}
+
+/// Suite des médianes obtenues en retirant une à une la mention médiane, tirée des
+/// effectifs de chaque mention, par séries de mentions égales : la comparer
+/// lexicographiquement revient au départage usuel du jugement majoritaire.
+fn median_sequence(distribution: &[usize]) -> Vec<(Grade, usize)> {
+    let mut counts = distribution.to_vec();
+    let mut remaining: usize = counts.iter().sum();
+
+    let mut sequence = vec![];
+    while remaining > 0 {
+        // Médiane basse : la mention de rang (remaining - 1) / 2.
+        let rank = (remaining - 1) / 2;
+        let (mut grade, mut below) = (0, 0);
+        while below + counts[grade] <= rank {
+            below += counts[grade];
+            grade += 1;
+        }
+        let above = remaining - below - counts[grade];
+
+        // La mention reste médiane tant qu'il en reste au moins `least`.
+        let least = 1.max(above.saturating_sub(below)).max((below + 1).saturating_sub(above));
+        let taken = counts[grade] + 1 - least;
+        sequence.push((Grade(grade), taken));
+        counts[grade] -= taken;
+        remaining -= taken;
+    }
+    sequence
+}
+
+/// Compare deux suites de médianes comme si chaque série était dépliée.
+fn compare_sequences(a: &[(Grade, usize)], b: &[(Grade, usize)]) -> Ordering {
+    let mut a = a.iter().copied().peekable();
+    let mut b = b.iter().copied().peekable();
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some((grade_a, _)), Some((grade_b, _))) if grade_a != grade_b => return grade_a.cmp(&grade_b),
+            // Deux séries voisines n'ont jamais la même mention : la plus courte est
+            // suivie d'une autre mention, ou de la fin de sa suite.
+            (Some((grade, length_a)), Some((_, length_b))) => match length_a.cmp(&length_b) {
+                Ordering::Equal => {}
+                Ordering::Less => return a.peek().map_or(Ordering::Less, |(next, _)| next.cmp(&grade)),
+                Ordering::Greater => return b.peek().map_or(Ordering::Greater, |(next, _)| grade.cmp(next)),
+            },
+        }
+    }
+}
+
+/// Un candidat non noté sur un bulletin reçoit la mention la plus basse.
+/// Chaque mention compte autant que le poids de l'électeur.
+pub fn majority_judgment(candidates: &[Candidate], ballots: &[WeightedBallot]) -> MajorityJudgment {
+    let mut received: Map<Candidate, Vec<usize>> = candidates.iter().map(|candidate| (candidate.clone(), vec![0; MAX_GRADE + 1])).collect();
+
+    for stored in ballots {
+        let Ballot::Graded(graded) = &stored.ballot else { continue };
+        for (candidate, counts) in received.iter_mut() {
+            let grade = graded.get(candidate).copied().unwrap_or(Grade(0));
+            counts[grade.0] += stored.weight.0;
+        }
+    }
+
+    let sequences: Map<&Candidate, Vec<(Grade, usize)>> = received.iter().map(|(candidate, counts)| (candidate, median_sequence(counts))).collect();
+
+    let grades = received
+        .iter()
+        .map(|(candidate, counts)| {
+            let count: usize = counts.iter().sum();
+            let total: usize = counts.iter().enumerate().map(|(grade, received)| grade * received).sum();
+            let average = match count {
+                0 => 0.0,
+                count => total as f64 / count as f64,
+            };
+
+            (
+                candidate.clone(),
+                CandidateGrades {
+                    distribution: counts.iter().copied().map(Score).collect(),
+                    median: sequences[candidate].first().map(|(grade, _)| *grade),
+                    average: Points(average),
+                },
+            )
+        })
+        .collect();
+
+    let mut ranking: Vec<Candidate> = candidates.to_vec();
+    ranking.sort_by(|a, b| compare_sequences(&sequences[b], &sequences[a]).then_with(|| a.cmp(b)));
+
+    MajorityJudgment { grades, ranking }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::Weight;
+
+    use super::*;
+
+    fn candidate(name: &str) -> Candidate {
+        Candidate(String::from(name))
+    }
+
+    fn graded(grades: &[(&str, usize)]) -> WeightedBallot {
+        WeightedBallot::from(Ballot::Graded(grades.iter().map(|(name, grade)| (candidate(name), Grade(*grade))).collect()))
+    }
+
+    #[test]
+    fn test_median_and_average() {
+        let candidates = vec![candidate("A"), candidate("B")];
+        let ballots = vec![graded(&[("A", 5), ("B", 1)]), graded(&[("A", 3), ("B", 2)]), graded(&[("A", 1)])];
+
+        let result = majority_judgment(&candidates, &ballots);
+
+        assert_eq!(result.grades[&candidate("A")].median, Some(Grade(3)));
+        assert_eq!(result.grades[&candidate("A")].average, Points(3.0));
+        assert_eq!(result.grades[&candidate("B")].distribution[0], Score(1));
+        assert_eq!(result.ranking, vec![candidate("A"), candidate("B")]);
+    }
+
+    #[test]
+    fn test_tie_on_median_is_broken() {
+        let candidates = vec![candidate("A"), candidate("B")];
+        let ballots = vec![
+            graded(&[("A", 3), ("B", 3)]),
+            graded(&[("A", 3), ("B", 3)]),
+            graded(&[("A", 2), ("B", 4)]),
+        ];
+
+        let result = majority_judgment(&candidates, &ballots);
+
+        assert_eq!(result.grades[&candidate("A")].median, result.grades[&candidate("B")].median);
+        assert_eq!(result.ranking, vec![candidate("B"), candidate("A")]);
+    }
+
+    #[test]
+    fn test_weight_moves_median() {
+        let candidates = vec![candidate("A")];
+        let mut heavy = graded(&[("A", 1)]);
+        heavy.weight = Weight(3);
+        let ballots = vec![graded(&[("A", 5)]), graded(&[("A", 5)]), heavy];
+
+        let result = majority_judgment(&candidates, &ballots);
+
+        assert_eq!(result.grades[&candidate("A")].median, Some(Grade(1)));
+        assert_eq!(result.grades[&candidate("A")].distribution[1], Score(3));
+        assert_eq!(result.grades[&candidate("A")].average, Points(2.6));
+    }
+
+    /// Suite dépliée, mention par mention, calculée en retirant chaque fois la médiane.
+    fn unfolded(distribution: &[usize]) -> Vec<Grade> {
+        let mut sorted: Vec<Grade> = distribution.iter().enumerate().flat_map(|(grade, count)| std::iter::repeat_n(Grade(grade), *count)).collect();
+        let mut sequence = vec![];
+        while !sorted.is_empty() {
+            sequence.push(sorted.remove((sorted.len() - 1) / 2));
+        }
+        sequence
+    }
+
+    #[test]
+    fn test_median_sequence_from_counts() {
+        let distributions: Vec<Vec<usize>> = (0..200)
+            .map(|seed: usize| (0..=MAX_GRADE).map(|grade| (seed * 7 + grade * 13 + seed * grade) % 5).collect())
+            .collect();
+
+        for a in &distributions {
+            let runs = median_sequence(a);
+            let expanded: Vec<Grade> = runs.iter().flat_map(|(grade, length)| std::iter::repeat_n(*grade, *length)).collect();
+            assert_eq!(expanded, unfolded(a));
+
+            for b in distributions.iter().take(20) {
+                assert_eq!(compare_sequences(&runs, &median_sequence(b)), unfolded(a).cmp(&unfolded(b)));
+            }
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod instant_runoff;
+pub mod condorcet;
+pub mod positional;
+pub mod stv;
+pub mod majority_judgment;
@@ -0,0 +1,146 @@
+use std::collections::BTreeMap as Map;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use crate::domain::{Ballot, Candidate, Points, Score, Scoreboard, WeightedBallot};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PositionalRule {
+    /// n - 1 points pour le premier, n - 2 pour le second, etc.
+    Borda,
+    /// 1, 1/2, 1/3, ... points selon le rang.
+    Dowdall,
+    /// Barème libre, par exemple 3-2-1 ; les rangs au-delà ne rapportent rien.
+    Custom(Vec<f64>),
+}
+
+impl PositionalRule {
+    fn points(&self, position: usize, candidate_count: usize) -> f64 {
+        match self {
+            PositionalRule::Borda => candidate_count.saturating_sub(position + 1) as f64,
+            PositionalRule::Dowdall => 1.0 / (position + 1) as f64,
+            PositionalRule::Custom(points) => points.get(position).copied().unwrap_or(0.0),
+        }
+    }
+}
+
+impl FromStr for PositionalRule {
+    type Err = anyhow::Error;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        match rule.to_lowercase().as_str() {
+            "borda" => Ok(PositionalRule::Borda),
+            "dowdall" => Ok(PositionalRule::Dowdall),
+            custom => {
+                let points = custom
+                    .split([',', '-'])
+                    .map(|value| value.trim().parse::<f64>())
+                    .collect::<Result<Vec<f64>, _>>()
+                    .map_err(|_| anyhow!("barème positionnel invalide : {}", rule))?;
+                Ok(PositionalRule::Custom(points))
+            }
+        }
+    }
+}
+
+/// Les points et les bulletins blancs ou nuls comptent pour le poids de leur électeur.
+pub fn positional(candidates: &[Candidate], ballots: &[WeightedBallot], rule: &PositionalRule) -> Scoreboard<Points> {
+    let mut scores: Map<Candidate, Points> = candidates.iter().map(|candidate| (candidate.clone(), Points(0.0))).collect();
+    let mut blank_score = 0;
+    let mut invalid_score = 0;
+
+    for stored in ballots {
+        let weight = stored.weight.0;
+        match &stored.ballot {
+            Ballot::Ranked(ranking) => {
+                for (position, candidate) in ranking.iter().enumerate() {
+                    if let Some(points) = scores.get_mut(candidate) {
+                        points.0 += rule.points(position, candidates.len()) * weight as f64;
+                    }
+                }
+            }
+            Ballot::Blank => blank_score += weight,
+            Ballot::Invalid => invalid_score += weight,
+            Ballot::Approval(_) | Ballot::Graded(_) | Ballot::Encrypted(_) => {}
+        }
+    }
+
+    Scoreboard {
+        scores,
+        blank_score: Score(blank_score),
+        invalid_score: Score(invalid_score),
+        head_counts: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::Weight;
+
+    use super::*;
+
+    fn candidate(name: &str) -> Candidate {
+        Candidate(String::from(name))
+    }
+
+    fn ranked(names: &[&str]) -> WeightedBallot {
+        WeightedBallot::from(Ballot::Ranked(names.iter().map(|name| candidate(name)).collect()))
+    }
+
+    fn setup() -> (Vec<Candidate>, Vec<WeightedBallot>) {
+        let candidates = vec![candidate("A"), candidate("B"), candidate("C")];
+        let ballots = vec![ranked(&["A", "B", "C"]), ranked(&["B", "C"]), ranked(&["C"]), WeightedBallot::from(Ballot::Blank)];
+        (candidates, ballots)
+    }
+
+    #[test]
+    fn test_borda() {
+        let (candidates, ballots) = setup();
+
+        let scoreboard = positional(&candidates, &ballots, &PositionalRule::Borda);
+
+        assert_eq!(scoreboard.scores[&candidate("A")], Points(2.0));
+        assert_eq!(scoreboard.scores[&candidate("B")], Points(3.0));
+        assert_eq!(scoreboard.scores[&candidate("C")], Points(3.0));
+        assert_eq!(scoreboard.blank_score, Score(1));
+    }
+
+    #[test]
+    fn test_dowdall_gives_fractional_points() {
+        let (candidates, ballots) = setup();
+
+        let scoreboard = positional(&candidates, &ballots, &PositionalRule::Dowdall);
+
+        assert_eq!(scoreboard.scores[&candidate("A")], Points(1.0));
+        assert_eq!(scoreboard.scores[&candidate("B")], Points(1.5));
+        assert!((scoreboard.scores[&candidate("C")].0 - (1.0 / 3.0 + 0.5 + 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        let (candidates, ballots) = setup();
+        let rule: PositionalRule = "3-2-1".parse().expect("barème invalide");
+
+        let scoreboard = positional(&candidates, &ballots, &rule);
+
+        assert_eq!(rule, PositionalRule::Custom(vec![3.0, 2.0, 1.0]));
+        assert_eq!(scoreboard.scores[&candidate("C")], Points(6.0));
+    }
+
+    #[test]
+    fn test_weighted_borda() {
+        let (candidates, mut ballots) = setup();
+        ballots[0].weight = Weight(3);
+
+        let scoreboard = positional(&candidates, &ballots, &PositionalRule::Borda);
+
+        assert_eq!(scoreboard.scores[&candidate("A")], Points(6.0));
+        assert_eq!(scoreboard.scores[&candidate("B")], Points(5.0));
+    }
+
+    #[test]
+    fn test_invalid_rule() {
+        assert!("abc".parse::<PositionalRule>().is_err());
+    }
+}
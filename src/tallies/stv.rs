@@ -0,0 +1,344 @@
+use std::collections::BTreeMap as Map;
+use std::collections::BTreeSet as Set;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use serde::Serialize;
+
+use crate::domain::{Ballot, Candidate, Points, WeightedBallot};
+
+const EPSILON: f64 = 1e-9;
+const MEEK_TOLERANCE: f64 = 1e-6;
+const MEEK_MAX_ITERATIONS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SurplusTransfer {
+    /// Les bulletins de l'élu sont reportés avec un poids réduit à surplus / total.
+    #[default]
+    Gregory,
+    /// Chaque élu conserve une fraction de chaque bulletin, recalculée jusqu'à convergence.
+    Meek,
+}
+
+impl FromStr for SurplusTransfer {
+    type Err = anyhow::Error;
+
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        match method.to_lowercase().as_str() {
+            "gregory" => Ok(SurplusTransfer::Gregory),
+            "meek" => Ok(SurplusTransfer::Meek),
+            _ => Err(anyhow!("méthode de report inconnue : {}", method)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StvRound {
+    pub counts: Map<Candidate, Points>,
+    pub quota: Points,
+    pub exhausted: Points,
+    pub elected: Vec<Candidate>,
+    pub eliminated: Option<Candidate>,
+    pub transfers: Map<Candidate, Points>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stv {
+    pub seats: usize,
+    pub rounds: Vec<StvRound>,
+    pub elected: Vec<Candidate>,
+}
+
+/// Classements avec le poids de leur électeur, valeur initiale de chaque bulletin.
+fn rankings(ballots: &[WeightedBallot]) -> Vec<(&Vec<Candidate>, f64)> {
+    ballots
+        .iter()
+        .filter_map(|stored| match &stored.ballot {
+            Ballot::Ranked(ranking) => Some((ranking, stored.weight.0 as f64)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn by_count_desc(counts: &Map<Candidate, f64>, mut candidates: Vec<Candidate>) -> Vec<Candidate> {
+    candidates.sort_by(|a, b| counts[b].total_cmp(&counts[a]).then_with(|| a.cmp(b)));
+    candidates
+}
+
+/// En cas d'égalité pour la dernière place, le premier par ordre alphabétique est éliminé.
+fn lowest(counts: &Map<Candidate, f64>, hopeful: &Set<Candidate>) -> Option<Candidate> {
+    hopeful
+        .iter()
+        .min_by(|a, b| counts[*a].total_cmp(&counts[*b]).then_with(|| a.cmp(b)))
+        .cloned()
+}
+
+fn to_points(counts: &Map<Candidate, f64>) -> Map<Candidate, Points> {
+    counts.iter().map(|(candidate, count)| (candidate.clone(), Points(*count))).collect()
+}
+
+/// Les reports d'un tour sont les voix gagnées par chaque candidat au tour suivant.
+fn fill_transfers(rounds: &mut [StvRound]) {
+    for index in 1..rounds.len() {
+        let (previous, next) = rounds.split_at_mut(index);
+        let previous = &mut previous[index - 1];
+        for (candidate, count) in &next[0].counts {
+            let before = previous.counts.get(candidate).map(|points| points.0).unwrap_or(0.0);
+            if count.0 - before > EPSILON {
+                previous.transfers.insert(candidate.clone(), Points(count.0 - before));
+            }
+        }
+    }
+}
+
+fn gregory(candidates: &[Candidate], ballots: &[WeightedBallot], seats: usize) -> Stv {
+    let (rankings, mut weights): (Vec<&Vec<Candidate>>, Vec<f64>) = rankings(ballots).into_iter().unzip();
+    let total: f64 = weights.iter().sum();
+    let quota = ((total as usize) / (seats + 1) + 1) as f64;
+
+    let mut hopeful: Set<Candidate> = candidates.iter().cloned().collect();
+    let mut elected: Vec<Candidate> = vec![];
+    let mut rounds: Vec<StvRound> = vec![];
+
+    while elected.len() < seats && !hopeful.is_empty() {
+        let mut counts: Map<Candidate, f64> = hopeful.iter().map(|candidate| (candidate.clone(), 0.0)).collect();
+        let mut exhausted = 0.0;
+        let mut holders: Vec<Option<Candidate>> = vec![];
+
+        for (ranking, weight) in rankings.iter().zip(&weights) {
+            let holder = ranking.iter().find(|candidate| hopeful.contains(*candidate)).cloned();
+            match &holder {
+                Some(candidate) => *counts.get_mut(candidate).unwrap() += weight,
+                None => exhausted += weight,
+            }
+            holders.push(holder);
+        }
+
+        let mut round = StvRound {
+            counts: to_points(&counts),
+            quota: Points(quota),
+            exhausted: Points(exhausted),
+            elected: vec![],
+            eliminated: None,
+            transfers: Map::new(),
+        };
+
+        if elected.len() + hopeful.len() <= seats {
+            round.elected = by_count_desc(&counts, hopeful.iter().cloned().collect());
+        } else {
+            let reached: Vec<Candidate> = hopeful
+                .iter()
+                .filter(|candidate| counts[*candidate] + EPSILON >= quota)
+                .cloned()
+                .collect();
+            round.elected = by_count_desc(&counts, reached);
+            round.elected.truncate(seats - elected.len());
+
+            for candidate in &round.elected {
+                let total = counts[candidate];
+                let factor = (total - quota).max(0.0) / total;
+                for (weight, holder) in weights.iter_mut().zip(&holders) {
+                    if holder.as_ref() == Some(candidate) {
+                        *weight *= factor;
+                    }
+                }
+            }
+
+            if round.elected.is_empty() {
+                round.eliminated = lowest(&counts, &hopeful);
+            }
+        }
+
+        for candidate in round.elected.iter().chain(round.eliminated.iter()) {
+            hopeful.remove(candidate);
+        }
+        elected.extend(round.elected.iter().cloned());
+        rounds.push(round);
+    }
+
+    fill_transfers(&mut rounds);
+    Stv { seats, rounds, elected }
+}
+
+fn meek_count(
+    rankings: &[(&Vec<Candidate>, f64)],
+    keep: &Map<Candidate, f64>,
+) -> (Map<Candidate, f64>, f64) {
+    let mut counts: Map<Candidate, f64> = keep.keys().map(|candidate| (candidate.clone(), 0.0)).collect();
+    let mut exhausted = 0.0;
+
+    for (ranking, value) in rankings {
+        let mut weight = *value;
+        for candidate in ranking.iter() {
+            let Some(factor) = keep.get(candidate) else { continue };
+            *counts.get_mut(candidate).unwrap() += weight * factor;
+            weight *= 1.0 - factor;
+            if weight < EPSILON {
+                break;
+            }
+        }
+        exhausted += weight;
+    }
+
+    (counts, exhausted)
+}
+
+fn meek(candidates: &[Candidate], ballots: &[WeightedBallot], seats: usize) -> Stv {
+    let rankings = rankings(ballots);
+    let total: f64 = rankings.iter().map(|(_, weight)| weight).sum();
+
+    let mut keep: Map<Candidate, f64> = candidates.iter().map(|candidate| (candidate.clone(), 1.0)).collect();
+    let mut hopeful: Set<Candidate> = candidates.iter().cloned().collect();
+    let mut elected: Vec<Candidate> = vec![];
+    let mut rounds: Vec<StvRound> = vec![];
+
+    while elected.len() < seats && !hopeful.is_empty() {
+        let (mut counts, mut exhausted) = meek_count(&rankings, &keep);
+        let mut quota = (total - exhausted) / (seats + 1) as f64;
+
+        for _ in 0..MEEK_MAX_ITERATIONS {
+            let converged = elected
+                .iter()
+                .all(|candidate| (counts[candidate] - quota).abs() < MEEK_TOLERANCE);
+            if converged {
+                break;
+            }
+            for candidate in &elected {
+                if counts[candidate] > EPSILON {
+                    let factor = keep[candidate] * quota / counts[candidate];
+                    keep.insert(candidate.clone(), factor.min(1.0));
+                }
+            }
+            (counts, exhausted) = meek_count(&rankings, &keep);
+            quota = (total - exhausted) / (seats + 1) as f64;
+        }
+
+        let mut round = StvRound {
+            counts: to_points(&counts),
+            quota: Points(quota),
+            exhausted: Points(exhausted),
+            elected: vec![],
+            eliminated: None,
+            transfers: Map::new(),
+        };
+
+        if elected.len() + hopeful.len() <= seats {
+            round.elected = by_count_desc(&counts, hopeful.iter().cloned().collect());
+        } else {
+            let reached: Vec<Candidate> = hopeful
+                .iter()
+                .filter(|candidate| counts[*candidate] > quota + EPSILON)
+                .cloned()
+                .collect();
+            round.elected = by_count_desc(&counts, reached);
+            round.elected.truncate(seats - elected.len());
+
+            if round.elected.is_empty() {
+                round.eliminated = lowest(&counts, &hopeful);
+            }
+        }
+
+        for candidate in &round.elected {
+            hopeful.remove(candidate);
+        }
+        if let Some(candidate) = &round.eliminated {
+            hopeful.remove(candidate);
+            keep.remove(candidate);
+        }
+        elected.extend(round.elected.iter().cloned());
+        rounds.push(round);
+    }
+
+    fill_transfers(&mut rounds);
+    Stv { seats, rounds, elected }
+}
+
+/// Vote unique transférable avec quota de Droop, calculé sur le poids des bulletins.
+pub fn stv(candidates: &[Candidate], ballots: &[WeightedBallot], seats: usize, transfer: SurplusTransfer) -> Stv {
+    match transfer {
+        SurplusTransfer::Gregory => gregory(candidates, ballots, seats),
+        SurplusTransfer::Meek => meek(candidates, ballots, seats),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::Weight;
+
+    use super::*;
+
+    fn candidate(name: &str) -> Candidate {
+        Candidate(String::from(name))
+    }
+
+    fn ranked(names: &[&str], times: usize) -> Vec<WeightedBallot> {
+        vec![WeightedBallot::from(Ballot::Ranked(names.iter().map(|name| candidate(name)).collect())); times]
+    }
+
+    fn setup() -> (Vec<Candidate>, Vec<WeightedBallot>) {
+        let candidates = vec![candidate("A"), candidate("B"), candidate("C"), candidate("D")];
+        let mut ballots = ranked(&["A", "B"], 6);
+        ballots.extend(ranked(&["C"], 3));
+        ballots.extend(ranked(&["D", "C"], 1));
+        ballots.extend(ranked(&["B"], 1));
+        (candidates, ballots)
+    }
+
+    #[test]
+    fn test_gregory_transfers_surplus() {
+        let (candidates, ballots) = setup();
+
+        let result = stv(&candidates, &ballots, 2, SurplusTransfer::Gregory);
+
+        assert_eq!(result.rounds[0].quota, Points(4.0));
+        assert_eq!(result.rounds[0].elected, vec![candidate("A")]);
+        assert!((result.rounds[0].transfers[&candidate("B")].0 - 2.0).abs() < 1e-6);
+        assert_eq!(result.elected.len(), 2);
+        assert_eq!(result.elected[0], candidate("A"));
+    }
+
+    #[test]
+    fn test_gregory_elects_after_eliminations() {
+        let (candidates, ballots) = setup();
+
+        let result = stv(&candidates, &ballots, 2, SurplusTransfer::Gregory);
+
+        assert_eq!(result.elected, vec![candidate("A"), candidate("C")]);
+        assert!(result.rounds.iter().any(|round| round.eliminated == Some(candidate("D"))));
+    }
+
+    #[test]
+    fn test_meek_elects_same_seats() {
+        let (candidates, ballots) = setup();
+
+        let result = stv(&candidates, &ballots, 2, SurplusTransfer::Meek);
+
+        assert_eq!(result.elected, vec![candidate("A"), candidate("C")]);
+    }
+
+    #[test]
+    fn test_single_seat_behaves_like_majority() {
+        let candidates = vec![candidate("A"), candidate("B")];
+        let mut ballots = ranked(&["A"], 3);
+        ballots.extend(ranked(&["B"], 2));
+
+        let result = stv(&candidates, &ballots, 1, SurplusTransfer::Gregory);
+
+        assert_eq!(result.elected, vec![candidate("A")]);
+    }
+
+    #[test]
+    fn test_weights_count_toward_quota() {
+        let candidates = vec![candidate("A"), candidate("B")];
+        let mut ballots = ranked(&["A"], 3);
+        ballots.push(WeightedBallot { ballot: Ballot::Ranked(vec![candidate("B")]), weight: Weight(4) });
+
+        for transfer in [SurplusTransfer::Gregory, SurplusTransfer::Meek] {
+            let result = stv(&candidates, &ballots, 1, transfer);
+
+            assert_eq!(result.rounds[0].counts[&candidate("B")], Points(4.0));
+            assert_eq!(result.elected, vec![candidate("B")]);
+        }
+        assert_eq!(stv(&candidates, &ballots, 1, SurplusTransfer::Gregory).rounds[0].quota, Points(4.0));
+    }
+}
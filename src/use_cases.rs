@@ -1,14 +1,18 @@
 
 use std::sync::Arc;
 
+use chrono::Utc;
 use serde::Deserialize;
-use tokio::sync::RwLock;
+use sha2::{Digest, Sha256};
 
-use crate::{domain::{BallotPaper, Candidate, VoteOutcome, Voter, VotingMachine}, storage::Storage};
+use crate::{configuration::VotingMethod, encryption::ElectionKey, proxies::ProxyRejection, results::{ResultsDocument, ResultsPublisher}, domain::{ApprovalBallotPaper, BallotPaper, Candidate, Casting, Deadline, Grade, GradedBallotPaper, Phase, PhaseChange, Prepared, RankedBallotPaper, RoundOutcome, Schedule, Scoreboard, TrackingCode, VoteOutcome, Voter, VotingMachine}, storage::Storage, voting_codes::random_code};
 
 #[derive(Deserialize, Clone)]
 pub struct VoteForm {
     pub voter : String,
+    /// Mandant au nom duquel `voter` vote par procuration.
+    #[serde(default)]
+    pub on_behalf_of: Option<String>,
     pub candidate: String,
 }
 
@@ -20,37 +24,273 @@ impl From<VoteForm> for BallotPaper{
         };
         
         Self{
-            voter: Voter(vote_form.voter),
-            candidate : candidate
+            voter: Voter(vote_form.on_behalf_of.unwrap_or(vote_form.voter)),
+            candidate
         }
     }
 
 }
 
+#[derive(Deserialize, Clone)]
+pub struct RankedVoteForm {
+    pub voter : String,
+    #[serde(default)]
+    pub on_behalf_of: Option<String>,
+    pub candidates: Vec<String>,
+}
+
+impl From<RankedVoteForm> for RankedBallotPaper{
+    fn from(vote_form: RankedVoteForm) -> Self {
+        Self{
+            voter: Voter(vote_form.on_behalf_of.unwrap_or(vote_form.voter)),
+            candidates: vote_form.candidates.into_iter().map(Candidate).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ApprovalVoteForm {
+    pub voter : String,
+    #[serde(default)]
+    pub on_behalf_of: Option<String>,
+    pub candidates: Vec<String>,
+}
+
+impl From<ApprovalVoteForm> for ApprovalBallotPaper{
+    fn from(vote_form: ApprovalVoteForm) -> Self {
+        Self{
+            voter: Voter(vote_form.on_behalf_of.unwrap_or(vote_form.voter)),
+            candidates: vote_form.candidates.into_iter().map(Candidate).collect(),
+        }
+    }
+}
+
+/// Mentions saisies sous la forme (candidat, note), la note restant à interpréter.
+#[derive(Deserialize, Clone)]
+pub struct GradedVoteForm {
+    pub voter : String,
+    #[serde(default)]
+    pub on_behalf_of: Option<String>,
+    pub grades: Vec<(String, String)>,
+}
+
+impl From<GradedVoteForm> for GradedBallotPaper{
+    fn from(vote_form: GradedVoteForm) -> Self {
+        Self{
+            voter: Voter(vote_form.on_behalf_of.unwrap_or(vote_form.voter)),
+            grades: vote_form
+                .grades
+                .into_iter()
+                .map(|(candidate, grade)| (Candidate(candidate), grade.parse().ok().map(Grade)))
+                .collect(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct VotingController<Store>{
-    store: Arc<RwLock<Store>>,
+    store: Arc<Store>,
+    method: VotingMethod,
+    schedule: Schedule,
+    publisher: Option<ResultsPublisher>,
+    admin_token: Option<String>,
 }
 impl<Store: Storage> VotingController<Store> {
     pub fn new(store: Store) -> Self {
-        Self { store: Arc::new(RwLock::new(store)) }
+        Self { store: Arc::new(store), method: VotingMethod::default(), schedule: Schedule::default(), publisher: None, admin_token: None }
+    }
+
+    pub fn with_method(mut self, method: VotingMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    pub fn with_publisher(mut self, publisher: ResultsPublisher) -> Self {
+        self.publisher = Some(publisher);
+        self
+    }
+
+    pub fn with_admin_token(mut self, token: &str) -> Self {
+        self.admin_token = Some(token.to_string());
+        self
+    }
+
+    pub fn has_admin_token(&self) -> bool {
+        self.admin_token.is_some()
+    }
+
+    /// Sans jeton configuré, tout le monde est administrateur, comme en local.
+    /// Les empreintes sont comparées pour ne pas révéler le jeton par le temps de réponse.
+    pub fn is_admin(&self, token: Option<&str>) -> bool {
+        match (&self.admin_token, token) {
+            (None, _) => true,
+            (Some(expected), Some(token)) => Sha256::digest(expected.as_bytes()) == Sha256::digest(token.as_bytes()),
+            (Some(_), None) => false,
+        }
+    }
+
+    pub fn get_publisher(&self) -> Option<&ResultsPublisher> {
+        self.publisher.as_ref()
+    }
+
+    pub fn get_method(&self) -> VotingMethod {
+        self.method
+    }
+
+    pub fn get_schedule(&self) -> Schedule {
+        self.schedule
+    }
+
+    /// Applique le calendrier puis l'action, en une seule opération du stockage.
+    /// Les résultats signés sont publiés dès qu'ils deviennent consultables : à la
+    /// clôture, ou au déchiffrement pour un scrutin chiffré.
+    async fn update<T: Send>(&self, action: impl FnOnce(&mut VotingMachine) -> T + Send) -> anyhow::Result<T> {
+        let now = Utc::now();
+        let schedule = self.schedule;
+
+        let (result, document) = self.store.update(move |voting_machine| {
+            let was_visible = voting_machine.results_visible();
+
+            voting_machine.follow_schedule(&schedule, now);
+            let result = action(voting_machine);
+
+            if was_visible || !voting_machine.results_visible() {
+                return Ok((result, None));
+            }
+
+            Ok((result, Some(ResultsDocument::new(voting_machine, &schedule, now))))
+        }).await?;
+
+        if let (Some(document), Some(publisher)) = (document, &self.publisher) {
+            publisher.publish(&document).await?;
+        }
+
+        Ok(result)
+    }
+
+    /// Applique le calendrier si une échéance est passée, sans rien enregistrer sinon.
+    async fn catch_up(&self) -> anyhow::Result<()> {
+        let schedule = self.schedule;
+        if self.store.read(|voting_machine| voting_machine.is_behind(&schedule, Utc::now())).await? {
+            self.update(|_| ()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Dépose le bulletin et remet un code de suivi. Pour un vote par procuration,
+    /// le bulletin est au nom du mandant et `voter` est le mandataire.
+    /// Le dépôt est préparé sans bloquer les autres votes, chiffrement compris, puis
+    /// seul ce dépôt est enregistré.
+    async fn cast<Paper: Clone + Send + Sync>(
+        &self,
+        voter: String,
+        on_behalf_of: Option<String>,
+        ballot_paper: Paper,
+        prepare: fn(&VotingMachine, Paper, &Casting) -> Prepared,
+    ) -> anyhow::Result<VoteOutcome> {
+        let code = TrackingCode(random_code());
+        let casting = Casting { proxy: on_behalf_of.map(|_| Voter(voter)), receipt: Some(code.clone()) };
+
+        // Un autre bulletin du même votant, ou l'heure de clôture, a pu passer entre la
+        // préparation et l'enregistrement : le calendrier suivi, la préparation refaite
+        // donne alors le motif du refus.
+        loop {
+            self.catch_up().await?;
+            let prepared = self.store.read(|voting_machine| prepare(voting_machine, ballot_paper.clone(), &casting)).await?;
+            let (deposit, outcome) = match prepared {
+                Ok(prepared) => prepared,
+                Err(rejection) => return Ok(rejection),
+            };
+
+            if self.store.record_ballot(deposit, &self.schedule, Utc::now()).await? {
+                return Ok(VoteOutcome::Tracked(Box::new(outcome), code));
+            }
+        }
     }
 
     pub async fn vote(&self, vote_form: VoteForm) -> anyhow::Result<VoteOutcome> {
-        let mut store = self.store.write().await;
-        
-        let mut voting_machine = store.get_voting_machine().await?;
+        let (voter, on_behalf_of) = (vote_form.voter.clone(), vote_form.on_behalf_of.clone());
+        self.cast(voter, on_behalf_of, BallotPaper::from(vote_form), VotingMachine::prepare_vote).await
+    }
+
+    pub async fn vote_ranked(&self, vote_form: RankedVoteForm) -> anyhow::Result<VoteOutcome> {
+        let (voter, on_behalf_of) = (vote_form.voter.clone(), vote_form.on_behalf_of.clone());
+        self.cast(voter, on_behalf_of, RankedBallotPaper::from(vote_form), VotingMachine::prepare_ranked).await
+    }
 
-        let outcome = voting_machine.vote(BallotPaper::from(vote_form));
+    pub async fn vote_approval(&self, vote_form: ApprovalVoteForm) -> anyhow::Result<VoteOutcome> {
+        let (voter, on_behalf_of) = (vote_form.voter.clone(), vote_form.on_behalf_of.clone());
+        self.cast(voter, on_behalf_of, ApprovalBallotPaper::from(vote_form), VotingMachine::prepare_approval).await
+    }
+
+    pub async fn vote_graded(&self, vote_form: GradedVoteForm) -> anyhow::Result<VoteOutcome> {
+        let (voter, on_behalf_of) = (vote_form.voter.clone(), vote_form.on_behalf_of.clone());
+        self.cast(voter, on_behalf_of, GradedBallotPaper::from(vote_form), VotingMachine::prepare_graded).await
+    }
 
-        store.put_voting_machine(voting_machine).await?;
+    pub async fn register_proxy(&self, proxy: &str, principal: &str) -> anyhow::Result<Result<(), ProxyRejection>> {
+        self.update(|voting_machine| voting_machine.register_proxy(Voter(proxy.to_string()), Voter(principal.to_string()))).await
+    }
+
+    pub async fn revoke(&self, voter: &str, credential: &str) -> anyhow::Result<VoteOutcome> {
+        self.catch_up().await?;
+        self.store.revoke(&Voter(voter.to_string()), credential).await
+    }
+
+    pub async fn add_candidate(&self, name: &str) -> anyhow::Result<bool> {
+        self.update(|voting_machine| voting_machine.add_candidate(Candidate(name.to_string()))).await
+    }
 
-        Ok(outcome)
+    pub async fn change_phase(&self, to: Phase) -> anyhow::Result<PhaseChange> {
+        self.update(|voting_machine| voting_machine.change_phase(to)).await
+    }
+
+    pub async fn close_round(&self) -> anyhow::Result<Option<RoundOutcome>> {
+        self.update(|voting_machine| voting_machine.close_round()).await
+    }
+
+    /// Déchiffre le dépouillement d'un scrutin chiffré et clos avec la clé privée
+    /// fournie par l'administrateur, qui n'est pas conservée.
+    pub async fn reveal(&self, key: &ElectionKey) -> anyhow::Result<bool> {
+        self.update(|voting_machine| voting_machine.reveal(key)).await
+    }
+
+    /// Ouvre et clôt le scrutin aux heures prévues ; rend la main quand il n'y a plus d'échéance.
+    pub async fn follow_schedule(&self) -> anyhow::Result<()> {
+        loop {
+            self.catch_up().await?;
+            let phase = self.get_phase().await?;
+
+            let deadline = match self.schedule.next_deadline(phase, Utc::now()) {
+                Some(Deadline::Opening(instant)) | Some(Deadline::Closing(instant)) => instant,
+                None => return Ok(()),
+            };
+
+            tokio::time::sleep((deadline - Utc::now()).to_std().unwrap_or_default()).await;
+        }
     }
 
     pub async fn get_voting_machine(&self) -> anyhow::Result<VotingMachine> {
-        let store = self.store.read().await;
-        store.get_voting_machine().await
+        self.store.get_voting_machine().await
+    }
+
+    pub async fn get_phase(&self) -> anyhow::Result<Phase> {
+        self.store.get_phase().await
+    }
+
+    pub async fn get_scoreboard(&self) -> anyhow::Result<Scoreboard> {
+        self.store.scores().await
+    }
+
+    /// Consulte la machine sans la copier, pendant que les votes continuent.
+    pub async fn read<T: Send>(&self, query: impl FnOnce(&VotingMachine) -> T + Send) -> anyhow::Result<T> {
+        self.store.read(query).await
     }
 }
 
@@ -58,14 +298,41 @@ impl<Store: Storage> VotingController<Store> {
 mod tests {
     use std::collections::BTreeMap;
 
-    use crate::{domain::{Score, Scoreboard}, storages::memory::MemoryStore};
+    use crate::{domain::{HeadCounts, Score, Scoreboard}, encryption::HomomorphicTally, storages::memory::MemoryStore};
 
     use super::*;
 
+    fn untracked(outcome: VoteOutcome) -> VoteOutcome {
+        match outcome {
+            VoteOutcome::Tracked(outcome, _) => *outcome,
+            outcome => outcome,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vote_returns_verifiable_tracking_code() -> anyhow::Result<()> {
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine: VotingMachine = VotingMachine::new(candidates).with_phase(Phase::Open);
+        let store = MemoryStore::new(voting_machine).await.expect("probleme lors de l'instanciation de la memoire");
+        let voting_controller = VotingController::new(store);
+
+        let result = voting_controller.vote(VoteForm { voter: String::from("Louis"), on_behalf_of: None, candidate: String::from("Louis") }).await?;
+        let voting_machine = voting_controller.get_voting_machine().await?;
+
+        match result {
+            VoteOutcome::Tracked(_, code) => {
+                assert_eq!(code.0.len(), 14);
+                assert!(voting_machine.verify(&code));
+            }
+            _ => panic!("Expected Tracked"),
+        }
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_accepted_vote() -> anyhow::Result<()> {
         let candidates = vec![Candidate(String::from("Louis"))];
-        let voting_machine: VotingMachine = VotingMachine::new(candidates);
+        let voting_machine: VotingMachine = VotingMachine::new(candidates).with_phase(Phase::Open);
         let store = MemoryStore::new(voting_machine).await.expect("probleme lors de l'instanciation de la memoire");
 
         
@@ -74,6 +341,7 @@ mod tests {
 
         let vote_form =  VoteForm{
             voter: String::from("Louis"),
+            on_behalf_of: None,
             candidate: String::from("Louis")
         };
         let mut correct_scores = BTreeMap::new();
@@ -81,14 +349,15 @@ mod tests {
 
         let correct_scoreboard = Scoreboard{
             
-                scores: correct_scores,
+                scores: correct_scores.clone(),
                 blank_score: Score(0),
                 invalid_score: Score(0),
+                head_counts: Some(HeadCounts { scores: correct_scores, blank_score: Score(0), invalid_score: Score(0) }),
             
         };
         let result = voting_controller.vote(vote_form).await.expect("err lors du vote");
         let voting_machine = voting_controller.get_voting_machine().await.expect("erreur lors de la recuperation");
-        assert_eq!(result,VoteOutcome::AcceptedVote(Voter( String::from("Louis")), Candidate(String::from("Louis"))));
+        assert_eq!(untracked(result),VoteOutcome::AcceptedVote(Voter( String::from("Louis")), Candidate(String::from("Louis"))));
         assert_eq!(correct_scoreboard,voting_machine.get_scoreboard().clone());
         Ok(())
 
@@ -97,13 +366,14 @@ mod tests {
     #[tokio::test]
     async fn test_blank_vote() -> anyhow::Result<()> {
         let candidates = vec![Candidate(String::from("Louis"))];
-        let voting_machine: VotingMachine = VotingMachine::new(candidates);
+        let voting_machine: VotingMachine = VotingMachine::new(candidates).with_phase(Phase::Open);
         let store = MemoryStore::new(voting_machine).await.expect("probleme lors de l'instanciation de la memoire");
         let voting_controller  = VotingController::new(store);
 
 
         let vote_form =  VoteForm{
             voter: String::from("Louis"),
+            on_behalf_of: None,
             candidate: String::from("")
         };
 		
@@ -112,14 +382,15 @@ mod tests {
 
         let correct_scoreboard = Scoreboard{
             
-                scores: correct_scores,
+                scores: correct_scores.clone(),
                 blank_score: Score(1),
                 invalid_score: Score(0),
+                head_counts: Some(HeadCounts { scores: correct_scores, blank_score: Score(1), invalid_score: Score(0) }),
             
         };
         let result = voting_controller.vote(vote_form).await.expect("err lors du vote");
         let voting_machine = voting_controller.get_voting_machine().await.expect("erreur lors de la recuperation");
-        assert_eq!(result,VoteOutcome::BlankVote(Voter( String::from("Louis"))));
+        assert_eq!(untracked(result),VoteOutcome::BlankVote(Voter( String::from("Louis"))));
         assert_eq!(correct_scoreboard,voting_machine.get_scoreboard().clone());
         Ok(())
 
@@ -128,13 +399,14 @@ mod tests {
     #[tokio::test]
     async fn test_null_vote() -> anyhow::Result<()> {
         let candidates = vec![Candidate(String::from("Louis"))];
-        let voting_machine: VotingMachine = VotingMachine::new(candidates);
+        let voting_machine: VotingMachine = VotingMachine::new(candidates).with_phase(Phase::Open);
         let store = MemoryStore::new(voting_machine).await.expect("probleme lors de l'instanciation de la memoire");
         let voting_controller: VotingController<_>  = VotingController::new(store);
 
 
         let vote_form =  VoteForm{
             voter: String::from("Louis"),
+            on_behalf_of: None,
             candidate: String::from("Jeane oscour")
         };
         let mut correct_scores = BTreeMap::new();
@@ -142,14 +414,15 @@ mod tests {
 
         let correct_scoreboard = Scoreboard{
             
-                scores: correct_scores,
+                scores: correct_scores.clone(),
                 blank_score: Score(0),
                 invalid_score: Score(1),
+                head_counts: Some(HeadCounts { scores: correct_scores, blank_score: Score(0), invalid_score: Score(1) }),
             
         };
         let result = voting_controller.vote(vote_form).await.expect("err lors du vote");
         let voting_machine = voting_controller.get_voting_machine().await.expect("erreur lors de la recuperation");
-        assert_eq!(result,VoteOutcome::InvalidVote(Voter( String::from("Louis"))));
+        assert_eq!(untracked(result),VoteOutcome::InvalidVote(Voter( String::from("Louis"))));
         assert_eq!(correct_scoreboard,voting_machine.get_scoreboard().clone());
         Ok(())
     }
@@ -158,13 +431,14 @@ mod tests {
     #[tokio::test]
     async fn test_has_already_voted() -> anyhow::Result<()> {
         let candidates = vec![Candidate(String::from("Louis"))];
-        let voting_machine: VotingMachine = VotingMachine::new(candidates.clone());
+        let voting_machine: VotingMachine = VotingMachine::new(candidates.clone()).with_phase(Phase::Open);
         let store = MemoryStore::new(voting_machine).await.expect("probleme lors de l'instanciation de la memoire");
         let voting_controller  = VotingController::new(store);
 
 
         let vote_form =  VoteForm{
             voter: String::from("Louis"),
+            on_behalf_of: None,
             candidate: String::from("Jeane oscour")
         };
         let mut correct_scores = BTreeMap::new();
@@ -172,9 +446,10 @@ mod tests {
 
         let correct_scoreboard = Scoreboard{
             
-                scores: correct_scores,
+                scores: correct_scores.clone(),
                 blank_score: Score(0),
                 invalid_score: Score(1),
+                head_counts: Some(HeadCounts { scores: correct_scores, blank_score: Score(0), invalid_score: Score(1) }),
             
         };
         voting_controller.vote(vote_form.clone()).await.expect("err lors du vote");
@@ -185,4 +460,123 @@ mod tests {
         assert_eq!(correct_scoreboard,voting_machine.get_scoreboard().clone());
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_ranked_vote() -> anyhow::Result<()> {
+        let candidates = vec![Candidate(String::from("Louis")), Candidate(String::from("Louise"))];
+        let voting_machine: VotingMachine = VotingMachine::new(candidates).with_phase(Phase::Open);
+        let store = MemoryStore::new(voting_machine).await.expect("probleme lors de l'instanciation de la memoire");
+        let voting_controller  = VotingController::new(store);
+
+        let vote_form = RankedVoteForm{
+            voter: String::from("Tux"),
+            on_behalf_of: None,
+            candidates: vec![String::from("Louise"), String::from("Louis")]
+        };
+
+        let result = voting_controller.vote_ranked(vote_form).await.expect("err lors du vote");
+        let voting_machine = voting_controller.get_voting_machine().await.expect("erreur lors de la recuperation");
+        assert_eq!(untracked(result),VoteOutcome::AcceptedRanking(Voter(String::from("Tux")), vec![Candidate(String::from("Louise")), Candidate(String::from("Louis"))]));
+        assert_eq!(voting_machine.get_scoreboard().scores[&Candidate(String::from("Louise"))], Score(1));
+        assert_eq!(voting_machine.get_ballots().len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_vote_refused_once_closed() -> anyhow::Result<()> {
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine: VotingMachine = VotingMachine::new(candidates);
+        let store = MemoryStore::new(voting_machine).await.expect("probleme lors de l'instanciation de la memoire");
+        let voting_controller  = VotingController::new(store);
+
+        assert_eq!(voting_controller.change_phase(Phase::Open).await?, PhaseChange::Changed(Phase::Open));
+        assert_eq!(voting_controller.change_phase(Phase::Closed).await?, PhaseChange::Changed(Phase::Closed));
+
+        let vote_form =  VoteForm{
+            voter: String::from("Louis"),
+            on_behalf_of: None,
+            candidate: String::from("Louis")
+        };
+        let result = voting_controller.vote(vote_form).await.expect("err lors du vote");
+        let voting_machine = voting_controller.get_voting_machine().await.expect("erreur lors de la recuperation");
+        assert_eq!(result,VoteOutcome::ElectionClosed(Voter(String::from("Louis"))));
+        assert_eq!(voting_machine.get_phase(), Phase::Closed);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_vote_follows_schedule() -> anyhow::Result<()> {
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine: VotingMachine = VotingMachine::new(candidates);
+        let store = MemoryStore::new(voting_machine).await.expect("probleme lors de l'instanciation de la memoire");
+        let schedule = Schedule {
+            opens_at: Some(Utc::now() - chrono::Duration::hours(2)),
+            closes_at: Some(Utc::now() - chrono::Duration::hours(1)),
+        };
+        let voting_controller  = VotingController::new(store).with_schedule(schedule);
+
+        let vote_form =  VoteForm{
+            voter: String::from("Louis"),
+            on_behalf_of: None,
+            candidate: String::from("Louis")
+        };
+        let result = voting_controller.vote(vote_form).await.expect("err lors du vote");
+        assert_eq!(result,VoteOutcome::ElectionClosed(Voter(String::from("Louis"))));
+
+        let voting_machine: VotingMachine = VotingMachine::new(vec![Candidate(String::from("Louis"))]);
+        let store = MemoryStore::new(voting_machine).await.expect("probleme lors de l'instanciation de la memoire");
+        let schedule = Schedule {
+            opens_at: Some(Utc::now() - chrono::Duration::hours(1)),
+            closes_at: None,
+        };
+        let voting_controller = VotingController::new(store).with_schedule(schedule);
+        let vote_form =  VoteForm{
+            voter: String::from("Louis"),
+            on_behalf_of: None,
+            candidate: String::from("Louis")
+        };
+        let result = voting_controller.vote(vote_form).await.expect("err lors du vote");
+        assert_eq!(untracked(result),VoteOutcome::AcceptedVote(Voter(String::from("Louis")), Candidate(String::from("Louis"))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_follow_schedule_closes_at_deadline() -> anyhow::Result<()> {
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine: VotingMachine = VotingMachine::new(candidates).with_phase(Phase::Open);
+        let store = MemoryStore::new(voting_machine).await.expect("probleme lors de l'instanciation de la memoire");
+        let schedule = Schedule {
+            opens_at: None,
+            closes_at: Some(Utc::now() + chrono::Duration::milliseconds(50)),
+        };
+        let voting_controller  = VotingController::new(store).with_schedule(schedule);
+
+        voting_controller.follow_schedule().await?;
+
+        let voting_machine = voting_controller.get_voting_machine().await.expect("erreur lors de la recuperation");
+        assert_eq!(voting_machine.get_phase(), Phase::Closed);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_tally_is_revealed_with_key() -> anyhow::Result<()> {
+        let key = ElectionKey::generate();
+        let candidates = vec![Candidate(String::from("Louis"))];
+        let voting_machine = VotingMachine::new(candidates.clone())
+            .with_phase(Phase::Open)
+            .with_encryption(Some(HomomorphicTally::new(key.public_key(), &candidates)));
+        let store = MemoryStore::new(voting_machine).await?;
+        let voting_controller = VotingController::new(store);
+
+        let outcome = voting_controller.vote(VoteForm { voter: String::from("Tux"), on_behalf_of: None, candidate: String::from("Louis") }).await?;
+        voting_controller.change_phase(Phase::Closed).await?;
+        let closed = voting_controller.get_voting_machine().await?;
+
+        assert_eq!(untracked(outcome), VoteOutcome::BallotReceived(Voter(String::from("Tux"))));
+        assert!(!closed.results_visible());
+        assert!(!voting_controller.reveal(&ElectionKey::generate()).await?);
+        assert!(voting_controller.reveal(&key).await?);
+        assert_eq!(voting_controller.get_scoreboard().await?.scores[&Candidate(String::from("Louis"))], Score(1));
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeSet as Set;
+
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+use crate::domain::{ElectoralRoll, Voter};
+
+/// Caractères des codes, sans les lettres et chiffres faciles à confondre.
+const ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// Tire un code aléatoire de trois groupes de quatre caractères.
+pub fn random_code() -> String {
+    let mut rng = rand::thread_rng();
+    let groups: Vec<String> = (0..3)
+        .map(|_| (0..4).map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char).collect())
+        .collect();
+
+    groups.join("-")
+}
+
+/// Empreinte d'un code de vote : la machine ne conserve jamais les codes en clair.
+pub fn digest(code: &str) -> String {
+    hex::encode(Sha256::digest(code.trim().to_uppercase().as_bytes()))
+}
+
+/// Codes de vote à usage unique : les empreintes émises et celles déjà utilisées.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct CodeSheet {
+    pub issued: Set<String>,
+    pub consumed: Set<String>,
+}
+
+/// Motif de refus d'un code de vote.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CodeRejection {
+    Unknown,
+    AlreadyUsed,
+}
+
+impl CodeSheet {
+    pub fn issue(codes: &[String]) -> Self {
+        Self {
+            issued: codes.iter().map(|code| digest(code)).collect(),
+            consumed: Set::new(),
+        }
+    }
+
+    /// Vérifie que le code d'empreinte `digest` a été émis et n'a pas déjà servi.
+    pub fn check(&self, digest: &str) -> Result<(), CodeRejection> {
+        if !self.issued.contains(digest) {
+            return Err(CodeRejection::Unknown);
+        }
+        if self.consumed.contains(digest) {
+            return Err(CodeRejection::AlreadyUsed);
+        }
+
+        Ok(())
+    }
+
+    /// Marque le code comme utilisé, s'il a été émis et ne l'a pas déjà été.
+    pub fn consume(&mut self, code: &str) -> Result<(), CodeRejection> {
+        let code = digest(code);
+
+        self.check(&code)?;
+        self.consumed.insert(code);
+        Ok(())
+    }
+}
+
+/// Tire un code par électeur inscrit et écrit la liste à imprimer au format CSV.
+/// Seules les empreintes des codes sont rendues à la machine.
+pub async fn issue_codes(roll: &ElectoralRoll, path: &str) -> anyhow::Result<CodeSheet> {
+    let codes: Vec<(Voter, String)> = roll.0.keys().map(|voter| (voter.clone(), random_code())).collect();
+
+    let mut csv = String::from("voter,code\n");
+    for (voter, code) in &codes {
+        csv.push_str(&format!("{},{}\n", voter.0, code));
+    }
+    fs::write(path, csv).await?;
+
+    let codes: Vec<String> = codes.into_iter().map(|(_, code)| code).collect();
+    Ok(CodeSheet::issue(&codes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_is_consumed_once() {
+        let mut sheet = CodeSheet::issue(&[String::from("ABCD-EFGH-JKLM")]);
+
+        assert_eq!(sheet.consume("WXYZ-WXYZ-WXYZ"), Err(CodeRejection::Unknown));
+        assert_eq!(sheet.consume("abcd-efgh-jklm"), Ok(()));
+        assert_eq!(sheet.consume("ABCD-EFGH-JKLM"), Err(CodeRejection::AlreadyUsed));
+    }
+
+    #[tokio::test]
+    async fn test_issue_codes_writes_csv_for_roll() {
+        let path = std::env::temp_dir().join("c1_test_codes.csv");
+        let roll = ElectoralRoll::from_voters([Voter(String::from("Alice")), Voter(String::from("Bob"))]);
+
+        let mut sheet = issue_codes(&roll, path.to_str().unwrap()).await.expect("emission impossible");
+        let csv = fs::read_to_string(&path).await.expect("lecture impossible");
+        let (_, code) = csv.lines().nth(1).and_then(|line| line.split_once(',')).expect("ligne absente");
+
+        assert_eq!(csv.lines().count(), 3);
+        assert!(!sheet.issued.contains(code));
+        assert_eq!(sheet.consume(code), Ok(()));
+    }
+}